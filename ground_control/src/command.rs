@@ -169,6 +169,12 @@ impl CommandScheduler {
             let urgent = (scheduled.command.priority as u8) <= 1;
             let deadline = scheduled.command.deadline;
 
+            // an invalid command won't become valid by retrying: drop it
+            if let Err(e) = scheduled.command.validate() {
+                warn!("Dropping invalid command {}: {}", scheduled.command.command_id, e);
+                continue;
+            }
+
             let send_res = network
                 .send_packet_with_deadline_check(
                     CommunicationPacket::new_command(scheduled.command.clone(), Source::GroundControl),
@@ -998,6 +998,7 @@ impl FaultSimulator {
             recommended_actions: actions,
             auto_recovery_attempted: severity <= 2,
             timestamp: Utc::now(),
+            metadata: HashMap::new(),
         }
     }
 
@@ -1039,6 +1040,7 @@ impl FaultSimulator {
             recommended_actions: actions,
             auto_recovery_attempted: severity <= 1,
             timestamp: Utc::now(),
+            metadata: HashMap::new(),
         }
     }
 
@@ -1080,6 +1082,7 @@ impl FaultSimulator {
             recommended_actions: actions,
             auto_recovery_attempted: severity <= 2,
             timestamp: Utc::now(),
+            metadata: HashMap::new(),
         }
     }
 
@@ -1121,6 +1124,7 @@ impl FaultSimulator {
             recommended_actions: actions,
             auto_recovery_attempted: severity >= 1,
             timestamp: Utc::now(),
+            metadata: HashMap::new(),
         }
     }
 
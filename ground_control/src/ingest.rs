@@ -0,0 +1,98 @@
+// src/ingest.rs
+// Decode what the satellite sends: UDP datagrams → (reassembly) → AEAD open → payload callbacks
+
+use shared_protocol::{
+    Capabilities, CommandAcknowledgment, CommunicationPacket, CryptoContext, CryptoError, EmergencyData, PacketHeader,
+    PacketPayload, Reassembler, SensorReading, SystemHealth,
+};
+use tokio::net::UdpSocket;
+
+/// Callbacks for decoded packets; implement only the ones you need.
+pub trait IngestSink: Send {
+    fn on_telemetry(&mut self, _header: &PacketHeader, _readings: Vec<SensorReading>) {}
+    fn on_ack(&mut self, _header: &PacketHeader, _ack: CommandAcknowledgment) {}
+    fn on_emergency(&mut self, _header: &PacketHeader, _alert: EmergencyData) {}
+    fn on_heartbeat(&mut self, _header: &PacketHeader, _health: SystemHealth) {}
+    /// The satellite's capability advertisement (answering it needs a send path).
+    fn on_handshake(&mut self, _header: &PacketHeader, _offer: Capabilities) {}
+    /// Frames that failed to open (auth, replay, malformed); the loop keeps going.
+    fn on_error(&mut self, _error: CryptoError) {}
+}
+
+/// Route one opened packet to the matching callback. Commands and config updates are
+/// ground → satellite traffic and are ignored here.
+pub fn dispatch(pkt: CommunicationPacket, sink: &mut impl IngestSink) {
+    let header = pkt.header;
+    match pkt.payload {
+        PacketPayload::TelemetryData(mut readings) => {
+            readings.iter_mut().for_each(SensorReading::expand); // undo the satellite's compact()
+            sink.on_telemetry(&header, readings)
+        }
+        PacketPayload::AcknowledgmentData(ack) => sink.on_ack(&header, ack),
+        PacketPayload::EmergencyAlert(alert) => sink.on_emergency(&header, alert),
+        PacketPayload::HeartbeatData(health) => sink.on_heartbeat(&header, health),
+        PacketPayload::Handshake(offer) => sink.on_handshake(&header, offer),
+        PacketPayload::CommandData(_) | PacketPayload::ConfigUpdate(_) => {}
+    }
+}
+
+/// Receive, reassemble, decrypt and dispatch until the socket fails.
+pub async fn receive_loop(
+    socket: &UdpSocket,
+    crypto: &CryptoContext,
+    sink: &mut impl IngestSink,
+) -> std::io::Result<()> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut reassembler = Reassembler::default();
+    loop {
+        let n = socket.recv(&mut buf).await?;
+        let Some(frame) = reassembler.push(&buf[..n]) else {
+            continue; // waiting for more fragments
+        };
+        match crypto.open_from_bytes(&frame) {
+            Ok(pkt) => dispatch(pkt, sink),
+            Err(e) => sink.on_error(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::{DEFAULT_REPLAY_WINDOW, Source, ThermalSensor};
+    use tokio::sync::mpsc;
+    use tokio::time::{Duration, timeout};
+
+    struct Forward(mpsc::UnboundedSender<Vec<SensorReading>>);
+
+    impl IngestSink for Forward {
+        fn on_telemetry(&mut self, _header: &PacketHeader, readings: Vec<SensorReading>) {
+            let _ = self.0.send(readings);
+        }
+    }
+
+    #[tokio::test]
+    async fn telemetry_batch_arrives_decoded_over_loopback() {
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+
+        let sat_crypto = CryptoContext::new(1, [7u8; 32], DEFAULT_REPLAY_WINDOW);
+        let ground_crypto = CryptoContext::new(1, [7u8; 32], DEFAULT_REPLAY_WINDOW);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let rx_task = tokio::spawn(async move {
+            let mut sink = Forward(tx);
+            receive_loop(&ground, &ground_crypto, &mut sink).await
+        });
+
+        let thermal = ThermalSensor::new(1, "CPU");
+        let readings: Vec<_> = (0..5).map(|i| thermal.create_reading(61.0 + i as f64, i)).collect();
+        let pkt = CommunicationPacket::new_telemetry(readings.clone(), Source::Satellite);
+        sat.send(&sat_crypto.seal_to_bytes(&pkt).unwrap()).await.unwrap();
+
+        let got = timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(got, readings);
+        rx_task.abort();
+    }
+}
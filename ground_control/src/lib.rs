@@ -0,0 +1,6 @@
+// src/lib.rs
+// Reusable ground-side pieces (the binary in main.rs wires up the full system)
+
+pub mod ingest;
+pub mod liveness;
+pub mod retransmit;
@@ -0,0 +1,87 @@
+// src/liveness.rs
+// Loss-of-signal detection: the satellite heartbeats at a fixed interval, so silence
+// longer than a few intervals means the link (or the OCS) is gone.
+
+use std::time::{Duration, Instant};
+
+/// Heartbeats the monitor waits out before declaring loss of signal.
+pub const DEFAULT_TIMEOUT_FACTOR: u32 = 3;
+
+/// A change in link liveness; each is reported once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LivenessEvent {
+    /// No heartbeat for `silent_for` (longer than the timeout).
+    Lost { silent_for: Duration },
+    /// A heartbeat arrived after an outage of `outage`.
+    Restored { outage: Duration },
+}
+
+#[derive(Debug)]
+pub struct LivenessMonitor {
+    timeout: Duration,
+    last_heartbeat: Instant,
+    lost: bool,
+    alerts: u64,
+}
+
+impl LivenessMonitor {
+    /// Armed from `now`, so a satellite that never heartbeats is reported too.
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        Self { timeout, last_heartbeat: now, lost: false, alerts: 0 }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Loss-of-signal alerts raised so far.
+    pub fn alerts(&self) -> u64 {
+        self.alerts
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost
+    }
+
+    /// A heartbeat arrived; clears an active alert.
+    pub fn heartbeat(&mut self, now: Instant) -> Option<LivenessEvent> {
+        let outage = now.saturating_duration_since(self.last_heartbeat);
+        self.last_heartbeat = now;
+        std::mem::take(&mut self.lost).then_some(LivenessEvent::Restored { outage })
+    }
+
+    /// Periodic check; raises the alert once when the timeout has passed in silence.
+    pub fn check(&mut self, now: Instant) -> Option<LivenessEvent> {
+        let silent_for = now.saturating_duration_since(self.last_heartbeat);
+        if self.lost || silent_for <= self.timeout {
+            return None;
+        }
+        self.lost = true;
+        self.alerts += 1;
+        Some(LivenessEvent::Lost { silent_for })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn withheld_heartbeats_raise_one_alert_and_resuming_clears_it() {
+        let ms = Duration::from_millis;
+        let t0 = Instant::now();
+        let mut m = LivenessMonitor::new(ms(300), t0);
+
+        assert_eq!(m.heartbeat(t0 + ms(100)), None);
+        assert_eq!(m.check(t0 + ms(350)), None, "250 ms of silence is within the timeout");
+        assert_eq!(m.check(t0 + ms(450)), Some(LivenessEvent::Lost { silent_for: ms(350) }));
+        assert_eq!(m.check(t0 + ms(900)), None, "alert is raised once");
+        assert!(m.is_lost());
+
+        assert_eq!(m.heartbeat(t0 + ms(1000)), Some(LivenessEvent::Restored { outage: ms(900) }));
+        assert!(!m.is_lost());
+        assert_eq!(m.check(t0 + ms(1200)), None);
+        assert_eq!(m.check(t0 + ms(1400)), Some(LivenessEvent::Lost { silent_for: ms(400) }));
+        assert_eq!(m.alerts(), 2);
+    }
+}
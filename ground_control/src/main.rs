@@ -607,6 +607,30 @@ impl GroundControlSystem {
             })
         };
 
+        // --- Task 9: heartbeat liveness (loss-of-signal alert) ---
+        let _liveness_task = {
+            let network_manager = Arc::clone(&self.network_manager);
+            let fault_tx_liveness = fault_tx.clone();
+            tokio::spawn(async move {
+                let timeout = network_manager.liveness_timeout().await;
+                info!("Liveness monitor started (heartbeat timeout {}ms)", timeout.as_millis());
+                let mut itv = interval(timeout / 4);
+                loop {
+                    itv.tick().await;
+                    if let Some(ground_control::liveness::LivenessEvent::Lost { silent_for }) = network_manager.check_liveness().await {
+                        error!("LOSS OF SIGNAL: no heartbeat for {:.1}s", silent_for.as_secs_f64());
+                        let _ = fault_tx_liveness.send(fault::FaultEvent {
+                            timestamp: Utc::now(),
+                            fault_type: fault::FaultType::CommunicationLoss,
+                            severity: fault::Severity::High,
+                            description: format!("No satellite heartbeat for {}ms", silent_for.as_millis()),
+                            affected_systems: vec!["communication".into()],
+                        }).await;
+                    }
+                }
+            })
+        };
+
         info!("All tasks started. Ground Control operational.");
 
         // Run tasks
@@ -651,6 +675,7 @@ impl GroundControlSystem {
         let perf = self.performance_tracker.lock().await;
         let final_stats = perf.get_current_stats();
         let drift_stats = self.network_manager.get_drift_stats().await;
+        let (packets_lost, loss_pct) = self.network_manager.get_loss_stats().await;
         let fault_stats = self.fault_manager.lock().await.get_stats();
 
         info!("=== FINAL GROUND CONTROL STATS ===");
@@ -664,6 +689,7 @@ impl GroundControlSystem {
         info!("Reception drift violations: {}", final_stats.reception_drift_violations);
         info!("Retransmissions: {}", final_stats.retransmission_requests);
         info!("Network timeouts: {}", final_stats.network_timeouts);
+        info!("Packets lost (sequence gaps): {} ({:.2}%)", packets_lost, loss_pct);
         info!("Faults handled: {} | Critical active: {}", fault_stats.total_faults_detected, fault_stats.active_faults_count);
         info!("System health score: {:.1}/100 | Uptime {:.2}%", final_stats.system_health_score, final_stats.uptime_percentage);
     }
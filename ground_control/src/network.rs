@@ -12,11 +12,17 @@ use anyhow::{Result, Context};
 use chrono::{DateTime, Utc};
 use std::collections::{HashMap, VecDeque};
 
+use ground_control::liveness::{LivenessEvent, LivenessMonitor, DEFAULT_TIMEOUT_FACTOR};
 use shared_protocol::{
     CommunicationPacket,
     Command,
     CryptoContext,
     EncryptedFrame,
+    Fragmenter,
+    LossTracker,
+    PacketPayload,
+    Reassembler,
+    SensorReading,
     SensorType,
     Source,
 };
@@ -26,6 +32,18 @@ const KEY_ID: u8 = 1;
 const KEY_HEX: &str =
     "0000000000000000000000000000000000000000000000000000000000000007";
 
+/// Satellite heartbeat period (its --heartbeat-ms default).
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Silence before loss of signal: GC_LIVENESS_TIMEOUT_MS, else 3 heartbeat intervals.
+fn liveness_timeout() -> Duration {
+    std::env::var("GC_LIVENESS_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(HEARTBEAT_INTERVAL * DEFAULT_TIMEOUT_FACTOR)
+}
+
 /// Reception timing data for performance tracking
 #[derive(Debug, Clone)]
 pub struct ReceptionTiming {
@@ -74,6 +92,16 @@ pub struct NetworkManager {
 
     // crypto (shared with satellite)
     crypto: CryptoContext,
+
+    // UDP fragmentation of frames larger than one datagram
+    fragmenter: Fragmenter,
+    reassembler: Mutex<Reassembler>,
+
+    // sequence-number gaps → packet loss per source
+    loss: Mutex<LossTracker>,
+
+    // heartbeat silence → loss of signal
+    liveness: Mutex<LivenessMonitor>,
 }
 
 #[derive(Debug, Clone)]
@@ -140,7 +168,7 @@ impl NetworkManager {
             .context("GC: failed to decode key hex")?;
         let key_array: [u8; 32] = key_bytes.try_into()
             .map_err(|_| anyhow::anyhow!("GC: key must be 32 bytes"))?;
-        let crypto = CryptoContext::new(KEY_ID, key_array);
+        let crypto = CryptoContext::new(KEY_ID, key_array, shared_protocol::DEFAULT_REPLAY_WINDOW);
 
         info!("Network Manager initialized - Local: {}, Satellite: {}",
             local_addr, satellite_addr);
@@ -169,6 +197,10 @@ impl NetworkManager {
             drift_history: Arc::new(Mutex::new(VecDeque::with_capacity(1000))),
             expected_schedule: Arc::new(Mutex::new(HashMap::new())),
             crypto,
+            fragmenter: Fragmenter::default(),
+            reassembler: Mutex::new(Reassembler::default()),
+            loss: Mutex::new(LossTracker::default()),
+            liveness: Mutex::new(LivenessMonitor::new(liveness_timeout(), Instant::now())),
         })
     }
 
@@ -179,13 +211,20 @@ impl NetworkManager {
         // Buffer for receiving data (sat sends: [len:4][EncryptedFrame JSON...])
         let mut buffer = vec![0u8; shared_protocol::MAX_PACKET_SIZE + 4];
 
-        // Receive with timeout
-        let (bytes_received, sender_addr) = timeout(
-            self.receive_timeout,
-            self.socket.recv_from(&mut buffer)
-        ).await
-        .context("Receive timeout")?
-        .context("Failed to receive packet")?;
+        // Receive with timeout; fragments are collected until a whole frame is in
+        let (frame_buf, bytes_received, sender_addr) = loop {
+            let (n, from) = timeout(
+                self.receive_timeout,
+                self.socket.recv_from(&mut buffer)
+            ).await
+            .context("Receive timeout")?
+            .context("Failed to receive packet")?;
+
+            if let Some(frame) = self.reassembler.lock().await.push(&buffer[..n]) {
+                let len = frame.len();
+                break (frame, len, from);
+            }
+        };
 
         let reception_time = Utc::now();
         let reception_latency = receive_start.elapsed().as_secs_f64() * 1000.0;
@@ -202,20 +241,19 @@ impl NetworkManager {
         }
 
         // ---- Deframe: length prefix (big-endian u32), then JSON EncryptedFrame
-        if bytes_received < 4 {
-            return Err(anyhow::anyhow!("short UDP frame ({} bytes)", bytes_received));
-        }
-        let len = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
-        if bytes_received < 4 + len {
+        let total = shared_protocol::framed_len(&frame_buf[..bytes_received])
+            .map_err(|e| anyhow::anyhow!("bad frame length: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("short UDP frame ({} bytes)", bytes_received))?;
+        if bytes_received < total {
             return Err(anyhow::anyhow!(
                 "incomplete framed payload: got {}, want {}",
-                bytes_received, 4 + len
+                bytes_received, total
             ));
         }
-        let frame_bytes = &buffer[4..4 + len];
+        let frame_bytes = &frame_buf[4..total];
 
         // Optional: inspect header fields for logs
-        if let Ok(frame) = serde_json::from_slice::<EncryptedFrame>(frame_bytes) {
+        if let Ok(frame) = EncryptedFrame::from_bytes(frame_bytes) {
             debug!(
                 event = "rx_frame",
                 pkt_type = ?frame.header.packet_type,
@@ -230,8 +268,12 @@ impl NetworkManager {
 
         // ---- Decrypt (measure decode time too)
         let decode_start = Instant::now();
-        let packet = self.crypto.open_from_bytes(frame_bytes)
+        let mut packet = self.crypto.open_from_bytes(&frame_buf[..total])
             .map_err(|e| anyhow::anyhow!("decrypt/open failed: {}", e))?;
+        // telemetry arrives compacted: rebuild the fields the satellite left out
+        if let PacketPayload::TelemetryData(readings) = &mut packet.payload {
+            readings.iter_mut().for_each(SensorReading::expand);
+        }
         let decode_time_ms = decode_start.elapsed().as_secs_f64() * 1000.0;
 
         // Calculate timing metrics WITH decode time
@@ -246,6 +288,36 @@ impl NetworkManager {
             );
         }
 
+        // Sequence gaps = frames lost on the link
+        {
+            let mut loss = self.loss.lock().await;
+            if let Some(gap) = loss.observe(packet.header.source, packet.header.sequence_number) {
+                warn!(
+                    "PACKET LOSS: {} packet(s) missing from {:?} after seq {} (loss {:.2}%)",
+                    gap.missing, gap.source, gap.after, loss.loss_pct(gap.source)
+                );
+            }
+        }
+
+        // Capability handshake: settle on a common set, then answer with our advertisement
+        // (sent even on failure, so the satellite reports the mismatch too)
+        if let PacketPayload::Handshake(offer) = &packet.payload {
+            match self.crypto.negotiate(offer) {
+                Ok(n) => info!("HANDSHAKE: negotiated {:?}", n),
+                Err(e) => error!("HANDSHAKE FAILED: {}", e),
+            }
+            let reply = CommunicationPacket::new_handshake(self.crypto.capabilities().clone(), Source::GroundControl);
+            if let Err(e) = self.send_packet(reply).await {
+                warn!("handshake reply failed: {}", e);
+            }
+        }
+
+        if matches!(packet.payload, PacketPayload::HeartbeatData(_))
+            && let Some(LivenessEvent::Restored { outage }) = self.liveness.lock().await.heartbeat(Instant::now())
+        {
+            info!("SIGNAL RESTORED: heartbeat received after {:.1}s of silence", outage.as_secs_f64());
+        }
+
         // Update statistics
         self.packets_received.fetch_add(1, Ordering::Relaxed);
         self.bytes_received.fetch_add(bytes_received as u64, Ordering::Relaxed);
@@ -380,6 +452,21 @@ impl NetworkManager {
         info!("Updated expected interval for '{}' to {:.1}ms", packet_type, interval_ms);
     }
 
+    /// Loss-of-signal check against the heartbeat timeout; call periodically.
+    pub async fn check_liveness(&self) -> Option<LivenessEvent> {
+        self.liveness.lock().await.check(Instant::now())
+    }
+
+    pub async fn liveness_timeout(&self) -> Duration {
+        self.liveness.lock().await.timeout()
+    }
+
+    /// Reception loss (packets lost, loss %) inferred from the satellite's sequence numbers.
+    pub async fn get_loss_stats(&self) -> (u64, f64) {
+        let loss = self.loss.lock().await;
+        (loss.lost(Source::Satellite), loss.loss_pct(Source::Satellite))
+    }
+
     /// Get current reception drift statistics
     pub async fn get_drift_stats(&self) -> DriftStats {
         let drift_history = self.drift_history.lock().await;
@@ -444,6 +531,20 @@ impl NetworkManager {
         report
     }
 
+    /// Send a sealed frame, fragmented to MTU-sized datagrams when needed.
+    /// Returns the frame bytes delivered to the socket.
+    async fn send_frame(&self, frame: &[u8]) -> std::io::Result<usize> {
+        let datagrams = self.fragmenter.fragment(frame)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        for d in &datagrams {
+            let n = self.socket.send_to(d, self.satellite_address).await?;
+            if n != d.len() {
+                return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "short datagram send"));
+            }
+        }
+        Ok(frame.len())
+    }
+
     /// Sends a packet to the satellite (seal + send framed bytes)
     pub async fn send_packet(&self, packet: CommunicationPacket) -> Result<()> {
         let send_start = Instant::now();
@@ -459,7 +560,7 @@ impl NetworkManager {
 
         let bytes_sent = timeout(
             self.send_timeout,
-            self.socket.send_to(&packet_bytes, self.satellite_address)
+            self.send_frame(&packet_bytes)
         ).await
         .context("Send timeout")?
         .context("Failed to send packet")?;
@@ -518,7 +619,7 @@ impl NetworkManager {
         let network_send_start = Instant::now();
         let bytes_sent = timeout(
             self.send_timeout,
-            self.socket.send_to(&packet_bytes, self.satellite_address)
+            self.send_frame(&packet_bytes)
         ).await
         .context("Send timeout")?
         .context("Failed to send packet")?;
@@ -604,7 +705,8 @@ impl NetworkManager {
             "thermal" => SensorType::Thermal,
             "power" => SensorType::Power,
             "attitude" => SensorType::Attitude,
-            other => anyhow::bail!("Invalid sensor type: {other}. Must be 'thermal', 'power', or 'attitude'"),
+            "radiation" => SensorType::Radiation,
+            other => anyhow::bail!("Invalid sensor type: {other}. Must be 'thermal', 'power', 'attitude', or 'radiation'"),
         };
 
         let re_request = Command::re_request_command(sensor_id, sensor_enum, reason);
@@ -0,0 +1,292 @@
+// src/retransmit.rs
+// Command sender that re-sends until the satellite ACKs: every timeout without any ACK
+// for a command_id re-seals `Command::next_retry` (retry_count + 1, deadline pushed back by
+// the command's backoff), up to the policy's or the command's `max_retries`, whichever is lower.
+// Every ACK is also timed against the command's latest transmission (logs/commands.csv).
+
+use chrono::Utc;
+use shared_protocol::{Command, CommandAcknowledgment, CommunicationPacket, CryptoContext, Source};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Where sealed command frames go: a connected UDP socket in the ground tool.
+pub trait FrameSink {
+    fn send_frame(&mut self, frame: &[u8]) -> impl Future<Output = std::io::Result<()>> + Send;
+}
+
+impl FrameSink for UdpSocket {
+    async fn send_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.send(frame).await.map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Wait this long for an ACK before re-sending
+    pub timeout: Duration,
+    /// Re-sends after the first attempt before giving up
+    pub max_retries: u8,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { timeout: Duration::from_millis(500), max_retries: 3 }
+    }
+}
+
+/// A command that was never acknowledged.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("command {command_id} unacknowledged after {attempts} attempts")]
+pub struct GaveUp {
+    pub command_id: String,
+    pub attempts: u32,
+}
+
+struct Outstanding {
+    command: Command,
+    last_sent: Instant,
+}
+
+pub const DEFAULT_RTT_LOG: &str = "logs/commands.csv";
+
+pub struct CommandSender<S> {
+    sink: S,
+    crypto: Arc<CryptoContext>,
+    policy: RetryPolicy,
+    outstanding: HashMap<String, Outstanding>,
+    /// Latest transmission per command, kept until its completed/failed ACK
+    sent_at: HashMap<String, Instant>,
+    rtt_log: PathBuf,
+}
+
+impl<S: FrameSink> CommandSender<S> {
+    pub fn new(sink: S, crypto: Arc<CryptoContext>, policy: RetryPolicy) -> Self {
+        Self {
+            sink,
+            crypto,
+            policy,
+            outstanding: HashMap::new(),
+            sent_at: HashMap::new(),
+            rtt_log: PathBuf::from(DEFAULT_RTT_LOG),
+        }
+    }
+
+    /// Write round-trip rows somewhere other than `DEFAULT_RTT_LOG`.
+    pub fn with_rtt_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rtt_log = path.into();
+        self
+    }
+
+    /// Send and start tracking `command` (its retry_count is reset to 0).
+    pub async fn send(&mut self, mut command: Command) -> Result<(), String> {
+        command.retry_count = 0;
+        self.transmit(&command).await?;
+        let now = Instant::now();
+        self.sent_at.insert(command.command_id.clone(), now);
+        self.outstanding.insert(
+            command.command_id.clone(),
+            Outstanding { command, last_sent: now },
+        );
+        Ok(())
+    }
+
+    /// Any ACK (received/executing/completed/failed) ends retransmission for its id.
+    /// Returns the round-trip time (ms) since the latest transmission, also appended to
+    /// the commands log, or None for a command this sender isn't tracking.
+    pub fn on_ack(&mut self, ack: &CommandAcknowledgment) -> Option<f64> {
+        let rtt_ms = self
+            .sent_at
+            .get(&ack.command_id)
+            .map(|sent| Instant::now().duration_since(*sent).as_secs_f64() * 1000.0);
+        if matches!(ack.status.as_str(), "completed" | "failed") {
+            self.sent_at.remove(&ack.command_id);
+        }
+        if self.outstanding.remove(&ack.command_id).is_some() {
+            info!(cmd_id = %ack.command_id, status = %ack.status, "command acknowledged");
+        }
+
+        let rtt_ms = rtt_ms?;
+        info!(cmd_id = %ack.command_id, status = %ack.status, rtt_ms = format_args!("{rtt_ms:.3}"), "command round trip");
+        if let Err(e) = self.log_rtt(&ack.command_id, &ack.status, rtt_ms) {
+            warn!(error = %e, "commands log write failed");
+        }
+        Some(rtt_ms)
+    }
+
+    /// commands.csv: ts,command_id,status,rtt_ms
+    fn log_rtt(&self, command_id: &str, status: &str, rtt_ms: f64) -> std::io::Result<()> {
+        if let Some(dir) = self.rtt_log.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let fresh = !self.rtt_log.exists();
+        let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&self.rtt_log)?;
+        if fresh {
+            writeln!(f, "ts,command_id,status,rtt_ms")?;
+        }
+        writeln!(f, "{},{},{},{:.3}", Utc::now().to_rfc3339(), command_id, status, rtt_ms)
+    }
+
+    /// Re-send every command whose ACK is overdue at `now`; commands out of retries are
+    /// dropped and returned.
+    pub async fn poll(&mut self, now: Instant) -> Vec<GaveUp> {
+        let due: Vec<String> = self
+            .outstanding
+            .iter()
+            .filter(|(_, o)| now.duration_since(o.last_sent) >= self.policy.timeout)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut gave_up = Vec::new();
+        for id in due {
+            let Some(mut o) = self.outstanding.remove(&id) else { continue };
+            let attempts = o.command.retry_count as u32 + 1;
+            let next = (o.command.retry_count < self.policy.max_retries)
+                .then(|| o.command.clone().next_retry())
+                .flatten();
+            let Some(next) = next else {
+                let e = GaveUp { command_id: id, attempts };
+                warn!(%e, "giving up on command");
+                self.sent_at.remove(&e.command_id);
+                gave_up.push(e);
+                continue;
+            };
+            o.command = next;
+            warn!(cmd_id = %id, retry = o.command.retry_count, "no ACK; retransmitting command");
+            if let Err(e) = self.transmit(&o.command).await {
+                warn!(cmd_id = %id, error = %e, "retransmit failed");
+            }
+            o.last_sent = now;
+            self.sent_at.insert(id.clone(), now);
+            self.outstanding.insert(id, o);
+        }
+        gave_up
+    }
+
+    pub fn outstanding(&self) -> usize {
+        self.outstanding.len()
+    }
+
+    /// A fresh packet each time, so the retry gets its own sequence number (replay window).
+    async fn transmit(&mut self, command: &Command) -> Result<(), String> {
+        command.validate()?;
+        let pkt = CommunicationPacket::new_command(command.clone(), Source::GroundControl);
+        let bytes = self.crypto.seal_to_bytes(&pkt).map_err(|e| e.to_string())?;
+        self.sink.send_frame(&bytes).await.map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::{DEFAULT_REPLAY_WINDOW, PacketPayload};
+    use tokio::time::timeout;
+
+    /// Loses the first frame, forwards the rest.
+    struct DropFirst {
+        sock: UdpSocket,
+        dropped: bool,
+    }
+
+    impl FrameSink for DropFirst {
+        async fn send_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+            if !self.dropped {
+                self.dropped = true;
+                return Ok(());
+            }
+            self.sock.send_frame(frame).await
+        }
+    }
+
+    #[tokio::test]
+    async fn lost_command_is_retransmitted_with_retry_count_one() {
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sock = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sock.connect(sat.local_addr().unwrap()).await.unwrap();
+
+        let crypto = Arc::new(CryptoContext::new(1, [9u8; 32], DEFAULT_REPLAY_WINDOW));
+        let sat_crypto = CryptoContext::new(1, [9u8; 32], DEFAULT_REPLAY_WINDOW);
+        let policy = RetryPolicy { timeout: Duration::from_millis(50), max_retries: 1 };
+        let mut sender = CommandSender::new(DropFirst { sock, dropped: false }, crypto, policy)
+            .with_rtt_log(std::env::temp_dir().join("commands_retry_test.csv"));
+
+        let cmd = Command::thermal_normal_operation(1);
+        sender.send(cmd.clone()).await.unwrap();
+        let t0 = Instant::now();
+        assert!(sender.poll(t0).await.is_empty()); // not due yet: nothing re-sent
+
+        assert!(sender.poll(t0 + Duration::from_millis(60)).await.is_empty());
+        let mut buf = vec![0u8; 64 * 1024];
+        let n = timeout(Duration::from_secs(2), sat.recv(&mut buf)).await.unwrap().unwrap();
+        let PacketPayload::CommandData(got) = sat_crypto.open_from_bytes(&buf[..n]).unwrap().payload else {
+            panic!("expected a command");
+        };
+        assert_eq!(got.command_id, cmd.command_id);
+        assert_eq!(got.retry_count, 1);
+
+        // out of retries: the next timeout gives up instead of re-sending
+        let gave_up = sender.poll(t0 + Duration::from_millis(200)).await;
+        assert_eq!(gave_up, [GaveUp { command_id: cmd.command_id.clone(), attempts: 2 }]);
+        assert_eq!(sender.outstanding(), 0);
+
+        // an ACK stops retransmission
+        let other = Command::thermal_normal_operation(2);
+        sender.send(other.clone()).await.unwrap();
+        sender.on_ack(&CommandAcknowledgment {
+            command_id: other.command_id,
+            status: "received".into(),
+            execution_timestamp: None,
+            completion_timestamp: None,
+            error_message: None,
+            execution_time_ms: 0.0,
+        });
+        assert!(sender.poll(t0 + Duration::from_secs(5)).await.is_empty());
+        assert_eq!(sender.outstanding(), 0);
+    }
+
+    fn ack(command_id: &str, status: &str) -> CommandAcknowledgment {
+        CommandAcknowledgment {
+            command_id: command_id.into(),
+            status: status.into(),
+            execution_timestamp: None,
+            completion_timestamp: None,
+            error_message: None,
+            execution_time_ms: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn ack_round_trip_is_timed_and_logged() {
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sock = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sock.connect(sat.local_addr().unwrap()).await.unwrap();
+        let crypto = Arc::new(CryptoContext::new(1, [9u8; 32], DEFAULT_REPLAY_WINDOW));
+        let path = std::env::temp_dir().join("commands_rtt_test.csv");
+        let _ = std::fs::remove_file(&path);
+        let mut sender = CommandSender::new(sock, crypto, RetryPolicy::default()).with_rtt_log(&path);
+
+        let cmd = Command::thermal_normal_operation(3);
+        sender.send(cmd.clone()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let rtt = sender.on_ack(&ack(&cmd.command_id, "received")).unwrap();
+        assert!((20.0..2000.0).contains(&rtt), "rtt {rtt}");
+        // later ACKs of the same command are timed too; the terminal one ends tracking
+        assert!(sender.on_ack(&ack(&cmd.command_id, "completed")).unwrap() >= rtt);
+        assert_eq!(sender.on_ack(&ack(&cmd.command_id, "completed")), None);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("ts,command_id,status,rtt_ms"));
+        let row = text
+            .lines()
+            .find(|l| l.contains(&cmd.command_id) && l.contains(",received,"))
+            .expect("received row");
+        let logged: f64 = row.rsplit(',').next().unwrap().parse().unwrap();
+        assert!((logged - rtt).abs() < 0.01, "{logged} vs {rtt}");
+    }
+}
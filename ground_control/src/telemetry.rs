@@ -162,6 +162,13 @@ impl TelemetryProcessor {
             last_reading_time: None,
             critical_thresholds: SensorThresholds::default(),
         });
+        expected_sensors.insert(7, ExpectedSensorConfig {
+            sensor_id: 7,
+            sensor_type: "radiation".to_string(),
+            expected_interval_ms: 1000, // 1Hz
+            last_reading_time: None,
+            critical_thresholds: SensorThresholds::default(),
+        });
         
         Self {
             last_sequence_numbers: HashMap::new(),
@@ -278,6 +285,14 @@ impl TelemetryProcessor {
             PacketPayload::AcknowledgmentData(ack) => {
                 debug!("Command acknowledgment: {} - {}", ack.command_id, ack.status);
             }
+
+            PacketPayload::ConfigUpdate(update) => {
+                debug!("Received config update {} (not typical for ground control)", update.update_id);
+            }
+
+            PacketPayload::Handshake(offer) => {
+                debug!("Capability handshake: versions {:?}", offer.protocol_versions);
+            }
         }
         
         // Check for missing packets in the sequence
@@ -442,10 +457,9 @@ impl TelemetryProcessor {
                 }
             }
             SensorType::Attitude => {
-                let roll = reading.value1;
-                let pitch = reading.value2;
-                let yaw = reading.value3;
-                let error = reading.value4;
+                // Euler or quaternion readings alike
+                let (roll, pitch, yaw) = reading.euler_degrees();
+                let error = reading.attitude_error();
                 
                 if error >= 10.0 {
                     status = "critical".to_string();
@@ -460,8 +474,18 @@ impl TelemetryProcessor {
                         roll, pitch, yaw));
                 }
             }
-            _ => {
-                anomalies.push(format!("Unknown sensor type: {:?}", reading.sensor_type));
+            SensorType::Radiation => {
+                let total_dose = reading.value2;
+                let warning_dose = reading.value3;
+                let critical_dose = reading.value4;
+
+                if total_dose >= critical_dose {
+                    status = "critical".to_string();
+                    anomalies.push(format!("Radiation dose critical: {:.2} mGy", total_dose));
+                } else if total_dose >= warning_dose {
+                    status = "warning".to_string();
+                    anomalies.push(format!("Radiation dose high: {:.2} mGy", total_dose));
+                }
             }
         }
         
@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use shared_protocol::{Priority, SensorReading, ThermalSensor};
+
+// The binary has no lib target: pull both buffers in by path
+#[allow(dead_code, unused_imports)]
+#[path = "../src/telemetry/prio_buffer.rs"]
+mod prio_buffer;
+#[allow(dead_code, unused_imports)]
+#[path = "../src/telemetry/heap_buffer.rs"]
+mod heap_buffer;
+
+use heap_buffer::HeapBufferHandle;
+use prio_buffer::BufferHandle;
+
+const CAPACITY: usize = 256;
+const READINGS: usize = 1024;
+const BATCH: usize = 32;
+
+// Mixed load: 10% Critical, 30% Important, 60% Normal
+fn mixed_readings() -> Vec<SensorReading> {
+    let sensor = ThermalSensor::new(1, "CPU");
+    (0..READINGS)
+        .map(|i| {
+            let mut r = sensor.create_reading(70.0, i as u64);
+            r.priority = match i % 10 {
+                0 => Priority::Critical,
+                1..=3 => Priority::Important,
+                _ => Priority::Normal,
+            };
+            r
+        })
+        .collect()
+}
+
+// Push everything (overflowing into evictions), popping a batch every 8 readings, then drain
+macro_rules! mixed_load {
+    ($buf:expr, $readings:expr) => {{
+        let buf = $buf;
+        for (i, r) in $readings.iter().cloned().enumerate() {
+            buf.push(r).await;
+            if i % 8 == 7 {
+                std::hint::black_box(buf.pop_many(BATCH / 8).await);
+            }
+        }
+        while !buf.pop_many(BATCH).await.is_empty() {}
+    }};
+}
+
+fn bench_mixed_load(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let readings = mixed_readings();
+
+    c.bench_function("vecdeque_buffer_mixed_load", |b| {
+        b.iter(|| rt.block_on(async { mixed_load!(BufferHandle::with_aging(CAPACITY, None), readings) }));
+    });
+    c.bench_function("heap_buffer_mixed_load", |b| {
+        b.iter(|| rt.block_on(async { mixed_load!(HeapBufferHandle::new(CAPACITY), readings) }));
+    });
+}
+
+criterion_group!(benches, bench_mixed_load);
+criterion_main!(benches);
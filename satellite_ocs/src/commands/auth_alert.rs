@@ -0,0 +1,88 @@
+// Authentication failures on the command link, counted over a sliding window: past a
+// threshold they raise a "security" emergency (forged traffic, or a key mismatch with ground)
+use chrono::Utc;
+use shared_protocol::{EmergencyData, Severity};
+use std::collections::VecDeque;
+use tokio::time::{Duration, Instant};
+
+pub struct AuthFailureMonitor {
+    threshold: usize, // zero disables the alert
+    window: Duration,
+    recent: VecDeque<Instant>,
+    total: u64, // every failure since start, alerting or not
+}
+
+impl AuthFailureMonitor {
+    pub fn new(threshold: usize, window: Duration) -> Self {
+        Self { threshold, window, recent: VecDeque::new(), total: 0 }
+    }
+
+    /// Count one failure at `now`. Returns the alert when it is the `threshold`-th inside
+    /// the window; the count then starts over, so a sustained attack alerts once per
+    /// `threshold` failures rather than on every frame.
+    pub fn record(&mut self, now: Instant, reason: &str) -> Option<EmergencyData> {
+        self.total += 1;
+        if self.threshold == 0 {
+            return None;
+        }
+        while self.recent.front().is_some_and(|&t| now.duration_since(t) > self.window) {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(now);
+        if self.recent.len() < self.threshold {
+            return None;
+        }
+        let failures = self.recent.len();
+        self.recent.clear();
+        Some(security_alert(failures, self.window, reason))
+    }
+
+    /// Failures recorded so far, including those that did not count towards an alert.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+fn security_alert(failures: usize, window: Duration, reason: &str) -> EmergencyData {
+    EmergencyData {
+        alert_id: format!("security-auth-{}", Utc::now().timestamp_millis()),
+        severity: Severity::High,
+        alert_type: "security".into(),
+        description: format!(
+            "{failures} frames failed authentication within {} ms (last: {reason}); possible attack or key mismatch",
+            window.as_millis()
+        ),
+        affected_systems: vec!["command_link".into()],
+        recommended_actions: vec!["verify_key_id_with_ground".into(), "rotate_keys_if_persistent".into()],
+        auto_recovery_attempted: false,
+        timestamp: Utc::now(),
+        metadata: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_fires_on_the_threshold_failure_inside_the_window() {
+        let mut m = AuthFailureMonitor::new(3, Duration::from_millis(100));
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        // spread out: each failure ages out before the third lands
+        assert!([0, 150, 300, 450].iter().all(|&ms| m.record(at(ms), "decrypt").is_none()));
+
+        assert!(m.record(at(460), "decrypt").is_none());
+        let alert = m.record(at(470), "decrypt").expect("third failure within 100 ms");
+        assert_eq!((alert.alert_type.as_str(), alert.severity), ("security", Severity::High));
+        assert!(alert.description.starts_with("3 frames"), "{}", alert.description);
+        // counting starts over after an alert
+        assert!(m.record(at(480), "decrypt").is_none());
+        assert_eq!(m.total(), 7);
+
+        let mut off = AuthFailureMonitor::new(0, Duration::from_secs(1));
+        assert!(off.record(t0, "decrypt").is_none());
+        assert_eq!(off.total(), 1);
+    }
+}
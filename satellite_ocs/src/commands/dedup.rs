@@ -0,0 +1,93 @@
+// src/commands/dedup.rs
+use shared_protocol::CommandAcknowledgment;
+use std::collections::{HashMap, VecDeque};
+
+/// Upper bound on remembered command ids.
+pub const SEEN_COMMANDS_CAPACITY: usize = 1024;
+
+/// LRU-bounded record of processed commands and the last ACK sent for each,
+/// so a retransmitted command gets its ACK re-sent instead of running again.
+#[derive(Debug)]
+pub struct SeenCommands {
+    capacity: usize,
+    order: VecDeque<String>, // front = least recently used
+    acks: HashMap<String, CommandAcknowledgment>,
+}
+
+impl SeenCommands {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            acks: HashMap::new(),
+        }
+    }
+
+    /// Last ACK for an already-seen command (also marks it recently used).
+    pub fn get(&mut self, command_id: &str) -> Option<CommandAcknowledgment> {
+        let ack = self.acks.get(command_id)?.clone();
+        if let Some(pos) = self.order.iter().position(|id| id == command_id) {
+            let id = self.order.remove(pos).unwrap();
+            self.order.push_back(id);
+        }
+        Some(ack)
+    }
+
+    /// Remember a new command with its first ACK, evicting the oldest entry when full.
+    pub fn insert(&mut self, ack: CommandAcknowledgment) {
+        if self.acks.contains_key(&ack.command_id) {
+            self.update(&ack);
+            return;
+        }
+        while self.order.len() >= self.capacity {
+            if let Some(old) = self.order.pop_front() {
+                self.acks.remove(&old);
+            }
+        }
+        self.order.push_back(ack.command_id.clone());
+        self.acks.insert(ack.command_id.clone(), ack);
+    }
+
+    /// Refresh the stored ACK of a tracked command (no-op once evicted).
+    pub fn update(&mut self, ack: &CommandAcknowledgment) {
+        if let Some(slot) = self.acks.get_mut(&ack.command_id) {
+            *slot = ack.clone();
+        }
+    }
+}
+
+impl Default for SeenCommands {
+    fn default() -> Self {
+        Self::new(SEEN_COMMANDS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ack(id: &str, status: &str) -> CommandAcknowledgment {
+        CommandAcknowledgment {
+            command_id: id.into(),
+            status: status.into(),
+            execution_timestamp: None,
+            completion_timestamp: None,
+            error_message: None,
+            execution_time_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut seen = SeenCommands::new(2);
+        seen.insert(ack("a", "received"));
+        seen.insert(ack("b", "received"));
+        assert!(seen.get("a").is_some()); // "b" is now the oldest
+        seen.insert(ack("c", "received"));
+
+        assert!(seen.get("b").is_none());
+        assert!(seen.get("c").is_some());
+        seen.update(&ack("a", "completed"));
+        assert_eq!(seen.get("a").unwrap().status, "completed");
+    }
+}
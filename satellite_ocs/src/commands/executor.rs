@@ -0,0 +1,116 @@
+// src/commands/executor.rs
+use chrono::Utc;
+use shared_protocol::{Command, CommandAcknowledgment, CommandType, TargetSystem};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
+
+// Longest simulated execution; keeps a bad param3 from parking the executor
+const MAX_WORK_MS: f64 = 5_000.0;
+
+/// Simulated execution time: a per-type base cost plus `param3` milliseconds.
+fn work_ms(cmd: &Command) -> f64 {
+    let base = match cmd.command_type {
+        CommandType::Emergency => 2.0,
+        CommandType::ThermalControl
+        | CommandType::PowerControl
+        | CommandType::AttitudeControl => 10.0,
+        CommandType::Recovery => 20.0,
+        CommandType::Diagnostic | CommandType::DataRequest => 15.0,
+        CommandType::SetThreshold | CommandType::ClockCorrection => 5.0,
+        CommandType::Maintenance => 50.0,
+    };
+    (base + cmd.param3.max(0.0)).min(MAX_WORK_MS)
+}
+
+/// Commands that change on-board state do so before the simulated work; a rejected
+/// change fails the command.
+fn apply(cmd: &Command) -> Result<(), String> {
+    match cmd.command_type {
+        CommandType::SetThreshold => crate::sensors::thresholds::update(cmd),
+        CommandType::ClockCorrection => crate::scheduler::timing::correct_clock(cmd.param1).map(|left_ms| {
+            info!(cmd_id = %cmd.command_id, by_ms = cmd.param1, left_ms, "clock corrected");
+        }),
+        // text_param "SNAPSHOT": newest reading of every sensor type, one packet
+        CommandType::DataRequest if cmd.text_param == shared_protocol::SNAPSHOT_REQUEST => {
+            crate::telemetry::snapshot().map(|n| {
+                info!(cmd_id = %cmd.command_id, readings = n, "sending telemetry snapshot");
+            })
+        }
+        // param1 = sensor id; the readings go out on their own telemetry packet
+        CommandType::DataRequest => crate::telemetry::replay(cmd.param1 as u32).map(|n| {
+            info!(cmd_id = %cmd.command_id, sensor_id = cmd.param1 as u32, readings = n, "replaying recent readings");
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Whether this executor has a handler for the command: subsystem control commands only
+/// drive their own subsystem (or all systems).
+pub fn supports(cmd: &Command) -> Result<(), String> {
+    let ok = match cmd.command_type {
+        CommandType::ThermalControl => matches!(cmd.target_system, TargetSystem::ThermalManagement | TargetSystem::AllSystems),
+        CommandType::PowerControl => matches!(cmd.target_system, TargetSystem::PowerManagement | TargetSystem::AllSystems),
+        CommandType::AttitudeControl => matches!(cmd.target_system, TargetSystem::AttitudeControl | TargetSystem::AllSystems),
+        _ => true,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("no {:?} handler for target {:?}", cmd.command_type, cmd.target_system))
+    }
+}
+
+fn ack(cmd: &Command, status: &str) -> CommandAcknowledgment {
+    CommandAcknowledgment {
+        command_id: cmd.command_id.clone(),
+        status: status.into(),
+        execution_timestamp: Some(Utc::now()),
+        completion_timestamp: None,
+        error_message: None,
+        execution_time_ms: 0.0,
+    }
+}
+
+/// Run one command: emits "executing", then "completed" or "failed".
+/// Fails on non-finite/negative `param3` or when the command's deadline passes mid-run.
+pub async fn execute(cmd: Command, ack_tx: mpsc::Sender<CommandAcknowledgment>) {
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let _ = ack_tx.send(ack(&cmd, "executing")).await;
+
+    let outcome: Result<(), String> = if !cmd.param3.is_finite() || cmd.param3 < 0.0 {
+        Err(format!("invalid param3: {}", cmd.param3))
+    } else if let Err(e) = apply(&cmd) {
+        Err(e)
+    } else {
+        let work = time::sleep(Duration::from_micros((work_ms(&cmd) * 1000.0) as u64));
+        match cmd.deadline {
+            Some(deadline) => {
+                let budget = (deadline - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+                time::timeout(budget, work)
+                    .await
+                    .map_err(|_| "deadline exceeded".to_string())
+            }
+            None => {
+                work.await;
+                Ok(())
+            }
+        }
+    };
+
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let mut done = ack(&cmd, if outcome.is_ok() { "completed" } else { "failed" });
+    done.execution_timestamp = Some(started_at);
+    done.completion_timestamp = Some(Utc::now());
+    done.execution_time_ms = elapsed_ms;
+
+    match outcome {
+        Ok(()) => info!(cmd_id = %cmd.command_id, elapsed_ms, "command completed"),
+        Err(e) => {
+            warn!(cmd_id = %cmd.command_id, elapsed_ms, error = %e, "command failed");
+            done.error_message = Some(e);
+        }
+    }
+    let _ = ack_tx.send(done).await;
+}
@@ -1,67 +1,306 @@
 use crate::{config::Config, crypto::Crypto, net::framing::Framer};
+use crate::net::{tcp, transport::Transport};
+use super::{auth_alert::AuthFailureMonitor, dedup::SeenCommands, queue::CommandQueue};
 use chrono::Utc;
-use shared_protocol::{CommandAcknowledgment, CommunicationPacket, PacketPayload, Source};
-use std::sync::Arc;
-use tokio::net::UdpSocket;
+use shared_protocol::{
+    CommandAcknowledgment, CommunicationPacket, CryptoError, EmergencyData, PacketPayload, Reassembler, Source,
+};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration};
 use tracing::{info, warn};
 
+/// Commands arrive over UDP and, when `cmd_tcp_addr` is set, over a TCP channel too;
+/// both feed the same dedup cache, and ACKs go back on the channel the command came in
+/// on. Security alerts go out on `em_tx`.
+/// Returns the UDP receive loop.
 pub async fn spawn_receiver(
-    _cfg: Config,
+    cfg: Config,
     crypto: Crypto,
-    rx_sock: Arc<UdpSocket>,
-    tx_sock: Arc<UdpSocket>,
+    rx_sock: Arc<dyn Transport>,
+    tx_sock: Arc<dyn Transport>,
     framer: Framer,
-) {
+    em_tx: Option<mpsc::Sender<EmergencyData>>,
+) -> tokio::task::JoinHandle<()> {
+    let monitor = AuthFailureMonitor::new(cfg.auth_alert_threshold, Duration::from_millis(cfg.auth_alert_window_ms));
+    // ACKs from the receiver and executors funnel through one sender task
+    let (ack_tx, mut ack_rx) = mpsc::channel::<CommandAcknowledgment>(64);
+    let ctx = HandlerCtx {
+        crypto,
+        framer,
+        seen: Arc::new(Mutex::new(SeenCommands::default())),
+        queue: CommandQueue::spawn(),
+        ack_tx,
+        auth: Arc::new(AuthAlerts::new(monitor, em_tx)),
+        nack_unexpected: cfg.nack_unexpected,
+    };
+    {
+        let crypto = ctx.crypto.clone();
+        let seen = ctx.seen.clone();
+        tokio::spawn(async move {
+            while let Some(ack) = ack_rx.recv().await {
+                // keep the latest ACK so a duplicate gets the current status
+                seen.lock().unwrap_or_else(|e| e.into_inner()).update(&ack);
+                let status = ack.status.clone();
+                if let Err(e) = send_ack(tx_sock.as_ref(), &crypto, ack).await {
+                    warn!(?e, %status, "failed to send ack");
+                }
+            }
+        });
+    }
+
+    // Reliable command channel (TCP), reconnecting while the link is down
+    if cfg.cmd_tcp_addr.is_some() {
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            loop {
+                match tcp::connect_command_channel(&cfg).await {
+                    Ok(conn) => {
+                        info!("command channel: TCP connected");
+                        ctx.serve_tcp(Arc::new(conn)).await;
+                        warn!("command channel: TCP closed; reconnecting");
+                    }
+                    Err(e) => warn!(?e, "command channel: TCP connect failed"),
+                }
+                time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+    }
+
     tokio::spawn(async move {
         let mut buf = vec![0u8; 64 * 1024];
-        let framer = framer; // move into task
+        let mut reassembler = Reassembler::default();
 
         loop {
-            match rx_sock.recv_from(&mut buf).await {
-                Ok((n, _from)) => {
-                    match framer.deframe(&buf[..n]) {
-                        Ok(frame) => match crypto.open(frame) {
-                            Ok(pkt) => match pkt.payload {
-                                PacketPayload::CommandData(cmd) => {
-                                    info!(
-                                        cmd_id = %cmd.command_id,
-                                        ?cmd.command_type,
-                                        ?cmd.target_system,
-                                        "received command"
-                                    );
-
-                                    // ACK: received
-                                    let ack_recv = CommandAcknowledgment {
-                                        command_id: cmd.command_id.clone(),
-                                        status: "received".into(),
-                                        execution_timestamp: Some(Utc::now()),
-                                        completion_timestamp: None,
-                                        error_message: None,
-                                        execution_time_ms: 0.0,
-                                    };
-                                    if let Err(e) = send_ack(tx_sock.as_ref(), &crypto, ack_recv).await {
-                                        warn!(?e, "failed to send 'received' ack");
-                                    }
-
-                                    // TODO: schedule/execute → send 'executing' and 'completed' ACKs
-                                }
-                                _other => {
-                                    // ignore non-command payloads for now
-                                }
-                            },
-                            Err(e) => warn!("decrypt error: {e}"),
-                        },
-                        Err(e) => warn!("deframe error: {e}"),
+            match rx_sock.recv(&mut buf).await {
+                Ok(n) => {
+                    // fragments are buffered until the whole frame is in
+                    if let Some(frame) = reassembler.push(&buf[..n]) {
+                        ctx.handle_frame(&frame).await
                     }
                 }
                 Err(e) => warn!("recv error: {e}"),
             }
         }
-    });
+    })
+}
+
+/// Turns a burst of authentication failures into a security alert (`--auth-alert-threshold`).
+struct AuthAlerts {
+    monitor: Mutex<AuthFailureMonitor>,
+    em_tx: Option<mpsc::Sender<EmergencyData>>,
+}
+
+impl AuthAlerts {
+    fn new(monitor: AuthFailureMonitor, em_tx: Option<mpsc::Sender<EmergencyData>>) -> Self {
+        Self { monitor: Mutex::new(monitor), em_tx }
+    }
+
+    /// Count one failure; returns the running total.
+    fn record(&self, reason: &str) -> u64 {
+        let (alert, total) = {
+            let mut monitor = self.monitor.lock().unwrap_or_else(|e| e.into_inner());
+            (monitor.record(time::Instant::now(), reason), monitor.total())
+        };
+        if let Some(em) = alert {
+            warn!(description = %em.description, "SECURITY ALERT: repeated authentication failures");
+            if let Some(em_tx) = &self.em_tx {
+                let _ = em_tx.try_send(em);
+            }
+        }
+        total
+    }
+}
+
+/// What every received frame is handled against; the UDP loop and the TCP channel each
+/// hold a clone.
+#[derive(Clone)]
+struct HandlerCtx {
+    crypto: Crypto,
+    framer: Framer,
+    /// Recently processed commands (dedup of ground retransmissions)
+    seen: Arc<Mutex<SeenCommands>>,
+    /// Accepted commands, run one at a time by urgency
+    queue: CommandQueue,
+    ack_tx: mpsc::Sender<CommandAcknowledgment>,
+    auth: Arc<AuthAlerts>,
+    /// `--nack-unexpected`: answer non-command payloads with a "failed" ACK.
+    nack_unexpected: bool,
+}
+
+impl HandlerCtx {
+    /// Feed every frame from a TCP command connection to `handle_frame` until it closes.
+    /// ACKs for commands that came in here go back on the same stream; one that can't
+    /// (the connection dropped while the command ran) falls back to the UDP path.
+    async fn serve_tcp(&self, conn: Arc<dyn Transport>) {
+        let (ack_tx, mut ack_rx) = mpsc::channel::<CommandAcknowledgment>(64);
+        {
+            let (crypto, seen, udp_ack_tx) = (self.crypto.clone(), self.seen.clone(), self.ack_tx.clone());
+            let conn = conn.clone();
+            tokio::spawn(async move {
+                while let Some(ack) = ack_rx.recv().await {
+                    seen.lock().unwrap_or_else(|e| e.into_inner()).update(&ack);
+                    if let Err(e) = send_ack(conn.as_ref(), &crypto, ack.clone()).await {
+                        warn!(?e, status = %ack.status, "failed to send ack over tcp; using udp");
+                        let _ = udp_ack_tx.send(ack).await;
+                    }
+                }
+            });
+        }
+        let on_tcp = Self { ack_tx, ..self.clone() };
+
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match conn.recv(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => on_tcp.handle_frame(&buf[..n]).await,
+                Err(e) => {
+                    warn!("tcp read error: {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Every frame in one UDP datagram or TCP frame, in order; a bad length prefix ends the
+    /// walk, since nothing after it can be located.
+    async fn handle_frame(&self, bytes: &[u8]) {
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            match self.framer.deframe_consumed(rest) {
+                Ok((_, consumed)) => {
+                    self.handle_one(&rest[..consumed]).await;
+                    rest = &rest[consumed..];
+                }
+                Err(e) => {
+                    warn!("deframe error: {e}");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Decrypt one length-prefixed frame; new commands get a "received" ACK and are queued for
+    /// the executor, already-seen ones get their last ACK re-sent.
+    async fn handle_one(&self, frame: &[u8]) {
+        let ack_tx = &self.ack_tx;
+        match self.crypto.open(frame) {
+            Ok(pkt) => match pkt.payload {
+                PacketPayload::CommandData(cmd) => {
+                    let previous = self
+                        .seen
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .get(&cmd.command_id);
+                    if let Some(last_ack) = previous {
+                        info!(
+                            cmd_id = %cmd.command_id,
+                            retry = cmd.retry_count,
+                            status = %last_ack.status,
+                            "duplicate command; re-sending last ack"
+                        );
+                        let _ = ack_tx.send(last_ack).await;
+                        return;
+                    }
+
+                    info!(
+                        cmd_id = %cmd.command_id,
+                        ?cmd.command_type,
+                        ?cmd.target_system,
+                        "received command"
+                    );
+
+                    // reject before executing; the reason travels in the "failed" ACK
+                    if let Err(reason) = cmd.validate().and_then(|()| super::executor::supports(&cmd)) {
+                        warn!(cmd_id = %cmd.command_id, %reason, "rejecting command");
+                        let ack_fail = failed_ack(cmd.command_id.clone(), reason);
+                        self.seen
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .insert(ack_fail.clone());
+                        let _ = ack_tx.send(ack_fail).await;
+                        return;
+                    }
+
+                    // ACK: received
+                    let ack_recv = CommandAcknowledgment {
+                        command_id: cmd.command_id.clone(),
+                        status: "received".into(),
+                        execution_timestamp: Some(Utc::now()),
+                        completion_timestamp: None,
+                        error_message: None,
+                        execution_time_ms: 0.0,
+                    };
+                    self.seen
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(ack_recv.clone());
+                    let _ = ack_tx.send(ack_recv).await;
+
+                    // *Control commands retune the target sensor's sampling period (param2 ms)
+                    crate::sensors::rate::apply(&cmd);
+
+                    // executing → completed/failed, once it is the most urgent queued command
+                    self.queue.push(cmd, ack_tx.clone());
+                }
+                PacketPayload::ConfigUpdate(update) => {
+                    let keys: Vec<&str> = update.overrides.keys().map(String::as_str).collect();
+                    let result = match crate::config::LIVE.get() {
+                        Some(live) => live.apply(&update.overrides),
+                        None => Err("runtime config not initialised".into()),
+                    };
+                    match &result {
+                        Ok(()) => info!(update_id = %update.update_id, ?keys, "config update applied"),
+                        Err(reason) => warn!(update_id = %update.update_id, %reason, "config update rejected"),
+                    }
+                    let ack = CommandAcknowledgment {
+                        command_id: update.update_id,
+                        status: if result.is_ok() { "completed" } else { "failed" }.into(),
+                        execution_timestamp: Some(Utc::now()),
+                        completion_timestamp: Some(Utc::now()),
+                        error_message: result.err(),
+                        execution_time_ms: 0.0,
+                    };
+                    let _ = ack_tx.send(ack).await;
+                }
+                // a late (or repeated) handshake reply from ground
+                PacketPayload::Handshake(offer) => match self.crypto.negotiate(&offer) {
+                    Ok(n) => info!(?n, "handshake: capabilities negotiated"),
+                    Err(e) => warn!(%e, "handshake: incompatible ground station"),
+                },
+                _ => {
+                    let kind = pkt.header.packet_type;
+                    warn!(packet_id = %pkt.header.packet_id, ?kind, "unexpected payload on the command channel");
+                    if self.nack_unexpected {
+                        let reason = format!("unexpected {kind:?} payload; expected a command");
+                        let _ = ack_tx.send(failed_ack(pkt.header.packet_id, reason)).await;
+                    }
+                }
+            },
+            Err(e @ (CryptoError::DecryptFailed | CryptoError::KeyIdMismatch(_))) => {
+                // forged, corrupted or sealed under an unknown key
+                let total = self.auth.record(&e.to_string());
+                warn!(%e, total, "frame failed authentication");
+            }
+            Err(CryptoError::Replay) => info!("replayed frame dropped"),
+            Err(e) => warn!(%e, "malformed frame"),
+        }
+    }
+}
+
+fn failed_ack(command_id: String, reason: String) -> CommandAcknowledgment {
+    CommandAcknowledgment {
+        command_id,
+        status: "failed".into(),
+        execution_timestamp: None,
+        completion_timestamp: Some(Utc::now()),
+        error_message: Some(reason),
+        execution_time_ms: 0.0,
+    }
 }
 
 async fn send_ack(
-    sock: &UdpSocket,
+    sock: &dyn Transport,
     crypto: &Crypto,
     ack: CommandAcknowledgment,
 ) -> Result<(), std::io::Error> {
@@ -71,3 +310,195 @@ async fn send_ack(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::Command;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+
+    /// A handler acking into `ack_tx`, with default alert settings and no NACKs.
+    fn ctx(crypto: &Crypto, ack_tx: mpsc::Sender<CommandAcknowledgment>) -> HandlerCtx {
+        HandlerCtx {
+            crypto: crypto.clone(),
+            framer: Framer,
+            seen: Default::default(),
+            queue: CommandQueue::spawn(),
+            ack_tx,
+            auth: Arc::new(AuthAlerts::new(AuthFailureMonitor::new(5, Duration::from_secs(10)), None)),
+            nack_unexpected: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn command_frame_yields_received_executing_completed() {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let cmd = Command::thermal_normal_operation(1);
+        let frame = crypto
+            .seal(&CommunicationPacket::new_command(cmd.clone(), Source::GroundControl))
+            .unwrap();
+
+        let (ack_tx, mut ack_rx) = mpsc::channel(8);
+        ctx(&crypto, ack_tx).handle_frame(&frame).await;
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let ack = timeout(Duration::from_secs(2), ack_rx.recv()).await.unwrap().unwrap();
+            assert_eq!(ack.command_id, cmd.command_id);
+            statuses.push(ack.status.clone());
+            if ack.status == "completed" {
+                assert!(ack.completion_timestamp.is_some());
+                assert!(ack.execution_time_ms > 0.0);
+            }
+        }
+        assert_eq!(statuses, ["received", "executing", "completed"]);
+    }
+
+    #[tokio::test]
+    async fn duplicate_command_is_acked_but_not_reexecuted() {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let cmd = Command::thermal_normal_operation(2);
+        let (ack_tx, mut ack_rx) = mpsc::channel(8);
+        let ctx = ctx(&crypto, ack_tx);
+
+        // two separate seals (fresh sequence numbers), same command_id
+        for _ in 0..2 {
+            let frame = crypto
+                .seal(&CommunicationPacket::new_command(cmd.clone(), Source::GroundControl))
+                .unwrap();
+            ctx.handle_frame(&frame).await;
+        }
+        drop(ctx);
+
+        let mut statuses = Vec::new();
+        while let Ok(Some(ack)) = timeout(Duration::from_secs(2), ack_rx.recv()).await {
+            statuses.push(ack.status);
+        }
+        let count = |s: &str| statuses.iter().filter(|x| x.as_str() == s).count();
+        assert_eq!(count("received"), 2, "{statuses:?}"); // original + re-sent
+        assert_eq!(count("executing"), 1, "{statuses:?}");
+        assert_eq!(count("completed"), 1, "{statuses:?}");
+    }
+
+    #[tokio::test]
+    async fn invalid_command_is_rejected_with_failed_ack() {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let mut cmd = Command::thermal_normal_operation(3);
+        cmd.deadline = Some(Utc::now() - chrono::Duration::seconds(1));
+        let frame = crypto
+            .seal(&CommunicationPacket::new_command(cmd.clone(), Source::GroundControl))
+            .unwrap();
+
+        let (ack_tx, mut ack_rx) = mpsc::channel(8);
+        ctx(&crypto, ack_tx).handle_frame(&frame).await;
+
+        let ack = timeout(Duration::from_secs(2), ack_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(ack.status, "failed");
+        assert!(ack.error_message.unwrap().contains("deadline"));
+        // never reaches the executor
+        assert!(timeout(Duration::from_secs(1), ack_rx.recv()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn unsupported_command_and_unexpected_payload_get_failed_acks() {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let mut cmd = Command::thermal_normal_operation(4);
+        cmd.target_system = shared_protocol::TargetSystem::PowerManagement;
+        let stray = CommunicationPacket::new_telemetry(Vec::new(), Source::GroundControl);
+        let packet_id = stray.header.packet_id.clone();
+
+        let (ack_tx, mut ack_rx) = mpsc::channel(8);
+        let ctx = HandlerCtx { nack_unexpected: true, ..ctx(&crypto, ack_tx) };
+        for pkt in [CommunicationPacket::new_command(cmd.clone(), Source::GroundControl), stray] {
+            ctx.handle_frame(&crypto.seal(&pkt).unwrap()).await;
+        }
+        drop(ctx);
+
+        let ack = timeout(Duration::from_secs(2), ack_rx.recv()).await.unwrap().unwrap();
+        assert_eq!((ack.command_id.as_str(), ack.status.as_str()), (cmd.command_id.as_str(), "failed"));
+        assert!(ack.error_message.unwrap().contains("no ThermalControl handler"));
+        let nack = timeout(Duration::from_secs(2), ack_rx.recv()).await.unwrap().unwrap();
+        assert_eq!((nack.command_id, nack.status.as_str()), (packet_id, "failed"));
+        assert!(nack.error_message.unwrap().contains("unexpected Telemetry payload"));
+        // neither reaches the executor
+        assert!(timeout(Duration::from_secs(1), ack_rx.recv()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn command_round_trips_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut cfg = Config::test_default();
+        cfg.cmd_tcp_addr = Some(listener.local_addr().unwrap().to_string());
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let cmd = Command::thermal_normal_operation(3);
+        let frame = crypto
+            .seal(&CommunicationPacket::new_command(cmd.clone(), Source::GroundControl))
+            .unwrap();
+
+        // ground side: write the frame in two pieces, read the ACKs off the stream, then close
+        let ground_crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let (a, b) = frame.split_at(frame.len() / 2);
+            sock.write_all(a).await.unwrap();
+            time::sleep(Duration::from_millis(10)).await;
+            sock.write_all(b).await.unwrap();
+
+            let mut stream = tcp::FramedTcp::new(sock, Framer);
+            let mut acks = Vec::new();
+            while acks.len() < 3 {
+                let frame = stream.next_frame().await.unwrap().expect("ack before close");
+                match ground_crypto.open(&frame).unwrap().payload {
+                    PacketPayload::AcknowledgmentData(ack) => acks.push(ack),
+                    other => panic!("expected an ack, got {other:?}"),
+                }
+            }
+            acks
+        });
+
+        let conn = tcp::connect_command_channel(&cfg).await.unwrap();
+        let (ack_tx, mut udp_ack_rx) = mpsc::channel(8);
+        let ctx = ctx(&crypto, ack_tx);
+        timeout(Duration::from_secs(5), ctx.serve_tcp(Arc::new(conn))).await.unwrap();
+
+        let acks = ground.await.unwrap();
+        assert!(acks.iter().all(|a| a.command_id == cmd.command_id));
+        let statuses: Vec<_> = acks.iter().map(|a| a.status.as_str()).collect();
+        assert_eq!(statuses, ["received", "executing", "completed"]);
+        // none of them went out over UDP
+        assert!(udp_ack_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn burst_of_forged_frames_raises_a_security_alert() {
+        let (em_tx, mut em_rx) = mpsc::channel(64);
+
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        // right key id, wrong key: the AEAD tag doesn't verify
+        let forger = shared_protocol::CryptoContext::new(cfg.key_id, [0x55; 32], shared_protocol::DEFAULT_REPLAY_WINDOW);
+        let forged = forger
+            .seal_to_bytes(&CommunicationPacket::new_command(Command::thermal_normal_operation(9), Source::GroundControl))
+            .unwrap();
+        let (ack_tx, _ack_rx) = mpsc::channel(8);
+        let auth = AuthAlerts::new(AuthFailureMonitor::new(3, Duration::from_secs(10)), Some(em_tx));
+        let ctx = HandlerCtx { auth: Arc::new(auth), ..ctx(&crypto, ack_tx) };
+        for _ in 0..3 {
+            ctx.handle_frame(&forged).await;
+        }
+
+        let alert = timeout(Duration::from_secs(1), async {
+            loop {
+                let em = em_rx.recv().await.unwrap();
+                if em.alert_type == "security" {
+                    break em;
+                }
+            }
+        })
+        .await
+        .expect("security alert after 3 forged frames");
+        assert!(alert.description.contains("failed authentication"), "{}", alert.description);
+    }
+}
@@ -1,2 +1,6 @@
+pub mod auth_alert;
+pub mod dedup;
+pub mod executor;
 pub mod handler;
+pub mod queue;
 pub use handler::spawn_receiver;
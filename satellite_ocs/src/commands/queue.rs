@@ -0,0 +1,125 @@
+// src/commands/queue.rs
+// Received commands wait here for the single executor task, most urgent first:
+// priority, then earliest deadline, then arrival. Emergency commands outrank everything.
+use shared_protocol::{Command, CommandAcknowledgment, CommandType, Priority};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+use super::executor;
+
+struct Queued {
+    key: Reverse<(Priority, i64, u64)>,
+    cmd: Command,
+    ack_tx: mpsc::Sender<CommandAcknowledgment>,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl Eq for Queued {}
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    heap: BinaryHeap<Queued>,
+    next_seq: u64,
+}
+
+/// Handle to the command queue; cloning shares it.
+#[derive(Clone)]
+pub struct CommandQueue {
+    inner: Arc<Mutex<Inner>>,
+    ready: Arc<Notify>,
+}
+
+impl CommandQueue {
+    /// Create the queue and its executor task. Commands run one at a time; a running
+    /// command is never preempted, the next pick is always the most urgent.
+    pub fn spawn() -> Self {
+        let q = Self { inner: Arc::default(), ready: Arc::new(Notify::new()) };
+        tokio::spawn({
+            let q = q.clone();
+            async move {
+                loop {
+                    match q.pop() {
+                        Some((cmd, ack_tx)) => executor::execute(cmd, ack_tx).await,
+                        None => q.ready.notified().await,
+                    }
+                }
+            }
+        });
+        q
+    }
+
+    /// Queue a validated command; its ACKs go to `ack_tx`.
+    pub fn push(&self, cmd: Command, ack_tx: mpsc::Sender<CommandAcknowledgment>) {
+        let priority = match cmd.command_type {
+            CommandType::Emergency => Priority::Emergency,
+            _ => cmd.priority,
+        };
+        let deadline = cmd.deadline.map_or(i64::MAX, |d| d.timestamp_micros());
+        {
+            let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            inner.heap.push(Queued { key: Reverse((priority, deadline, seq)), cmd, ack_tx });
+        }
+        self.ready.notify_one();
+    }
+
+    fn pop(&self) -> Option<(Command, mpsc::Sender<CommandAcknowledgment>)> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.heap.pop().map(|q| (q.cmd, q.ack_tx))
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).heap.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    async fn emergency_runs_before_an_earlier_normal_command() {
+        let (ack_tx, mut ack_rx) = mpsc::channel(16);
+        // occupy the executor so both commands below are queued together
+        let mut busy = Command::thermal_normal_operation(1);
+        busy.param3 = 50.0;
+        let mut normal = Command::thermal_normal_operation(1);
+        normal.priority = Priority::Normal;
+        let emergency = Command::thermal_emergency_response(1, 95.0);
+
+        let queue = CommandQueue::spawn();
+        queue.push(busy.clone(), ack_tx.clone());
+        tokio::task::yield_now().await;
+        queue.push(normal.clone(), ack_tx.clone());
+        queue.push(emergency.clone(), ack_tx);
+        assert_eq!(queue.len(), 2);
+
+        let mut started = Vec::new();
+        while started.len() < 3 {
+            let ack = timeout(Duration::from_secs(2), ack_rx.recv()).await.unwrap().unwrap();
+            if ack.status == "executing" {
+                started.push(ack.command_id);
+            }
+        }
+        assert_eq!(started, [busy.command_id, emergency.command_id, normal.command_id]);
+    }
+}
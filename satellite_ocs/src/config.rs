@@ -2,38 +2,379 @@
 // runtime configuration (ports, keys, rates)
 use anyhow::Result;
 use clap::Parser;
+use once_cell::sync::OnceCell;
+use shared_protocol::AeadAlgo;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+use crate::logging::csv::LogFormat;
+use crate::scheduler::{exec::ExecModel, SchedPolicy};
+use crate::sensors::profile::TempProfileKind;
+use crate::telemetry::prio_buffer::{BatchCaps, BucketCaps, DropPolicy};
+use crate::sensors::{SensorDef, DEFAULT_SENSORS};
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub gcs_addr: String,
     pub bind_addr: String,
     pub key_id: u8,
-    pub key_hex: String,
+    pub key: KeySource,
     pub batch_ms: u64,
     pub max_batch: usize,
+    pub sched_policy: SchedPolicy,
+    pub thermal_min_interarrival_ms: u64,
+    pub aging_ms: u64,
+    pub cmd_tcp_addr: Option<String>,
+    pub mtu: usize,
+    pub downlink_interval_ms: u64,
+    pub downlink_window_ms: u64,
+    pub downlink_schedule: Option<String>,
+    pub fault_schedule: Option<String>,
+    pub fault_soft_ms: u64,
+    pub fault_hard_ms: u64,
+    pub fault_deadline_ms: u64,
+    pub thermal_profile: TempProfileKind,
+    pub thermal_replay_csv: Option<String>,
+    pub downsample_fill_pct: f64,
+    pub log_rotate_bytes: u64,
+    pub emergency_coalesce_ms: u64,
+    pub log_format: LogFormat,
+    pub lower_quota_pct: f64,
+    pub watchdog_periods: u32,
+    pub rng_seed: Option<u64>,
+    pub seeded_nonces: bool,
+    pub cpu_window_ms: u64,
+    pub sensors: Vec<SensorDef>,
+    pub aead: AeadAlgo,
+    pub metrics_port: Option<u16>,
+    pub flush_fill_pct: f64,
+    pub drop_policy: DropPolicy,
+    pub sensor_jitter_ms: f64,
+    pub max_age_ms: u64,
+    pub batch_caps: BatchCaps,
+    pub clock_drift_ppm: f64,
+    pub emergency_fast_path: bool,
+    pub binary_logs: Vec<String>,
+    pub heartbeat_ms: u64,
+    pub exec_model: ExecModel,
+    pub udp_reuse_addr: bool,
+    pub udp_recv_buffer: usize,
+    pub link_drop_pct: f64,
+    pub link_dup_pct: f64,
+    pub link_latency_ms: u64,
+    pub link_jitter_ms: u64,
+    pub nack_unexpected: bool,
+    pub summary_secs: u64,
+    pub handshake_timeout_ms: u64,
+    pub history_depth: usize,
+    pub run_for_secs: u64,
+    pub emergency_repeats: u32,
+    pub emergency_repeat_ms: u64,
+    pub emergency_repeat_high: bool,
+    pub bucket_caps: Option<BucketCaps>,
+    pub auth_alert_threshold: usize,
+    pub auth_alert_window_ms: u64,
+    pub log_dir: String,
+}
+
+/// Where the frame key comes from: raw hex (automation) or a passphrase + salt that
+/// `Crypto::from_config` stretches into 32 bytes once at startup.
+#[derive(Clone)]
+pub enum KeySource {
+    Hex(String),
+    Passphrase { passphrase: String, salt: String },
+}
+
+// Config is logged at startup; never print key material.
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Hex(_) => f.write_str("Hex(..)"),
+            KeySource::Passphrase { salt, .. } => write!(f, "Passphrase {{ salt: {salt:?}, .. }}"),
+        }
+    }
+}
+
+/// Offline tools; without one the OCS runs normally.
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// Re-encode a recorded sensors.csv into sealed telemetry frames (no live sensors)
+    Replay(crate::telemetry::csv_replay::ReplayArgs),
+    /// Print a binary logs/<stem>.bin as CSV rows (ts, then the log's usual columns)
+    DecodeLog { path: std::path::PathBuf },
 }
 
 #[derive(Parser, Debug, Clone)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
     #[arg(long, default_value = "127.0.0.1:7891")] pub gcs_addr: String,
     #[arg(long, default_value = "0.0.0.0:7892")]   pub bind_addr: String,
     #[arg(long, default_value_t = 1)]              pub key_id: u8,
     #[arg(long, default_value = "0000000000000000000000000000000000000000000000000000000000000007")]
     pub key_hex: String,
+    /// Derive the key from this passphrase (with --key-salt) instead of --key-hex
+    #[arg(long, env = "OCS_KEY_PASSPHRASE", hide_env_values = true, conflicts_with = "key_hex", requires = "key_salt")]
+    pub key_passphrase: Option<String>,
+    /// Salt for --key-passphrase; ground must use the same pair
+    #[arg(long)]                                   pub key_salt: Option<String>,
     #[arg(long, default_value_t = 50)]             pub batch_ms: u64,
     #[arg(long, default_value_t = 64)]             pub max_batch: usize,
+    #[arg(long, value_enum, default_value = "rm")] pub sched_policy: SchedPolicy,
+    /// Minimum spacing of sporadic thermal_control jobs (for utilization analysis)
+    #[arg(long, default_value_t = 100)]            pub thermal_min_interarrival_ms: u64,
+    /// Buffered readings older than this are sent ahead of priority order (0 = off)
+    #[arg(long, default_value_t = 500)]            pub aging_ms: u64,
+    /// Ground TCP endpoint for reliable commands (UDP-only when unset)
+    #[arg(long)]                                   pub cmd_tcp_addr: Option<String>,
+    /// Largest UDP datagram sent; bigger frames are fragmented
+    #[arg(long, default_value_t = 1400)]           pub mtu: usize,
+    /// Visibility window spacing and length (used when no schedule is given)
+    #[arg(long, default_value_t = 5000)]           pub downlink_interval_ms: u64,
+    #[arg(long, default_value_t = 800)]            pub downlink_window_ms: u64,
+    /// Explicit passes as "start_ms:duration_ms,..." (e.g. "0:800,5000:800,12000:1500")
+    #[arg(long)]                                   pub downlink_schedule: Option<String>,
+    /// Fault schedule file, or inline "at_ms:kind:target:duration_ms[:param],..."
+    #[arg(long)]                                   pub fault_schedule: Option<String>,
+    /// Fault recovery slower than this warns; slower than the hard limit aborts
+    #[arg(long, default_value_t = 100)]            pub fault_soft_ms: u64,
+    #[arg(long, default_value_t = 200)]            pub fault_hard_ms: u64,
+    /// How long to wait for a recovery ACK before aborting
+    #[arg(long, default_value_t = 500)]            pub fault_deadline_ms: u64,
+    /// Simulated temperature model for the thermal sensor
+    #[arg(long, value_enum, default_value = "sawtooth")] pub thermal_profile: TempProfileKind,
+    /// Recorded "t_ms,temp_c" rows for --thermal-profile replay
+    #[arg(long)]                                   pub thermal_replay_csv: Option<String>,
+    /// Buffer fill % above which Normal readings are decimated (>= 100 disables)
+    #[arg(long, default_value_t = 60.0)]           pub downsample_fill_pct: f64,
+    /// Rotate each logs/*.csv once it passes this many bytes (0 = never)
+    #[arg(long, default_value_t = 0)]              pub log_rotate_bytes: u64,
+    /// Repeats of the same emergency within this window are counted, not sent (0 = off)
+    #[arg(long, default_value_t = 2000)]           pub emergency_coalesce_ms: u64,
+    /// Log output: csv, json (NDJSON .jsonl files) or both
+    #[arg(long, value_enum, default_value = "csv")] pub log_format: LogFormat,
+    /// Share of each batch (%) reserved for Important/Normal readings when queued
+    #[arg(long, default_value_t = 20.0)]           pub lower_quota_pct: f64,
+    /// A sensor silent for this many of its periods is reported stalled
+    #[arg(long, default_value_t = 5)]              pub watchdog_periods: u32,
+    /// Seed for fault ids and the "random" fault schedule (unset = entropy)
+    #[arg(long, env = "OCS_RNG_SEED")]             pub rng_seed: Option<u64>,
+    /// Test mode: derive AEAD nonces from --rng-seed so runs are byte-identical
+    #[arg(long)]                                   pub seeded_nonces: bool,
+    /// Scheduler CPU accounting window (cpu.csv row and heartbeat utilization)
+    #[arg(long, default_value_t = 1000)]           pub cpu_window_ms: u64,
+    /// Sensor tasks as "kind:id:location[:interval_ms[:t0:t1]]", comma-separated
+    #[arg(long, value_delimiter = ',', default_value = DEFAULT_SENSORS)]
+    pub sensors: Vec<SensorDef>,
+    /// Frame cipher: chacha20-poly1305 or aes-256-gcm (ground follows each frame's tag)
+    #[arg(long, default_value = "chacha20-poly1305")] pub aead: AeadAlgo,
+    /// Serve Prometheus metrics on http://0.0.0.0:PORT/metrics (unset = off)
+    #[arg(long)]                                   pub metrics_port: Option<u16>,
+    /// Buffer fill % that sends batches at once instead of on the next tick (>= 100 disables)
+    #[arg(long, default_value_t = 50.0)]           pub flush_fill_pct: f64,
+    /// Full buffer: head-drop (evict lowest priority), tail-drop (reject new) or priority-evict
+    #[arg(long, value_enum, default_value = "head-drop")] pub drop_policy: DropPolicy,
+    /// Random delay (0..=ms, seeded by --rng-seed) before each sensor sample; 0 = off
+    #[arg(long, default_value_t = 0.0)]            pub sensor_jitter_ms: f64,
+    /// Oldest buffered reading may wait this long before a send is forced (0 = no SLA)
+    #[arg(long, default_value_t = 0)]              pub max_age_ms: u64,
+    /// Max share of each batch per priority as "critical,important,normal" fractions
+    #[arg(long, default_value = "1,1,1")]          pub batch_caps: BatchCaps,
+    /// On-board clock rate error against ground time (ppm); ClockCorrection steps it back
+    #[arg(long, default_value_t = 0.0)]            pub clock_drift_ppm: f64,
+    /// Send Emergency readings at once, one per packet, instead of buffering them
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)] pub emergency_fast_path: bool,
+    /// Logs (e.g. sensors,scheduler) written as compact binary logs/<stem>.bin instead
+    #[arg(long, value_delimiter = ',')]            pub binary_logs: Vec<String>,
+    /// Heartbeat period; ground declares loss of signal after a few missed ones
+    #[arg(long, default_value_t = 1000)]           pub heartbeat_ms: u64,
+    /// Job execution time around WCET: fixed, uniform:BCET_FRAC or normal:MEAN:SD:MAX
+    #[arg(long, default_value = "fixed")]          pub exec_model: ExecModel,
+    /// Set SO_REUSEADDR on the command socket so a restart can rebind at once
+    #[arg(long)]                                   pub udp_reuse_addr: bool,
+    /// Kernel receive buffer for the command socket, bytes (0 = OS default)
+    #[arg(long, default_value_t = 0)]              pub udp_recv_buffer: usize,
+    /// Link emulation: chance (%) each outgoing frame is lost (0 = off)
+    #[arg(long, default_value_t = 0.0)]            pub link_drop_pct: f64,
+    /// Link emulation: chance (%) a delivered frame arrives twice
+    #[arg(long, default_value_t = 0.0)]            pub link_dup_pct: f64,
+    /// Link emulation: extra one-way delay added to every frame
+    #[arg(long, default_value_t = 0)]              pub link_latency_ms: u64,
+    /// Link emulation: uniform 0..=jitter added on top of the latency
+    #[arg(long, default_value_t = 0)]              pub link_jitter_ms: u64,
+    /// Answer non-command payloads on the command socket with a "failed" ACK keyed by packet id
+    #[arg(long)]                                   pub nack_unexpected: bool,
+    /// Interval of the summary log line / summary.csv row (0 = off)
+    #[arg(long, default_value_t = 10)]             pub summary_secs: u64,
+    /// Exchange capabilities with ground before telemetry, waiting up to this long (0 = skip)
+    #[arg(long, default_value_t = 0)]              pub handshake_timeout_ms: u64,
+    /// Readings kept per sensor for DataRequest replays and snapshots
+    #[arg(long, default_value_t = 32)]             pub history_depth: usize,
+    /// Shut down on its own after this many seconds (0 = run until Ctrl+C)
+    #[arg(long, default_value_t = 0)]              pub run_for_secs: u64,
+    /// UDP copies of each Critical emergency (1 = send once)
+    #[arg(long, default_value_t = 3)]              pub emergency_repeats: u32,
+    /// Spacing between repeated emergency copies
+    #[arg(long, default_value_t = 20)]             pub emergency_repeat_ms: u64,
+    /// Repeat High-severity emergencies too, not just Critical
+    #[arg(long)]                                   pub emergency_repeat_high: bool,
+    /// Reserved buffer slots per priority as "critical,important,normal" (unset = one shared pool)
+    #[arg(long)]                                   pub bucket_caps: Option<BucketCaps>,
+    /// Authentication failures within --auth-alert-window-ms that raise a security alert (0 = off)
+    #[arg(long, default_value_t = 5)]              pub auth_alert_threshold: usize,
+    /// Sliding window for --auth-alert-threshold
+    #[arg(long, default_value_t = 10_000)]         pub auth_alert_window_ms: u64,
+    /// Directory the logs and the blackbox dump are written to
+    #[arg(long, default_value = "logs")]           pub log_dir: String,
 }
 
 impl Cli {
-    pub fn parse_and_build_config() -> Result<Config> {
-        let c = <Cli as Parser>::parse();
-        Ok(Config {
+    /// Runtime config plus the subcommand, if one was given.
+    pub fn parse_and_build_config() -> Result<(Config, Option<CliCommand>)> {
+        let mut cli = <Cli as Parser>::parse();
+        let command = cli.command.take();
+        Ok((cli.into(), command))
+    }
+}
+
+impl From<Cli> for Config {
+    fn from(c: Cli) -> Self {
+        Config {
             gcs_addr: c.gcs_addr,
             bind_addr: c.bind_addr,
             key_id: c.key_id,
-            key_hex: c.key_hex,
+            key: match (c.key_passphrase, c.key_salt) {
+                (Some(passphrase), Some(salt)) => KeySource::Passphrase { passphrase, salt },
+                _ => KeySource::Hex(c.key_hex),
+            },
             batch_ms: c.batch_ms,
             max_batch: c.max_batch,
-        })
+            sched_policy: c.sched_policy,
+            thermal_min_interarrival_ms: c.thermal_min_interarrival_ms,
+            aging_ms: c.aging_ms,
+            cmd_tcp_addr: c.cmd_tcp_addr,
+            mtu: c.mtu,
+            downlink_interval_ms: c.downlink_interval_ms,
+            downlink_window_ms: c.downlink_window_ms,
+            downlink_schedule: c.downlink_schedule,
+            fault_schedule: c.fault_schedule,
+            fault_soft_ms: c.fault_soft_ms,
+            fault_hard_ms: c.fault_hard_ms,
+            fault_deadline_ms: c.fault_deadline_ms,
+            thermal_profile: c.thermal_profile,
+            thermal_replay_csv: c.thermal_replay_csv,
+            downsample_fill_pct: c.downsample_fill_pct,
+            log_rotate_bytes: c.log_rotate_bytes,
+            emergency_coalesce_ms: c.emergency_coalesce_ms,
+            log_format: c.log_format,
+            lower_quota_pct: c.lower_quota_pct,
+            watchdog_periods: c.watchdog_periods,
+            rng_seed: c.rng_seed,
+            seeded_nonces: c.seeded_nonces,
+            cpu_window_ms: c.cpu_window_ms,
+            sensors: c.sensors,
+            aead: c.aead,
+            metrics_port: c.metrics_port,
+            flush_fill_pct: c.flush_fill_pct,
+            drop_policy: c.drop_policy,
+            sensor_jitter_ms: c.sensor_jitter_ms,
+            max_age_ms: c.max_age_ms,
+            batch_caps: c.batch_caps,
+            clock_drift_ppm: c.clock_drift_ppm,
+            emergency_fast_path: c.emergency_fast_path,
+            binary_logs: c.binary_logs,
+            heartbeat_ms: c.heartbeat_ms,
+            exec_model: c.exec_model,
+            udp_reuse_addr: c.udp_reuse_addr,
+            udp_recv_buffer: c.udp_recv_buffer,
+            link_drop_pct: c.link_drop_pct,
+            link_dup_pct: c.link_dup_pct,
+            link_latency_ms: c.link_latency_ms,
+            link_jitter_ms: c.link_jitter_ms,
+            nack_unexpected: c.nack_unexpected,
+            summary_secs: c.summary_secs,
+            handshake_timeout_ms: c.handshake_timeout_ms,
+            history_depth: c.history_depth,
+            run_for_secs: c.run_for_secs,
+            emergency_repeats: c.emergency_repeats,
+            emergency_repeat_ms: c.emergency_repeat_ms,
+            emergency_repeat_high: c.emergency_repeat_high,
+            bucket_caps: c.bucket_caps,
+            auth_alert_threshold: c.auth_alert_threshold,
+            auth_alert_window_ms: c.auth_alert_window_ms,
+            log_dir: c.log_dir,
+        }
+    }
+}
+
+/// The runtime config as changed in flight by `PacketType::Config` updates. Readers take
+/// a snapshot per loop; `subscribe` wakes tasks that must re-arm timers.
+#[derive(Clone)]
+pub struct LiveConfig(Arc<watch::Sender<Arc<Config>>>);
+
+/// Set by the batcher at spawn; the command handler applies updates through it.
+pub static LIVE: OnceCell<LiveConfig> = OnceCell::new();
+
+impl LiveConfig {
+    pub fn new(cfg: Config) -> Self {
+        Self(Arc::new(watch::Sender::new(Arc::new(cfg))))
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.0.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.0.subscribe()
+    }
+
+    /// Apply every override or none. Keys: batch_ms, max_batch, flush_fill_pct,
+    /// downsample_fill_pct, lower_quota_pct and `threshold.<sensor_id>` = "a:b".
+    pub fn apply(&self, overrides: &BTreeMap<String, String>) -> Result<(), String> {
+        fn num<T: std::str::FromStr>(key: &str, v: &str) -> Result<T, String> {
+            v.trim().parse().map_err(|_| format!("{key}: bad value '{v}'"))
+        }
+        fn pct(key: &str, v: &str) -> Result<f64, String> {
+            let p: f64 = num(key, v)?;
+            if !(0.0..=100.0).contains(&p) {
+                return Err(format!("{key}: {p} is not a percentage"));
+            }
+            Ok(p)
+        }
+
+        let mut next = (*self.get()).clone();
+        let mut thresholds = Vec::new();
+        for (key, v) in overrides {
+            match key.as_str() {
+                "batch_ms" => next.batch_ms = num(key, v)?,
+                "max_batch" => next.max_batch = num(key, v)?,
+                "flush_fill_pct" => next.flush_fill_pct = pct(key, v)?,
+                "downsample_fill_pct" => next.downsample_fill_pct = pct(key, v)?,
+                "lower_quota_pct" => next.lower_quota_pct = pct(key, v)?,
+                _ => {
+                    let Some(id) = key.strip_prefix("threshold.") else {
+                        return Err(format!("unknown config key '{key}'"));
+                    };
+                    let (a, b) = v.split_once(':').ok_or_else(|| format!("{key}: expected 'a:b'"))?;
+                    thresholds.push((num(key, id)?, [num(key, a)?, num(key, b)?]));
+                }
+            }
+        }
+        if next.batch_ms == 0 || next.max_batch == 0 {
+            return Err("batch_ms and max_batch must be positive".into());
+        }
+        // thresholds are checked as a set before anything is written
+        crate::sensors::thresholds::replace_all(&thresholds)?;
+        self.0.send_replace(Arc::new(next));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    /// Config with every CLI default, for unit tests.
+    pub fn test_default() -> Self {
+        <Cli as Parser>::parse_from(["satellite_ocs"]).into()
     }
 }
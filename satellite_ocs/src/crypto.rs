@@ -1,8 +1,41 @@
 // src/crypto.rs (recap)
 use std::sync::Arc;
 use anyhow::{bail, Result};
-use shared_protocol::{CommunicationPacket, CryptoContext};
-use crate::config::Config;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use shared_protocol::{
+    Capabilities, CommunicationPacket, CryptoContext, CryptoError, Negotiated, NegotiationError, NonceStrategy,
+    DEFAULT_REPLAY_WINDOW,
+};
+use crate::config::{Config, KeySource};
+
+// HKDF "info": binds derived keys to this protocol so the same passphrase used
+// elsewhere yields a different key.
+const KDF_INFO: &[u8] = b"rts-ocs frame key v1";
+
+/// HKDF-SHA256(passphrase, salt) → frame key. Deterministic: ground derives the same key.
+pub fn derive_key(passphrase: &str, salt: &str) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt.as_bytes()), passphrase.as_bytes())
+        .expand(KDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn load_key(source: &KeySource) -> Result<[u8; 32]> {
+    match source {
+        KeySource::Hex(text) => {
+            let bytes = hex::decode(text).map_err(|e| anyhow::anyhow!("invalid key_hex: {e}"))?;
+            if bytes.len() != 32 { bail!("key_hex must be 64 hex chars"); }
+            let mut key = [0u8; 32]; key.copy_from_slice(&bytes);
+            Ok(key)
+        }
+        KeySource::Passphrase { passphrase, salt } => {
+            if passphrase.is_empty() || salt.is_empty() { bail!("key passphrase and salt must not be empty"); }
+            Ok(derive_key(passphrase, salt))
+        }
+    }
+}
 
 pub struct Crypto {
     ctx: Arc<CryptoContext>,
@@ -10,20 +43,53 @@ pub struct Crypto {
 }
 
 impl Crypto {
+    /// Builds the context once (any key derivation happens here, not per packet).
     pub fn from_config(cfg: &Config) -> Result<Self> {
-        let bytes = hex::decode(&cfg.key_hex)
-            .map_err(|e| anyhow::anyhow!("invalid key_hex: {e}"))?;
-        if bytes.len() != 32 { bail!("key_hex must be 64 hex chars"); }
-        let mut key = [0u8; 32]; key.copy_from_slice(&bytes);
-        Ok(Self { ctx: Arc::new(CryptoContext::new(cfg.key_id, key)), key_id: cfg.key_id })
+        let key = load_key(&cfg.key)?;
+        let mut ctx = CryptoContext::new(cfg.key_id, key, DEFAULT_REPLAY_WINDOW).with_algo(cfg.aead);
+        if cfg.seeded_nonces {
+            let Some(seed) = cfg.rng_seed else { bail!("--seeded-nonces needs --rng-seed") };
+            ctx = ctx.with_nonce_strategy(NonceStrategy::Seeded(seed));
+        }
+        Ok(Self { ctx: Arc::new(ctx), key_id: cfg.key_id })
     }
-    #[inline] pub fn seal(&self, pkt: &CommunicationPacket) -> Result<Vec<u8>, String> {
+    #[inline] pub fn seal(&self, pkt: &CommunicationPacket) -> Result<Vec<u8>, CryptoError> {
         self.ctx.seal_to_bytes(pkt)
     }
-    #[inline] pub fn open(&self, frame: &[u8]) -> Result<CommunicationPacket, String> {
+    #[inline] pub fn open(&self, frame: &[u8]) -> Result<CommunicationPacket, CryptoError> {
         self.ctx.open_from_bytes(frame)
     }
+    pub fn capabilities(&self) -> Capabilities {
+        self.ctx.capabilities().clone()
+    }
+    /// Settle on a common set with ground's advertisement; later seals use it.
+    pub fn negotiate(&self, peer: &Capabilities) -> Result<Negotiated, NegotiationError> {
+        self.ctx.negotiate(peer)
+    }
 }
 impl Clone for Crypto {
     fn clone(&self) -> Self { Self { ctx: self.ctx.clone(), key_id: self.key_id } }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::{Source, ThermalSensor};
+
+    #[test]
+    fn passphrase_key_is_stable_and_roundtrips() {
+        let key = derive_key("correct horse battery staple", "ocs-1");
+        assert_eq!(key, derive_key("correct horse battery staple", "ocs-1"));
+        assert_ne!(key, derive_key("correct horse battery staple", "ocs-2"));
+
+        let mut cfg = Config::test_default();
+        cfg.key = KeySource::Passphrase { passphrase: "correct horse battery staple".into(), salt: "ocs-1".into() };
+        let sat = Crypto::from_config(&cfg).unwrap();
+        let ground = CryptoContext::new(cfg.key_id, key, DEFAULT_REPLAY_WINDOW);
+        let pkt = CommunicationPacket::new_telemetry(vec![ThermalSensor::new(1, "CPU").create_reading(60.0, 0)], Source::Satellite);
+        assert_eq!(ground.open_from_bytes(&sat.seal(&pkt).unwrap()).unwrap().payload, pkt.payload);
+        // a different passphrase does not open it
+        let other = CryptoContext::new(cfg.key_id, derive_key("hunter2", "ocs-1"), DEFAULT_REPLAY_WINDOW);
+        assert!(other.open_from_bytes(&sat.seal(&pkt).unwrap()).is_err());
+    }
+}
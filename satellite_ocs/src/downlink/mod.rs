@@ -1,11 +1,22 @@
+use crate::{config::Config, logging};
 use once_cell::sync::OnceCell;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
 use tokio::time::{self, Duration, Instant};
 use tracing::{info, warn};
 
 pub static DL: OnceCell<Downlink> = OnceCell::new();
 
+/// Pointing error above which a ready link only runs degraded.
+pub const ALIGNMENT_DEGRADED_DEG: f64 = 1.0;
+/// Open-loop antenna drift while no `antenna_alignment` job corrects it.
+const POINTING_DRIFT_DEG_PER_S: f64 = 1.0;
+/// Share of the current pointing error each alignment job removes.
+const ALIGNMENT_GAIN: f64 = 0.8;
+/// The batcher is told to flush this long before a window closes (at most half the pass).
+const PRE_CLOSE_LEAD: Duration = Duration::from_millis(50);
+
 #[derive(Debug, Clone, Copy)]
 enum LinkState {
     Closed,
@@ -13,39 +24,106 @@ enum LinkState {
     Ready { opened_at: Instant, ready_at: Instant, degraded: bool },
 }
 
+#[derive(Debug)]
+struct Link {
+    state: LinkState,
+    window_opened: Option<Instant>, // start of the current pass (survives MissedInit)
+    closes_at: Option<Instant>,     // planned end of the current pass
+    reacquire_pending: bool,        // a pass was missed; flag the next good send
+    pointing_error_deg: f64,        // as of the last alignment job
+    aligned_at: Option<Instant>,    // drift accrues from here (None = never aligned)
+}
+
+impl Link {
+    fn pointing_error(&self, now: Instant) -> f64 {
+        let drift = self.aligned_at.map_or(0.0, |t| now.duration_since(t).as_secs_f64() * POINTING_DRIFT_DEG_PER_S);
+        self.pointing_error_deg + drift
+    }
+}
+
 #[derive(Clone)]
 pub struct Downlink {
-    inner: Arc<Mutex<LinkState>>,
+    inner: Arc<Mutex<Link>>,
+    closing: Arc<Notify>, // pre-close signal; one permit, consumed by the batcher
 }
 
 impl Downlink {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
-            inner: Arc::new(Mutex::new(LinkState::Closed)),
+            inner: Arc::new(Mutex::new(Link {
+                state: LinkState::Closed,
+                window_opened: None,
+                closes_at: None,
+                reacquire_pending: false,
+                pointing_error_deg: 0.0,
+                aligned_at: None,
+            })),
+            closing: Arc::new(Notify::new()),
         }
     }
 
-    async fn open(&self) {
-        let mut g = self.inner.lock().await;
-        *g = LinkState::Opening {
-            opened_at: Instant::now(),
-            init_started: false,
-        };
-        info!("downlink: window OPEN");
+    pub(crate) async fn open(&self, planned_ms: u64) {
+        {
+            let mut g = self.inner.lock().await;
+            let now = Instant::now();
+            g.state = LinkState::Opening {
+                opened_at: now,
+                init_started: false,
+            };
+            g.window_opened = Some(now);
+            g.closes_at = Some(now + Duration::from_millis(planned_ms));
+        }
+        info!(planned_ms, "downlink: window OPEN");
+        logging::csv::log_downlink(0, 0.0, 0.0, 0.0, "open", planned_ms as f64).await;
     }
 
     async fn close(&self) {
-        let mut g = self.inner.lock().await;
-        *g = LinkState::Closed;
-        info!("downlink: window CLOSED");
+        let open_ms = {
+            let mut g = self.inner.lock().await;
+            g.state = LinkState::Closed;
+            g.closes_at = None;
+            g.window_opened
+                .take()
+                .map(|t| t.elapsed().as_secs_f64() * 1000.0)
+                .unwrap_or(0.0)
+        };
+        info!(open_ms = format_args!("{:.1}", open_ms), "downlink: window CLOSED");
+        logging::csv::log_downlink(0, 0.0, 0.0, 0.0, "close", open_ms).await;
+    }
+
+    /// Time left in the current pass (`None` while closed).
+    pub async fn time_to_close(&self) -> Option<Duration> {
+        let g = self.inner.lock().await;
+        g.closes_at.map(|t| t.saturating_duration_since(Instant::now()))
+    }
+
+    /// Link state for status output: "closed", "opening", "ready" or "degraded".
+    pub async fn state(&self) -> &'static str {
+        match self.inner.lock().await.state {
+            LinkState::Closed => "closed",
+            LinkState::Opening { .. } => "opening",
+            LinkState::Ready { degraded: false, .. } => "ready",
+            LinkState::Ready { degraded: true, .. } => "degraded",
+        }
+    }
+
+    /// Resolves once the open window is about to close (`PRE_CLOSE_LEAD` ahead).
+    pub async fn closing(&self) {
+        self.closing.notified().await
+    }
+
+    /// Pre-close signal; the permit is kept if the batcher is busy right now.
+    pub(crate) fn announce_close(&self) {
+        self.closing.notify_one();
     }
 
     /// Called by batcher before a send; enforces 5ms init, checks 30ms prep.
+    /// The first sendable result after a `MissedInit` is reported as `Reacquired`.
     pub async fn pre_send(&self) -> DownlinkEvent {
         let mut g = self.inner.lock().await;
         let now = Instant::now();
 
-        match *g {
+        let ev = match g.state {
             LinkState::Closed => DownlinkEvent::NotInWindow,
             LinkState::Opening {
                 opened_at,
@@ -55,12 +133,14 @@ impl Downlink {
                 if since_open > Duration::from_millis(5) && !init_started {
                     // Missed 5ms init — treat as missed comms for this window
                     warn!("downlink: init >5ms → missed communication");
-                    *g = LinkState::Closed;
+                    logging::metrics::count(&logging::metrics::DOWNLINK_MISSED_INITS);
+                    g.state = LinkState::Closed;
+                    g.reacquire_pending = true;
                     DownlinkEvent::MissedInit
                 } else {
                     // Lazily start init on first attempt; become ready quickly (simulate)
                     let ready_at = now;
-                    *g = LinkState::Ready {
+                    g.state = LinkState::Ready {
                         opened_at,
                         ready_at,
                         degraded: false,
@@ -87,29 +167,50 @@ impl Downlink {
                     DownlinkEvent::Ready
                 }
             }
+        };
+
+        // closed-loop pointing: a stalled alignment task costs link quality
+        let ev = match ev {
+            DownlinkEvent::Ready if g.pointing_error(now) > ALIGNMENT_DEGRADED_DEG => DownlinkEvent::ReadyDegraded,
+            ev => ev,
+        };
+
+        if g.reacquire_pending && ev.can_send() {
+            g.reacquire_pending = false;
+            info!("downlink: link re-acquired");
+            return DownlinkEvent::Reacquired;
         }
+        ev
+    }
+
+    /// One `antenna_alignment` job: fold in the drift since the last correction, then
+    /// remove `ALIGNMENT_GAIN` of it. Returns the residual error (degrees).
+    pub async fn align(&self) -> f64 {
+        let mut g = self.inner.lock().await;
+        let now = Instant::now();
+        g.pointing_error_deg = g.pointing_error(now) * (1.0 - ALIGNMENT_GAIN);
+        g.aligned_at = Some(now);
+        g.pointing_error_deg
     }
 
     pub async fn set_degraded(&self, on: bool) {
         let mut g = self.inner.lock().await;
-        match *g {
-            LinkState::Ready {
+        if let LinkState::Ready {
+            opened_at,
+            ready_at,
+            ..
+        } = g.state
+        {
+            g.state = LinkState::Ready {
                 opened_at,
                 ready_at,
-                ..
-            } => {
-                *g = LinkState::Ready {
-                    opened_at,
-                    ready_at,
-                    degraded: on,
-                };
-                if on {
-                    warn!("downlink: DEGRADED mode enabled (buffer > 80%)");
-                } else {
-                    info!("downlink: degraded mode cleared");
-                }
+                degraded: on,
+            };
+            if on {
+                warn!("downlink: DEGRADED mode enabled (buffer > 80%)");
+            } else {
+                info!("downlink: degraded mode cleared");
             }
-            _ => {}
         }
     }
 }
@@ -121,23 +222,158 @@ pub enum DownlinkEvent {
     Ready,
     ReadyPrepLate { prep_ms: f64 },
     ReadyDegraded,
+    /// First sendable window after a missed init
+    Reacquired,
 }
 
-/// Simulate visibility windows (e.g., every 5s open for 800ms)
-pub fn init_and_spawn() {
-    let dl = DL.get_or_init(|| Downlink::new()).clone();
+impl DownlinkEvent {
+    /// Whether a batch may go out under this event.
+    pub fn can_send(&self) -> bool {
+        !matches!(self, DownlinkEvent::NotInWindow | DownlinkEvent::MissedInit)
+    }
 
-    tokio::spawn(async move {
-        let mut ticker = time::interval(Duration::from_millis(5000));
-        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DownlinkEvent::NotInWindow => "not_in_window",
+            DownlinkEvent::MissedInit => "missed_init",
+            DownlinkEvent::Ready => "ready",
+            DownlinkEvent::ReadyPrepLate { .. } => "ready_prep_late",
+            DownlinkEvent::ReadyDegraded => "ready_degraded",
+            DownlinkEvent::Reacquired => "reacquired",
+        }
+    }
+}
 
-        loop {
-            ticker.tick().await;
-            dl.open().await;
+/// One visibility pass, relative to the start of the schedule cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
 
-            // Keep window open for 800ms
-            time::sleep(Duration::from_millis(800)).await;
-            dl.close().await;
+/// Parse "start_ms:duration_ms,..." (starts ascending, passes must not overlap).
+pub fn parse_schedule(s: &str) -> Result<Vec<Window>, String> {
+    let mut out: Vec<Window> = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, dur) = part
+            .split_once(':')
+            .ok_or_else(|| format!("bad window '{part}': expected start_ms:duration_ms"))?;
+        let w = Window {
+            start_ms: start.trim().parse().map_err(|e| format!("bad start in '{part}': {e}"))?,
+            duration_ms: dur.trim().parse().map_err(|e| format!("bad duration in '{part}': {e}"))?,
+        };
+        if w.duration_ms == 0 {
+            return Err(format!("window '{part}' has zero duration"));
+        }
+        if let Some(prev) = out.last()
+            && w.start_ms < prev.start_ms + prev.duration_ms
+        {
+            return Err(format!("window '{part}' overlaps the previous one"));
         }
-    });
+        out.push(w);
+    }
+    if out.is_empty() {
+        return Err("empty downlink schedule".into());
+    }
+    Ok(out)
+}
+
+/// Open/close `dl` following `windows`; the cycle restarts `interval_ms` after the
+/// last window's start.
+fn spawn_schedule(dl: Downlink, windows: Vec<Window>, interval_ms: u64) -> JoinHandle<()> {
+    let last_start = windows.last().map(|w| w.start_ms).unwrap_or(0);
+    let cycle = Duration::from_millis(last_start + interval_ms);
+
+    tokio::spawn(async move {
+        loop {
+            let cycle_start = Instant::now();
+            for w in &windows {
+                time::sleep_until(cycle_start + Duration::from_millis(w.start_ms)).await;
+                dl.open(w.duration_ms).await;
+                let pass = Duration::from_millis(w.duration_ms);
+                let lead = PRE_CLOSE_LEAD.min(pass / 2);
+                time::sleep(pass - lead).await;
+                dl.announce_close();
+                time::sleep(lead).await;
+                dl.close().await;
+            }
+            time::sleep_until(cycle_start + cycle).await;
+        }
+    })
+}
+
+/// Simulate visibility windows: `--downlink-schedule` if given, otherwise
+/// every `downlink_interval_ms` open for `downlink_window_ms` (default 5s / 800ms).
+pub fn init_and_spawn(cfg: &Config) -> Result<JoinHandle<()>, String> {
+    let windows = match cfg.downlink_schedule.as_deref() {
+        Some(s) => parse_schedule(s)?,
+        None => vec![Window { start_ms: 0, duration_ms: cfg.downlink_window_ms }],
+    };
+    let dl = DL.get_or_init(Downlink::new).clone();
+    info!(?windows, interval_ms = cfg.downlink_interval_ms, "downlink: schedule loaded");
+    Ok(spawn_schedule(dl, windows, cfg.downlink_interval_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_explicit_schedule() {
+        let w = parse_schedule("0:800,5000:800,12000:1500").unwrap();
+        assert_eq!(w.len(), 3);
+        assert_eq!(w[2], Window { start_ms: 12000, duration_ms: 1500 });
+        assert!(parse_schedule("0:800,500:100").is_err()); // overlap
+        assert!(parse_schedule("0-800").is_err());
+    }
+
+    #[tokio::test]
+    async fn pre_send_is_not_in_window_outside_short_pass() {
+        let dl = Downlink::new();
+        let task = spawn_schedule(dl.clone(), vec![Window { start_ms: 0, duration_ms: 30 }], 10_000);
+
+        time::sleep(Duration::from_millis(2)).await;
+        assert!(!matches!(dl.pre_send().await, DownlinkEvent::NotInWindow));
+
+        time::sleep(Duration::from_millis(60)).await;
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::NotInWindow));
+        task.abort();
+        // the pass announced its close before it ended
+        time::timeout(Duration::from_millis(10), dl.closing()).await.expect("no pre-close signal");
+        assert_eq!(dl.time_to_close().await, None);
+    }
+
+    #[tokio::test]
+    async fn missed_pass_is_followed_by_reacquired_and_logged() {
+        let dl = Downlink::new();
+        dl.open(37).await;
+        time::sleep(Duration::from_millis(10)).await; // past the 5ms init budget
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::MissedInit));
+        dl.close().await;
+
+        dl.open(41).await;
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::Reacquired));
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::Ready));
+        dl.close().await;
+
+        let csv = std::fs::read_to_string(crate::logging::csv::log_path("downlink.csv")).unwrap();
+        assert!(csv.lines().any(|l| l.ends_with(",open,37.0")), "{csv}");
+        assert!(csv.lines().any(|l| l.ends_with(",open,41.0")), "{csv}");
+        assert!(csv.lines().any(|l| l.contains(",close,")), "{csv}");
+    }
+
+    #[tokio::test]
+    async fn pointing_error_degrades_the_link_until_aligned() {
+        let dl = Downlink::new();
+        dl.inner.lock().await.pointing_error_deg = 5.0;
+        dl.open(800).await;
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::ReadyDegraded));
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::ReadyDegraded));
+
+        // two corrections: 5.0 → 1.0 → ~0.2 degrees
+        dl.align().await;
+        assert!(dl.align().await < ALIGNMENT_DEGRADED_DEG);
+        assert!(matches!(dl.pre_send().await, DownlinkEvent::Ready));
+        dl.close().await;
+    }
 }
@@ -5,5 +5,6 @@ use thiserror::Error;
 pub enum OcsError {
     #[error("IO: {0}")] Io(#[from] std::io::Error),
     #[error("Protocol: {0}")] Protocol(String),
+    #[error("Crypto: {0}")] Crypto(#[from] shared_protocol::CryptoError),
     #[error("Other: {0}")] Other(String),
 }
@@ -0,0 +1,187 @@
+// src/faults/schedule.rs
+// Declarative fault schedule: "at_ms:kind:target:duration_ms[:param],..."
+use std::path::Path;
+
+use rand::Rng;
+
+use super::CorruptMode;
+
+/// What to break. `param` on the schedule entry is the extra delay (Delay) or the
+/// timestamp shift (ClockSkew); other kinds ignore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultKind {
+    Delay,                // thermal only
+    Corrupt(CorruptMode), // power only
+    Pause,                // attitude only
+    Dropout,              // any sensor: no readings produced
+    ClockSkew,            // any sensor: timestamps shifted by `param` ms
+}
+
+impl FaultKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultKind::Delay => "delay",
+            FaultKind::Corrupt(CorruptMode::Full) => "corrupt",
+            FaultKind::Corrupt(CorruptMode::PartialCurrent) => "corrupt_current",
+            FaultKind::Pause => "pause",
+            FaultKind::Dropout => "dropout",
+            FaultKind::ClockSkew => "clock_skew",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultSpec {
+    pub at_ms: u64, // offset from injector start
+    pub kind: FaultKind,
+    pub target: String,
+    pub duration_ms: u64,
+    pub param: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultSchedule {
+    pub entries: Vec<FaultSpec>,
+    /// Restart the schedule every `repeat_ms` (None = run once)
+    pub repeat_ms: Option<u64>,
+}
+
+impl Default for FaultSchedule {
+    /// The original rotation: power corrupt, attitude pause, thermal delay, one per minute.
+    fn default() -> Self {
+        let spec = |at_ms, kind, target: &str, duration_ms, param| FaultSpec {
+            at_ms,
+            kind,
+            target: target.into(),
+            duration_ms,
+            param,
+        };
+        Self {
+            entries: vec![
+                spec(0, FaultKind::Corrupt(CorruptMode::Full), "power", 200, 0),
+                spec(60_000, FaultKind::Pause, "attitude", 150, 0),
+                spec(120_000, FaultKind::Delay, "thermal", 150, 10),
+            ],
+            repeat_ms: Some(180_000),
+        }
+    }
+}
+
+const SENSORS: [&str; 4] = ["thermal", "power", "attitude", "radiation"];
+
+/// Parse entries separated by commas or newlines; `#` starts a comment.
+pub fn parse_schedule(s: &str) -> Result<FaultSchedule, String> {
+    let mut entries: Vec<FaultSpec> = Vec::new();
+    let parts = s
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or(""))
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|p| !p.is_empty());
+
+    for part in parts {
+        let f: Vec<&str> = part.split(':').map(str::trim).collect();
+        if !(4..=5).contains(&f.len()) {
+            return Err(format!(
+                "bad fault '{part}': expected at_ms:kind:target:duration_ms[:param]"
+            ));
+        }
+        let kind = match f[1] {
+            "delay" => FaultKind::Delay,
+            "corrupt" => FaultKind::Corrupt(CorruptMode::Full),
+            "corrupt_current" => FaultKind::Corrupt(CorruptMode::PartialCurrent),
+            "pause" => FaultKind::Pause,
+            "dropout" => FaultKind::Dropout,
+            "clock_skew" => FaultKind::ClockSkew,
+            other => return Err(format!("unknown fault kind '{other}' in '{part}'")),
+        };
+        let target = f[2];
+        let target_ok = match kind {
+            FaultKind::Delay => target == "thermal",
+            FaultKind::Corrupt(_) => target == "power",
+            FaultKind::Pause => target == "attitude",
+            FaultKind::Dropout | FaultKind::ClockSkew => SENSORS.contains(&target),
+        };
+        if !target_ok {
+            return Err(format!("fault '{}' cannot target '{target}'", kind.as_str()));
+        }
+        let spec = FaultSpec {
+            at_ms: f[0].parse().map_err(|e| format!("bad at_ms in '{part}': {e}"))?,
+            kind,
+            target: target.into(),
+            duration_ms: f[3].parse().map_err(|e| format!("bad duration in '{part}': {e}"))?,
+            param: match f.get(4) {
+                Some(p) => p.parse().map_err(|e| format!("bad param in '{part}': {e}"))?,
+                None if kind == FaultKind::Delay => 10,
+                None if kind == FaultKind::ClockSkew => 250,
+                None => 0,
+            },
+        };
+        if let Some(prev) = entries.last()
+            && spec.at_ms < prev.at_ms
+        {
+            return Err(format!("fault '{part}' is earlier than the previous entry"));
+        }
+        entries.push(spec);
+    }
+    if entries.is_empty() {
+        return Err("empty fault schedule".into());
+    }
+    Ok(FaultSchedule { entries, repeat_ms: None })
+}
+
+/// `--fault-schedule random`: `count` faults drawn from `rng`, each 1..=`max_gap_ms` after
+/// the previous and lasting 10..=`max_duration_ms`; the drawn set then repeats. The same
+/// seed gives the same schedule.
+pub fn random_schedule(rng: &mut impl Rng, count: usize, max_gap_ms: u64, max_duration_ms: u64) -> FaultSchedule {
+    let mut at_ms = 0;
+    let entries = (0..count)
+        .map(|i| {
+            if i > 0 {
+                at_ms += rng.random_range(1..=max_gap_ms);
+            }
+            let any_sensor = SENSORS[rng.random_range(0..SENSORS.len())];
+            let (kind, target, param) = match rng.random_range(0..6) {
+                0 => (FaultKind::Corrupt(CorruptMode::Full), "power", 0),
+                1 => (FaultKind::Corrupt(CorruptMode::PartialCurrent), "power", 0),
+                2 => (FaultKind::Pause, "attitude", 0),
+                3 => (FaultKind::Delay, "thermal", rng.random_range(5..=20)),
+                4 => (FaultKind::Dropout, any_sensor, 0),
+                _ => (FaultKind::ClockSkew, any_sensor, rng.random_range(-500..=500)),
+            };
+            FaultSpec {
+                at_ms,
+                kind,
+                target: target.into(),
+                duration_ms: rng.random_range(10..=max_duration_ms.max(10)),
+                param,
+            }
+        })
+        .collect();
+    FaultSchedule { entries, repeat_ms: Some(at_ms + max_gap_ms) }
+}
+
+/// `src` is either a path to a schedule file or the schedule itself.
+pub fn load_schedule(src: &str) -> Result<FaultSchedule, String> {
+    if Path::new(src).is_file() {
+        let text = std::fs::read_to_string(src).map_err(|e| format!("read {src}: {e}"))?;
+        parse_schedule(&text)
+    } else {
+        parse_schedule(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries_and_rejects_bad_targets() {
+        let s = parse_schedule("0:dropout:power:300\n# skew\n500:clock_skew:thermal:200:-40").unwrap();
+        assert_eq!(s.entries.len(), 2);
+        assert_eq!(s.entries[0].kind, FaultKind::Dropout);
+        assert_eq!(s.entries[1].param, -40);
+        assert!(parse_schedule("0:delay:power:100").is_err());
+        assert!(parse_schedule("100:pause:attitude:50,0:pause:attitude:50").is_err());
+    }
+}
@@ -1,29 +1,110 @@
 // health/heartbeat.rs
 use crate::{config::Config, crypto::Crypto};
 use std::sync::Arc;
-use tokio::net::UdpSocket;
+use std::time::Instant;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use crate::net::transport::Transport;
 use tokio::time::{self, Duration};
 use tracing::warn;
 use shared_protocol::{SystemHealth, CommunicationPacket, Source};
 use chrono::Utc;
 
-pub async fn spawn_heartbeat(_cfg: Config, crypto: Crypto, sock: Arc<UdpSocket>) {
+/// Process resource usage for the heartbeat.
+pub trait MetricsSource: Send + 'static {
+    /// (cpu %, memory % of total RAM) for this process
+    fn sample(&mut self) -> (f64, f64);
+}
+
+/// `sysinfo`-backed metrics for the current process.
+pub struct SysinfoMetrics {
+    sys: System,
+    pid: Option<Pid>,
+}
+
+impl SysinfoMetrics {
+    pub fn new() -> Self {
+        Self { sys: System::new(), pid: sysinfo::get_current_pid().ok() }
+    }
+}
+
+impl MetricsSource for SysinfoMetrics {
+    fn sample(&mut self) -> (f64, f64) {
+        let Some(pid) = self.pid else { return (0.0, 0.0) };
+        self.sys.refresh_memory();
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+        let total = self.sys.total_memory();
+        match self.sys.process(pid) {
+            Some(p) => {
+                let mem_pct = if total > 0 { p.memory() as f64 / total as f64 * 100.0 } else { 0.0 };
+                (p.cpu_usage() as f64, mem_pct)
+            }
+            None => (0.0, 0.0),
+        }
+    }
+}
+
+/// Reports the scheduler's own utilization (last `cpu_window_ms` window) as CPU %, falling
+/// back to the wrapped source until the first window closes; memory always comes from it.
+pub struct SchedulerCpu<M>(pub M);
+
+impl<M: MetricsSource> MetricsSource for SchedulerCpu<M> {
+    fn sample(&mut self) -> (f64, f64) {
+        let (cpu, mem) = self.0.sample();
+        (crate::scheduler::cpu_utilization().unwrap_or(cpu), mem)
+    }
+}
+
+/// Builds one `SystemHealth` per heartbeat; remembers the miss total so the
+/// status reflects only the last window.
+struct HealthReporter<M> {
+    started: Instant,
+    metrics: M,
+    last_total_misses: u64,
+}
+
+impl<M: MetricsSource> HealthReporter<M> {
+    fn report(&mut self, now: Instant) -> SystemHealth {
+        // deadline misses recorded by the scheduler since boot
+        let deadline_misses = crate::scheduler::deadline_miss_snapshot();
+        let total_misses: u64 = deadline_misses.values().sum();
+        let new_misses = total_misses.saturating_sub(self.last_total_misses);
+        self.last_total_misses = total_misses;
+
+        let (cpu, mem) = self.metrics.sample();
+        SystemHealth {
+            overall_status: if new_misses > 0 { "degraded" } else { "nominal" }.into(),
+            cpu_usage_percent: cpu,
+            memory_usage_percent: mem,
+            disk_usage_percent: 0.0,
+            uptime_seconds: now.duration_since(self.started).as_secs(),
+            active_tasks: crate::scheduler::active_tasks(),
+            failed_tasks: total_misses.min(u32::MAX as u64) as u32,
+            timestamp: Utc::now(),
+            deadline_misses,
+            ingress_backpressure: crate::telemetry::backpressure_count(),
+        }
+    }
+}
+
+/// `started` is the process start captured in main.
+pub async fn spawn_heartbeat(
+    cfg: Config,
+    crypto: Crypto,
+    sock: Arc<dyn Transport>,
+    started: Instant,
+    metrics: impl MetricsSource,
+) -> tokio::task::JoinHandle<()> {
+    let mut reporter = HealthReporter { started, metrics, last_total_misses: 0 };
     tokio::spawn(async move {
-        let mut tick = time::interval(Duration::from_secs(1)); // tune as needed
+        let mut tick = time::interval(Duration::from_millis(cfg.heartbeat_ms.max(1)));
         loop {
             tick.tick().await;
 
-            let hb = SystemHealth {
-                overall_status: "nominal".into(),
-                cpu_usage_percent: 0.0,
-                memory_usage_percent: 0.0,
-                disk_usage_percent: 0.0,
-                uptime_seconds: 0,
-                active_tasks: 0,
-                failed_tasks: 0,
-                timestamp: Utc::now(),
-            };
-
+            let hb = reporter.report(Instant::now());
             let pkt = CommunicationPacket::new_heartbeat(hb, Source::Satellite);
             match crypto.seal(&pkt) {
                 Ok(bytes) => {
@@ -34,5 +115,28 @@ pub async fn spawn_heartbeat(_cfg: Config, crypto: Crypto, sock: Arc<UdpSocket>)
                 Err(e) => warn!(%e, "heartbeat seal error"),
             }
         }
-    });
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed;
+
+    impl MetricsSource for Fixed {
+        fn sample(&mut self) -> (f64, f64) {
+            (12.5, 3.0)
+        }
+    }
+
+    #[test]
+    fn uptime_increases_between_heartbeats() {
+        let started = Instant::now();
+        let mut r = HealthReporter { started, metrics: Fixed, last_total_misses: 0 };
+        let first = r.report(started + Duration::from_secs(2));
+        let second = r.report(started + Duration::from_secs(3));
+        assert_eq!((first.uptime_seconds, second.uptime_seconds), (2, 3));
+        assert_eq!(second.cpu_usage_percent, 12.5);
+    }
 }
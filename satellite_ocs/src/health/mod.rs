@@ -1,2 +1,3 @@
 pub mod heartbeat;
-pub use heartbeat::spawn_heartbeat;
+pub mod watchdog;
+pub use heartbeat::{spawn_heartbeat, SchedulerCpu, SysinfoMetrics};
@@ -0,0 +1,149 @@
+// health/watchdog.rs
+// Sensor liveness: every sensor pets its slot each cycle; a slot older than
+// `watchdog_periods` of that sensor's current period raises an emergency once.
+use crate::config::Config;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use shared_protocol::{EmergencyData, Severity};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::time::{self, Duration};
+use tracing::{error, info};
+
+static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+fn now_ms() -> i64 {
+    EPOCH.elapsed().as_millis() as i64
+}
+
+/// One sensor's slot; the sensor keeps the `Arc` and calls `pet` every cycle.
+pub struct Pet {
+    name: String,
+    last_ms: AtomicI64,
+    period_ms: AtomicI64,
+    alarmed: AtomicBool,
+}
+
+impl Pet {
+    /// `period` is the sensor's current period (it changes with safe mode and retuning).
+    pub fn pet(&self, period: Duration) {
+        self.period_ms.store(period.as_millis() as i64, Ordering::Relaxed);
+        self.last_ms.store(now_ms(), Ordering::Relaxed);
+        if self.alarmed.swap(false, Ordering::Relaxed) {
+            info!(sensor = %self.name, "watchdog: sensor alive again");
+        }
+    }
+}
+
+pub struct Watchdog {
+    pets: Mutex<Vec<Arc<Pet>>>,
+}
+
+static WATCHDOG: Lazy<Watchdog> = Lazy::new(Watchdog::new);
+
+/// Sensors register once at spawn, named "<kind>#<sensor_id>".
+pub fn register(name: impl Into<String>, period: Duration) -> Arc<Pet> {
+    WATCHDOG.register(name, period)
+}
+
+impl Watchdog {
+    fn new() -> Self {
+        Self { pets: Mutex::new(Vec::new()) }
+    }
+
+    fn register(&self, name: impl Into<String>, period: Duration) -> Arc<Pet> {
+        let pet = Arc::new(Pet {
+            name: name.into(),
+            last_ms: AtomicI64::new(now_ms()),
+            period_ms: AtomicI64::new(period.as_millis() as i64),
+            alarmed: AtomicBool::new(false),
+        });
+        self.pets.lock().unwrap_or_else(|e| e.into_inner()).push(pet.clone());
+        pet
+    }
+
+    /// Sensors that just went silent for more than `periods` periods: (name, silent ms).
+    /// Each stall is reported once until the sensor pets again.
+    fn newly_stalled(&self, periods: u32) -> Vec<(String, i64)> {
+        let now = now_ms();
+        self.pets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter_map(|p| {
+                let silent = now - p.last_ms.load(Ordering::Relaxed);
+                let limit = p.period_ms.load(Ordering::Relaxed) * periods as i64;
+                (silent > limit && !p.alarmed.swap(true, Ordering::Relaxed)).then_some((p.name.clone(), silent))
+            })
+            .collect()
+    }
+}
+
+fn stall_alert(sensor: &str, silent_ms: i64) -> EmergencyData {
+    EmergencyData {
+        alert_id: format!("watchdog-{sensor}-{}", Utc::now().timestamp_millis()),
+        severity: Severity::High,
+        alert_type: "watchdog".into(),
+        description: format!("{sensor} sensor task stalled: no cycle for {silent_ms} ms"),
+        affected_systems: vec![sensor.to_string()],
+        recommended_actions: vec!["restart_sensor_task".into(), "enter_safe_mode_if_persistent".into()],
+        auto_recovery_attempted: false,
+        timestamp: Utc::now(),
+        metadata: Default::default(),
+    }
+}
+
+/// Checks every 100 ms until shutdown.
+pub fn spawn_watchdog(cfg: &Config) -> tokio::task::JoinHandle<()> {
+    let periods = cfg.watchdog_periods;
+    let mut shutdown_rx = crate::shutdown::subscribe();
+    tokio::spawn(async move {
+        let mut ticker = time::interval(Duration::from_millis(100));
+        loop {
+            ticker.tick().await;
+            // sensors stop on shutdown; that's not a stall
+            if crate::shutdown::requested(&mut shutdown_rx) {
+                break;
+            }
+            for (sensor, silent_ms) in WATCHDOG.newly_stalled(periods) {
+                error!(%sensor, silent_ms, "watchdog: sensor task stalled");
+                if let Some(em_tx) = crate::telemetry::EMER_TX.get() {
+                    let _ = em_tx.try_send(stall_alert(&sensor, silent_ms));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn silent_sensor_trips_watchdog_within_timeout() {
+        let dog = Watchdog::new();
+        let period = Duration::from_millis(20);
+        let alive = dog.register("alive", period);
+        let _stuck = dog.register("stuck", period); // never pets
+
+        let started = Instant::now();
+        let mut fired = None;
+        while started.elapsed() < Duration::from_millis(500) {
+            alive.pet(period);
+            let stalled = dog.newly_stalled(5);
+            assert!(stalled.iter().all(|(n, _)| *n == "stuck"), "{stalled:?}");
+            if !stalled.is_empty() {
+                fired = Some(started.elapsed());
+                break;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        // 5 periods of 20 ms, plus one poll and scheduling slack
+        let fired = fired.expect("watchdog never fired");
+        assert!(fired >= Duration::from_millis(100) && fired < Duration::from_millis(250), "{fired:?}");
+
+        // reported once, not on every check
+        assert!(dog.newly_stalled(5).is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! Flight recorder: the last `CAPACITY` significant events (sensor misses, deadline
+//! violations, drops, downlink misses), dumped to `blackbox.csv` in the log directory on mission abort.
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info};
+
+use crate::faults::{self, FaultEvent};
+
+pub const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    SensorMiss,
+    DeadlineMiss,
+    Drop,
+    DownlinkMiss,
+    Degrade,
+    FaultOverrun,
+}
+
+impl EventKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EventKind::SensorMiss => "sensor_miss",
+            EventKind::DeadlineMiss => "deadline_miss",
+            EventKind::Drop => "drop",
+            EventKind::DownlinkMiss => "downlink_miss",
+            EventKind::Degrade => "degrade",
+            EventKind::FaultOverrun => "fault_overrun",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub ts: DateTime<Utc>,
+    pub kind: EventKind,
+    /// Sensor, task or priority the event is about
+    pub source: String,
+    pub detail: String,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, source: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { ts: Utc::now(), kind, source: source.into(), detail: detail.into() }
+    }
+}
+
+/// Bounded ring; the oldest event is overwritten once full.
+pub struct BlackBox {
+    capacity: usize,
+    ring: Mutex<VecDeque<Event>>,
+}
+
+static BLACKBOX: Lazy<BlackBox> = Lazy::new(|| BlackBox::new(CAPACITY));
+
+/// Components call this; never blocks for more than the ring push.
+pub fn record(event: Event) {
+    BLACKBOX.record(event);
+}
+
+impl BlackBox {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), ring: Mutex::new(VecDeque::with_capacity(capacity)) }
+    }
+
+    pub fn record(&self, event: Event) {
+        let mut ring = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+        if ring.len() == self.capacity {
+            ring.pop_front();
+        }
+        ring.push_back(event);
+    }
+
+    /// Write the ring, oldest first, to `path` (replacing any previous dump).
+    pub async fn dump(&self, path: &str) -> std::io::Result<usize> {
+        let events: Vec<Event> = self.ring.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect();
+        let mut out = String::from("ts,kind,source,detail\n");
+        for e in &events {
+            out.push_str(&format!(
+                "{},{},{},{}\n",
+                e.ts.to_rfc3339(),
+                e.kind.as_str(),
+                e.source.replace(',', ";"),
+                e.detail.replace(',', ";")
+            ));
+        }
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut f = tokio::fs::File::create(path).await?;
+        f.write_all(out.as_bytes()).await?;
+        f.flush().await?;
+        Ok(events.len())
+    }
+}
+
+/// Events of `kind` currently in the recorder.
+#[cfg(test)]
+pub fn count(kind: EventKind) -> usize {
+    BLACKBOX.ring.lock().unwrap_or_else(|e| e.into_inner()).iter().filter(|e| e.kind == kind).count()
+}
+
+/// Dump the recorder every time the fault injector broadcasts `Abort`.
+/// Call after `faults::init_and_spawn` (no-op if the injector isn't running).
+pub fn spawn_dump_on_abort() {
+    let Some(mut rx) = faults::subscribe() else { return };
+    let path = crate::logging::csv::log_path("blackbox.csv");
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(FaultEvent::Abort { reason }) => match BLACKBOX.dump(&path).await {
+                    Ok(n) => info!(%reason, events = n, %path, "blackbox: dumped"),
+                    Err(e) => error!(%e, "blackbox: dump failed"),
+                },
+                Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dump_keeps_the_most_recent_events_in_order() {
+        let bb = BlackBox::new(4);
+        for i in 0..10 {
+            bb.record(Event::new(EventKind::Drop, "normal", format!("event{i}")));
+        }
+        let path = &crate::logging::csv::log_path("blackbox_test.csv");
+        assert_eq!(bb.dump(path).await.unwrap(), 4);
+
+        let text = std::fs::read_to_string(path).unwrap();
+        let details: Vec<&str> = text.lines().skip(1).filter_map(|l| l.rsplit(',').next()).collect();
+        assert_eq!(details, ["event6", "event7", "event8", "event9"]);
+    }
+}
@@ -1,179 +1,540 @@
 use std::sync::Arc;
-use chrono::Utc;
+use std::sync::atomic::{AtomicU16, AtomicU64, AtomicU8, Ordering};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, OnceCell};
 use tokio::{
     fs::{self, OpenOptions},
-    io::{AsyncWriteExt, BufWriter},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
 };
 
 
-// All logs use the same OnceCell type for simplicity/consistency.
-static SENSORS: OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
-static DROPS:   OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
-static BATCHES: OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
-static SCHED:   OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
-static CPU:     OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
-static DOWNLINK: OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new(); 
-static FAULTS: OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>> = OnceCell::const_new();
+/// Rotate a log once it would grow past this many bytes (0 = never).
+static ROTATE_BYTES: AtomicU64 = AtomicU64::new(0);
+/// Rotated generations kept per log (`x.1.csv` newest … `x.N.csv` oldest).
+const MAX_ROTATED: usize = 5;
+
+/// Set from config at startup; applies to log files opened afterwards.
+pub fn set_rotate_bytes(limit: u64) {
+    ROTATE_BYTES.store(limit, Ordering::Relaxed);
+}
+
+/// Directory every log (and the blackbox dump) is written under.
+static DIR: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Set from config at startup, before the first log is written.
+pub fn set_dir(dir: &str) {
+    let _ = DIR.set(dir.trim_end_matches('/').to_string());
+}
+
+/// `name` inside the log directory. Tests default to a per-process temp dir so they
+/// never write into the checked-in `logs/`.
+pub fn log_path(name: &str) -> String {
+    let dir = DIR.get_or_init(|| {
+        if cfg!(test) {
+            std::env::temp_dir().join(format!("ocs_test_logs_{}", std::process::id())).to_string_lossy().into_owned()
+        } else {
+            "logs".to_string()
+        }
+    });
+    format!("{dir}/{name}")
+}
+
+/// Buffered CSV file that rotates itself by size; every generation starts with the header.
+pub struct LogFile {
+    path: String,
+    header: String,
+    w: BufWriter<tokio::fs::File>,
+    bytes: u64,
+    rotate_bytes: u64,
+}
+
+impl LogFile {
+    async fn open(path: &str, header: &str, rotate_bytes: u64) -> std::io::Result<Self> {
+        // rows from an older column layout stay with their own header in x.1.csv
+        if let Ok(old) = fs::File::open(path).await {
+            let mut first = String::new();
+            let _ = BufReader::new(old).read_line(&mut first).await;
+            if !first.is_empty() && first.trim_end() != header.trim_end() {
+                shift_generations(path).await?;
+            }
+        }
+        let fresh = !fs::try_exists(path).await.unwrap_or(false);
+        let f = OpenOptions::new().create(true).append(true).open(path).await?;
+        let bytes = f.metadata().await.map(|m| m.len()).unwrap_or(0);
+        let mut lf = Self {
+            path: path.to_string(),
+            header: header.to_string(),
+            w: BufWriter::new(f),
+            bytes,
+            rotate_bytes,
+        };
+        if fresh {
+            lf.w.write_all(header.as_bytes()).await?;
+            lf.w.flush().await?;
+            lf.bytes = header.len() as u64;
+        }
+        Ok(lf)
+    }
+
+    pub async fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        let has_rows = self.bytes > self.header.len() as u64;
+        if self.rotate_bytes > 0 && has_rows && self.bytes + buf.len() as u64 > self.rotate_bytes {
+            self.rotate().await?;
+        }
+        self.w.write_all(buf).await?;
+        self.bytes += buf.len() as u64;
+        Ok(())
+    }
+
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.w.flush().await
+    }
+
+    /// Flush and fsync (shutdown path).
+    pub async fn sync(&mut self) -> std::io::Result<()> {
+        self.w.flush().await?;
+        self.w.get_mut().sync_all().await
+    }
+
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        self.w.flush().await?;
+        shift_generations(&self.path).await?;
+        *self = Self::open(&self.path.clone(), &self.header.clone(), self.rotate_bytes).await?;
+        Ok(())
+    }
+}
+
+fn generation(path: &str, n: usize) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}.{n}.{ext}"),
+        None => format!("{path}.{n}"),
+    }
+}
+
+/// x.csv → x.1.csv (older generations shift up, the oldest is overwritten).
+async fn shift_generations(path: &str) -> std::io::Result<()> {
+    for n in (1..MAX_ROTATED).rev() {
+        let _ = fs::rename(generation(path, n), generation(path, n + 1)).await;
+    }
+    fs::rename(path, generation(path, 1)).await
+}
+
+type Shared<S> = Arc<Mutex<S>>;
+
+/// Which files each `log_*` call writes: `x.csv`, `x.jsonl`, or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Csv,
+    Json,
+    Both,
+}
+
+static FORMAT: AtomicU8 = AtomicU8::new(LogFormat::Csv as u8);
+
+/// Set from config at startup.
+pub fn set_format(format: LogFormat) {
+    FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+tokio::task_local! {
+    // lets a test pick the format without changing it for tests running alongside
+    static FORMAT_OVERRIDE: LogFormat;
+}
+
+fn format() -> LogFormat {
+    #[cfg(test)]
+    if let Ok(f) = FORMAT_OVERRIDE.try_with(|f| *f) {
+        return f;
+    }
+    match FORMAT.load(Ordering::Relaxed) {
+        1 => LogFormat::Json,
+        2 => LogFormat::Both,
+        _ => LogFormat::Csv,
+    }
+}
+
+/// Logs (bit = index in `ALL_LOGS`) written as binary records instead of text.
+static BINARY: AtomicU16 = AtomicU16::new(0);
+
+/// Set from config at startup: these logs go to `<log dir>/<stem>.bin` regardless of `LogFormat`.
+pub fn set_binary_logs(stems: &[String]) -> Result<(), String> {
+    let mut mask = 0u16;
+    for stem in stems {
+        let i = ALL_LOGS.iter().position(|l| l.stem == stem.as_str()).ok_or_else(|| {
+            let known: Vec<_> = ALL_LOGS.iter().map(|l| l.stem).collect();
+            format!("--binary-logs: unknown log '{stem}' (one of {})", known.join(", "))
+        })?;
+        mask |= 1 << i;
+    }
+    BINARY.store(mask, Ordering::Relaxed);
+    Ok(())
+}
+
+fn is_binary(log: &Log) -> bool {
+    let mask = BINARY.load(Ordering::Relaxed);
+    ALL_LOGS.iter().position(|l| std::ptr::eq(*l, log)).is_some_and(|i| mask & (1 << i) != 0)
+}
+
+/// One column value; CSV renders floats at `precision`, JSON keeps the number.
+enum Val<'a> {
+    F(f64, usize),
+    U(u64),
+    S(&'a str),
+    B(bool),
+    Null, // empty CSV cell / JSON null
+}
+use Val::{B, F, Null, S, U};
+
+impl Val<'_> {
+    fn csv(&self) -> String {
+        match self {
+            F(v, p) => format!("{v:.p$}"),
+            U(v) => v.to_string(),
+            S(v) => v.to_string(),
+            B(v) => v.to_string(),
+            Null => String::new(),
+        }
+    }
+
+    fn json(&self) -> serde_json::Value {
+        match self {
+            F(v, _) => serde_json::json!(v),
+            U(v) => serde_json::json!(v),
+            S(v) => serde_json::json!(v),
+            B(v) => serde_json::json!(v),
+            Null => serde_json::Value::Null,
+        }
+    }
+
+    fn bin(&self) -> BinValue {
+        match self {
+            F(v, _) => BinValue::F(*v),
+            U(v) => BinValue::U(*v),
+            S(v) => BinValue::S(v.to_string()),
+            B(v) => BinValue::B(*v),
+            Null => BinValue::Null,
+        }
+    }
+}
+
+/// A decoded column of a binary log; floats keep full precision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BinValue {
+    F(f64),
+    U(u64),
+    S(String),
+    B(bool),
+    Null,
+}
+
+impl std::fmt::Display for BinValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinValue::F(v) => write!(f, "{v}"),
+            BinValue::U(v) => write!(f, "{v}"),
+            BinValue::S(v) => f.write_str(v),
+            BinValue::B(v) => write!(f, "{v}"),
+            BinValue::Null => Ok(()),
+        }
+    }
+}
+
+/// One binary log record: values in the column order of the matching CSV (minus `ts`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinRecord {
+    pub ts_micros: i64,
+    pub values: Vec<BinValue>,
+}
+
+/// Where one record goes: the CSV line, the NDJSON object or the binary frame.
+trait LogSink {
+    async fn write_record(&mut self, ts: DateTime<Utc>, fields: &[(&str, Val<'_>)]) -> std::io::Result<()>;
+    fn file(&mut self) -> &mut LogFile;
+}
+
+struct CsvSink(LogFile);
+
+impl LogSink for CsvSink {
+    async fn write_record(&mut self, ts: DateTime<Utc>, fields: &[(&str, Val<'_>)]) -> std::io::Result<()> {
+        let line = std::iter::once(ts.to_rfc3339())
+            .chain(fields.iter().map(|(_, v)| v.csv()))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.0.write_all(format!("{line}\n").as_bytes()).await
+    }
+
+    fn file(&mut self) -> &mut LogFile {
+        &mut self.0
+    }
+}
+
+struct JsonSink {
+    kind: &'static str,
+    file: LogFile,
+}
+
+impl LogSink for JsonSink {
+    async fn write_record(&mut self, ts: DateTime<Utc>, fields: &[(&str, Val<'_>)]) -> std::io::Result<()> {
+        let mut rec = serde_json::Map::new();
+        rec.insert("kind".into(), self.kind.into());
+        rec.insert("ts".into(), ts.to_rfc3339().into());
+        for (k, v) in fields {
+            rec.insert((*k).into(), v.json());
+        }
+        let line = serde_json::Value::Object(rec).to_string();
+        self.file.write_all(format!("{line}\n").as_bytes()).await
+    }
+
+    fn file(&mut self) -> &mut LogFile {
+        &mut self.file
+    }
+}
+
+/// `[len: u32 LE][bincode(BinRecord)]` per record; a fraction of the CSV size and parse cost.
+struct BinSink(LogFile);
+
+impl LogSink for BinSink {
+    async fn write_record(&mut self, ts: DateTime<Utc>, fields: &[(&str, Val<'_>)]) -> std::io::Result<()> {
+        let rec = BinRecord { ts_micros: ts.timestamp_micros(), values: fields.iter().map(|(_, v)| v.bin()).collect() };
+        let body = bincode::serde::encode_to_vec(&rec, bincode::config::standard())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut frame = Vec::with_capacity(4 + body.len());
+        frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&body);
+        self.0.write_all(&frame).await // one write: rotation never splits a frame
+    }
+
+    fn file(&mut self) -> &mut LogFile {
+        &mut self.0
+    }
+}
+
+/// Decode a `.bin` log written by `BinSink`. A torn final frame (crash mid-write) ends the log.
+pub fn read_binary_log(path: &std::path::Path) -> std::io::Result<Vec<BinRecord>> {
+    let bytes = std::fs::read(path)?;
+    let mut records = Vec::new();
+    let mut rest = &bytes[..];
+    while let Some((len, tail)) = rest.split_first_chunk::<4>() {
+        let Some(body) = tail.get(..u32::from_le_bytes(*len) as usize) else { break };
+        let (rec, _) = bincode::serde::decode_from_slice(body, bincode::config::standard())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        records.push(rec);
+        rest = &tail[body.len()..];
+    }
+    Ok(records)
+}
+
+/// A log stream: its file stem, JSON `kind`, and the lazily opened files.
+struct Log {
+    stem: &'static str,
+    kind: &'static str,
+    csv: OnceCell<Shared<CsvSink>>,
+    json: OnceCell<Shared<JsonSink>>,
+    bin: OnceCell<Shared<BinSink>>,
+}
+
+impl Log {
+    const fn new(stem: &'static str, kind: &'static str) -> Self {
+        Self {
+            stem,
+            kind,
+            csv: OnceCell::const_new(),
+            json: OnceCell::const_new(),
+            bin: OnceCell::const_new(),
+        }
+    }
+}
+
+// Every log goes through `write`, so the columns below are the single source for both formats.
+static SENSORS:  Log = Log::new("sensors", "sensor");
+static DROPS:    Log = Log::new("drops", "drop");
+static BATCHES:  Log = Log::new("batches", "batch");
+static SCHED:    Log = Log::new("scheduler", "sched");
+static CPU:      Log = Log::new("cpu", "cpu");
+static DOWNLINK: Log = Log::new("downlink", "downlink");
+static FAULTS:   Log = Log::new("faults", "fault");
+static TXQ:      Log = Log::new("txqueue", "txqueue");
+static LATENCY:  Log = Log::new("latency", "latency");
+static PACKETS:  Log = Log::new("packets", "packet");
+static SUMMARY:  Log = Log::new("summary", "summary");
+
+const ALL_LOGS: [&Log; 11] =
+    [&SENSORS, &DROPS, &BATCHES, &SCHED, &CPU, &DOWNLINK, &FAULTS, &TXQ, &LATENCY, &PACKETS, &SUMMARY];
 
 async fn ensure_dir() {
-    let _ = fs::create_dir_all("logs").await;
+    let _ = fs::create_dir_all(log_path("")).await;
 }
 
-async fn get_file(
-    cell: &OnceCell<Arc<Mutex<BufWriter<tokio::fs::File>>>>,
+async fn get_file<S>(
+    cell: &OnceCell<Shared<S>>,
     path: &str,
     header: &str,
-) -> Arc<Mutex<BufWriter<tokio::fs::File>>> {
+    sink: impl FnOnce(LogFile) -> S,
+) -> Shared<S> {
     let arc = cell.get_or_init(|| async move {
         ensure_dir().await;
-        let fresh = !fs::try_exists(path).await.unwrap_or(false);
-        let f = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(path)
+        let f = LogFile::open(path, header, ROTATE_BYTES.load(Ordering::Relaxed))
             .await
             .expect("open log file");
-        let writer = BufWriter::new(f);
-        let m = Arc::new(Mutex::new(writer));
-        if fresh {
-            let mut g = m.lock().await;
-            let _ = g.write_all(header.as_bytes()).await;
-            let _ = g.flush().await;
-        }
-        m
+        Arc::new(Mutex::new(sink(f)))
     }).await;
     arc.clone()
-} 
+}
 
-async fn get_faults_file() -> Arc<Mutex<BufWriter<tokio::fs::File>>> {
-    super::csv::get_file(
-        &FAULTS,
-        "logs/faults.csv",
-        "ts,event,fault_id,target,kind,duration_ms,component,recovery_ms,aborted,note\n",
-    ).await
+async fn append(sink: &Mutex<impl LogSink>, ts: DateTime<Utc>, fields: &[(&str, Val<'_>)]) {
+    let mut s = sink.lock().await;
+    let _ = s.write_record(ts, fields).await;
+    let _ = s.file().flush().await;
 }
 
-/// sensors.csv: ts,sensor,seq,jitter_ms,drift_ms,processing_latency_ms,priority,status
+/// Append one record (a `ts` column is prepended) in the configured format(s).
+async fn write(log: &Log, fields: &[(&str, Val<'_>)]) {
+    let ts = Utc::now();
+
+    if is_binary(log) {
+        let path = log_path(&format!("{}.bin", log.stem));
+        append(&*get_file(&log.bin, &path, "", BinSink).await, ts, fields).await;
+        return;
+    }
+
+    let format = format();
+    if matches!(format, LogFormat::Csv | LogFormat::Both) {
+        let header = std::iter::once("ts")
+            .chain(fields.iter().map(|(k, _)| *k))
+            .collect::<Vec<_>>()
+            .join(",");
+        let path = log_path(&format!("{}.csv", log.stem));
+        append(&*get_file(&log.csv, &path, &format!("{header}\n"), CsvSink).await, ts, fields).await;
+    }
+
+    if matches!(format, LogFormat::Json | LogFormat::Both) {
+        let path = log_path(&format!("{}.jsonl", log.stem));
+        let kind = log.kind;
+        append(&*get_file(&log.json, &path, "", |file| JsonSink { kind, file }).await, ts, fields).await;
+    }
+}
+
+/// sensors.csv: ts,sensor,seq,jitter_ms,drift_ms,processing_latency_ms,priority,status,skipped_cycles,sensor_id
+#[allow(clippy::too_many_arguments)] // one argument per column
 pub async fn log_sensor_reading(
     sensor: &str,
+    sensor_id: u32,
     seq: u64,
     jitter_ms: f64,
     drift_ms: f64,
     proc_ms: f64,
     priority: &str,
     status: &str,
+    skipped_cycles: u64,
 ) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!(
-        "{ts},{sensor},{seq},{jitter_ms:.3},{drift_ms:.3},{proc_ms:.3},{priority},{status}\n"
-    );
-    let file = get_file(
-        &SENSORS,
-        "logs/sensors.csv",
-        "ts,sensor,seq,jitter_ms,drift_ms,processing_latency_ms,priority,status\n",
-    ).await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
-}
-
-/// drops.csv: ts,priority,dropped_count
-pub async fn log_drop(priority: &str, dropped_count: usize) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!("{ts},{priority},{dropped_count}\n");
-    let file = get_file(&DROPS, "logs/drops.csv", "ts,priority,dropped_count\n").await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
+    write(&SENSORS, &[
+        ("sensor", S(sensor)),
+        ("seq", U(seq)),
+        ("jitter_ms", F(jitter_ms, 3)),
+        ("drift_ms", F(drift_ms, 3)),
+        ("processing_latency_ms", F(proc_ms, 3)),
+        ("priority", S(priority)),
+        ("status", S(status)),
+        ("skipped_cycles", U(skipped_cycles)),
+        ("sensor_id", U(sensor_id as u64)),
+    ]).await;
+}
+
+/// drops.csv: ts,priority,dropped_count,reason ("overflow", "downsample_1_in_N", "invalid", ...)
+pub async fn log_drop(priority: &str, dropped_count: usize, reason: &str) {
+    write(&DROPS, &[
+        ("priority", S(priority)),
+        ("dropped_count", U(dropped_count as u64)),
+        ("reason", S(reason)),
+    ]).await;
 }
 
 /// batches.csv: ts,total,critical,important,normal
 pub async fn log_batch(total: usize, c: usize, i: usize, n: usize) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!("{ts},{total},{c},{i},{n}\n");
-    let file = get_file(
-        &BATCHES,
-        "logs/batches.csv",
-        "ts,total,critical,important,normal\n",
-    ).await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
-}
-
-/// scheduler.csv: ts,task,seq,start_delay_ms,completion_delay_ms,runtime_ms,preemptions,deadline_ms
+    write(&BATCHES, &[
+        ("total", U(total as u64)),
+        ("critical", U(c as u64)),
+        ("important", U(i as u64)),
+        ("normal", U(n as u64)),
+    ]).await;
+}
+
+/// scheduler.csv: ts,task,seq,start_delay_ms,completion_delay_ms,runtime_ms,exec_ms,preemptions,deadline_ms,policy
+#[allow(clippy::too_many_arguments)] // one argument per column
 pub async fn log_sched_event(
     task: &str,
     seq: u64,
     start_delay_ms: f64,
     completion_delay_ms: f64,
     runtime_ms: f64,
+    exec_ms: f64,
     preemptions: u32,
     deadline_ms: f64,
+    policy: &str,
 ) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!(
-        "{ts},{task},{seq},{start_delay_ms:.3},{completion_delay_ms:.3},{runtime_ms:.3},{preemptions},{deadline_ms:.3}\n"
-    );
-    let file = get_file(
-        &SCHED,
-        "logs/scheduler.csv",
-        "ts,task,seq,start_delay_ms,completion_delay_ms,runtime_ms,preemptions,deadline_ms\n",
-    ).await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
+    write(&SCHED, &[
+        ("task", S(task)),
+        ("seq", U(seq)),
+        ("start_delay_ms", F(start_delay_ms, 3)),
+        ("completion_delay_ms", F(completion_delay_ms, 3)),
+        ("runtime_ms", F(runtime_ms, 3)),
+        ("exec_ms", F(exec_ms, 3)),
+        ("preemptions", U(preemptions as u64)),
+        ("deadline_ms", F(deadline_ms, 3)),
+        ("policy", S(policy)),
+    ]).await;
 }
 
 /// cpu.csv: ts,window_ms,active_ms,idle_ms,active_pct
 pub async fn log_cpu(window_ms: u64, active_ms: f64, idle_ms: f64) {
-    let ts = Utc::now().to_rfc3339();
     let active_pct = if window_ms > 0 {
-    (active_ms / window_ms as f64) * 100.0
-} else {
-    0.0
-};
+        (active_ms / window_ms as f64) * 100.0
+    } else {
+        0.0
+    };
+    write(&CPU, &[
+        ("window_ms", U(window_ms)),
+        ("active_ms", F(active_ms, 3)),
+        ("idle_ms", F(idle_ms, 3)),
+        ("active_pct", F(active_pct, 2)),
+    ]).await;
+}
 
-    let line = format!("{ts},{window_ms},{active_ms:.3},{idle_ms:.3},{active_pct:.2}\n");
-    let file = get_file(
-        &CPU,
-        "logs/cpu.csv",
-        "ts,window_ms,active_ms,idle_ms,active_pct\n",
-    ).await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
-} 
-
-/// downlink.csv: ts,event,info
+/// downlink.csv: ts,batch_size,avg_queue_ms,max_queue_ms,fill_pct,event,window_ms
 pub async fn log_downlink(
     batch_size: usize,
     avg_queue_ms: f64,
     max_queue_ms: f64,
     fill_pct: f64,
     event: &str,
+    window_ms: f64,
 ) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!("{ts},{batch_size},{avg_queue_ms:.3},{max_queue_ms:.3},{fill_pct:.1},{event}\n");
-    let file = get_file(
-        &DOWNLINK,
-        "logs/downlink.csv",
-        "ts,batch_size,avg_queue_ms,max_queue_ms,fill_pct,event\n",
-    ).await;
-    let mut f = file.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
-    let _ = f.flush().await;
-} 
+    write(&DOWNLINK, &[
+        ("batch_size", U(batch_size as u64)),
+        ("avg_queue_ms", F(avg_queue_ms, 3)),
+        ("max_queue_ms", F(max_queue_ms, 3)),
+        ("fill_pct", F(fill_pct, 1)),
+        ("event", S(event)),
+        ("window_ms", F(window_ms, 1)),
+    ]).await;
+}
 
 /// faults.csv (injection): ts=now, event="inject"
 pub async fn log_fault_inject(fault_id: &str, target: &str, kind: &str, duration_ms: u64) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!("{ts},inject,{fault_id},{target},{kind},{duration_ms},,,,\n");
-    let f = get_faults_file().await;
-    let mut g = f.lock().await;
-    let _ = g.write_all(line.as_bytes()).await;
-    let _ = g.flush().await;
+    write(&FAULTS, &[
+        ("event", S("inject")),
+        ("fault_id", S(fault_id)),
+        ("target", S(target)),
+        ("kind", S(kind)),
+        ("duration_ms", U(duration_ms)),
+        ("component", Null),
+        ("recovery_ms", Null),
+        ("aborted", Null),
+        ("note", Null),
+    ]).await;
 }
 
 /// faults.csv (recovery): ts=now, event="recovery"
@@ -183,42 +544,163 @@ pub async fn log_fault_recovery(
     recovery_ms: f64,
     aborted: bool,
 ) {
-    let ts = Utc::now().to_rfc3339();
-    let line = format!(
-        "{ts},recovery,{fault_id},,,,{component},{recovery_ms:.1},{aborted},\n"
-    );
-    let f = get_faults_file().await;
-    let mut g = f.lock().await;
-    let _ = g.write_all(line.as_bytes()).await;
-    let _ = g.flush().await;
-} 
-
-// txqueue.csv: ts,oldest_ms,fill_pct
-pub async fn log_tx_queue(oldest_ms: f64, fill_pct: f64) {
-    use tokio::sync::OnceCell;
-    use tokio::{fs::{self, OpenOptions}, io::AsyncWriteExt, sync::Mutex};
-    use chrono::Utc;
-
-    static TXQ: OnceCell<Mutex<tokio::fs::File>> = OnceCell::const_new();
-
-    async fn file() -> &'static Mutex<tokio::fs::File> {
-        TXQ.get_or_init(|| async {
-            let _ = fs::create_dir_all("logs").await;
-            let fresh = !fs::try_exists("logs/txqueue.csv").await.unwrap_or(false);
-            let f = OpenOptions::new().create(true).append(true).open("logs/txqueue.csv").await.unwrap();
-            let m = Mutex::new(f);
-            if fresh {
-                let mut g = m.lock().await;
-                let _ = g.write_all(b"ts,oldest_ms,fill_pct\n").await;
-            }
-            m
-        }).await
-    }
+    write(&FAULTS, &[
+        ("event", S("recovery")),
+        ("fault_id", S(fault_id)),
+        ("target", Null),
+        ("kind", Null),
+        ("duration_ms", Null),
+        ("component", S(component)),
+        ("recovery_ms", F(recovery_ms, 1)),
+        ("aborted", B(aborted)),
+        ("note", Null),
+    ]).await;
+}
+
+// txqueue.csv: ts,oldest_ms,fill_pct,deferred
+pub async fn log_tx_queue(oldest_ms: f64, fill_pct: f64, deferred: usize) {
+    write(&TXQ, &[
+        ("oldest_ms", F(oldest_ms, 3)),
+        ("fill_pct", F(fill_pct, 1)),
+        ("deferred", U(deferred as u64)),
+    ]).await;
+}
+
+/// latency.csv: ts,window_ms,count,p50_ms,p95_ms,p99_ms (bucket upper bounds; "inf" = overflow)
+pub async fn log_latency(window_ms: u64, count: u64, p50_ms: f64, p95_ms: f64, p99_ms: f64) {
+    write(&LATENCY, &[
+        ("window_ms", U(window_ms)),
+        ("count", U(count)),
+        ("p50_ms", F(p50_ms, 2)),
+        ("p95_ms", F(p95_ms, 2)),
+        ("p99_ms", F(p99_ms, 2)),
+    ]).await;
+}
+
+/// packets.csv: ts,packet_type,seq,bytes (every frame sent on the tx socket; seq as in the clear header)
+pub async fn log_packet(pkt_type: &str, seq: u32, bytes: usize) {
+    write(&PACKETS, &[
+        ("packet_type", S(pkt_type)),
+        ("seq", U(seq as u64)),
+        ("bytes", U(bytes as u64)),
+    ]).await;
+}
 
-    let ts = Utc::now().to_rfc3339();
-    let line = format!("{ts},{oldest_ms:.3},{fill_pct:.1}\n");
-    let mut f = file().await.lock().await;
-    let _ = f.write_all(line.as_bytes()).await;
+/// summary.csv: ts,interval_s,batches_per_s,drops_per_s_{emergency,critical,important,normal},
+/// latency_mean_ms,latency_p95_ms,deadline_misses,buffer_fill_pct,downlink (rates over the interval)
+#[allow(clippy::too_many_arguments)] // one argument per column
+pub async fn log_summary(
+    interval_s: f64,
+    batches_per_s: f64,
+    drops_per_s: [f64; 4],
+    latency_mean_ms: f64,
+    latency_p95_ms: f64,
+    deadline_misses: u64,
+    buffer_fill_pct: f64,
+    downlink: &str,
+) {
+    write(&SUMMARY, &[
+        ("interval_s", F(interval_s, 1)),
+        ("batches_per_s", F(batches_per_s, 2)),
+        ("drops_per_s_emergency", F(drops_per_s[0], 2)),
+        ("drops_per_s_critical", F(drops_per_s[1], 2)),
+        ("drops_per_s_important", F(drops_per_s[2], 2)),
+        ("drops_per_s_normal", F(drops_per_s[3], 2)),
+        ("latency_mean_ms", F(latency_mean_ms, 2)),
+        ("latency_p95_ms", F(latency_p95_ms, 2)),
+        ("deadline_misses", U(deadline_misses)),
+        ("buffer_fill_pct", F(buffer_fill_pct, 1)),
+        ("downlink", S(downlink)),
+    ]).await;
 }
 
- 
+/// Flush every log file opened so far (shutdown path).
+pub async fn flush_all() {
+    async fn sync(cell: &OnceCell<Shared<impl LogSink>>) {
+        if let Some(sink) = cell.get() {
+            let _ = sink.lock().await.file().sync().await;
+        }
+    }
+    for log in ALL_LOGS {
+        sync(&log.csv).await;
+        sync(&log.json).await;
+        sync(&log.bin).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flush_all_puts_last_line_on_disk() {
+        log_drop("normal", 1, "flush_all_test_marker").await;
+        flush_all().await;
+        let on_disk = std::fs::read_to_string(log_path("drops.csv")).unwrap();
+        assert!(on_disk.contains(",normal,1,flush_all_test_marker"), "{on_disk}");
+    }
+
+    #[tokio::test]
+    async fn rotates_past_size_limit_with_header() {
+        ensure_dir().await;
+        let path = &log_path("rotate_test.csv");
+        for p in [path.clone(), log_path("rotate_test.1.csv"), log_path("rotate_test.2.csv")] {
+            let _ = std::fs::remove_file(p);
+        }
+
+        let mut f = LogFile::open(path, "ts,value\n", 64).await.unwrap();
+        for i in 0..10 {
+            f.write_all(format!("2025-01-01T00:00:00Z,{i}\n").as_bytes()).await.unwrap();
+        }
+        f.flush().await.unwrap();
+
+        let rotated = std::fs::read_to_string(log_path("rotate_test.1.csv")).unwrap();
+        assert!(rotated.starts_with("ts,value\n"), "{rotated}");
+        let current = std::fs::read_to_string(path).unwrap();
+        assert!(current.starts_with("ts,value\n") && current.ends_with(",9\n"), "{current}");
+        assert!(current.len() <= 64);
+    }
+
+    #[tokio::test]
+    async fn binary_records_read_back_identically() {
+        ensure_dir().await;
+        let path = &log_path("bin_test.bin");
+        let _ = std::fs::remove_file(path);
+        let mut sink = BinSink(LogFile::open(path, "", 0).await.unwrap());
+        let t0 = DateTime::from_timestamp_micros(1_700_000_000_000_000).unwrap();
+        let mut expected = Vec::new();
+        for i in 0..200u64 {
+            let ts = t0 + chrono::Duration::milliseconds(50 * i as i64);
+            let jitter = i as f64 / 3.0;
+            let fields = [("sensor", S("thermal")), ("seq", U(i)), ("jitter_ms", F(jitter, 3)), ("ok", B(i % 2 == 0)), ("note", Null)];
+            sink.write_record(ts, &fields).await.unwrap();
+            expected.push(BinRecord {
+                ts_micros: ts.timestamp_micros(),
+                values: vec![BinValue::S("thermal".into()), BinValue::U(i), BinValue::F(jitter), BinValue::B(i % 2 == 0), BinValue::Null],
+            });
+        }
+        sink.file().write_all(&[9, 0, 0, 0, 1]).await.unwrap(); // torn last frame
+        sink.file().flush().await.unwrap();
+
+        assert_eq!(read_binary_log(std::path::Path::new(path)).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn sensor_reading_in_json_mode_has_named_fields() {
+        FORMAT_OVERRIDE
+            .scope(LogFormat::Json, log_sensor_reading("json_test", 0, 42, 0.5, -0.25, 1.0, "normal", "ok", 0))
+            .await;
+        let text = std::fs::read_to_string(log_path("sensors.jsonl")).unwrap();
+        let rec: serde_json::Value = text
+            .lines()
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+            .find(|v| v["sensor"] == "json_test")
+            .expect("json_test record");
+        assert_eq!(rec["kind"], "sensor");
+        assert_eq!(rec["seq"], 42);
+        assert_eq!(rec["drift_ms"], -0.25);
+        for key in ["ts", "jitter_ms", "processing_latency_ms", "priority", "status"] {
+            assert!(rec.get(key).is_some(), "missing {key}: {rec}");
+        }
+    }
+}
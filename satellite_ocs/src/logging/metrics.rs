@@ -1,3 +1,126 @@
-//! Minimal metrics stub (extend with counters / MAs)
-#[derive(Default, Clone)]
-pub struct Metrics;
+//! Read→ingest latency histogram (`processing_latency_ms`), bucketed with atomics so
+//! the ingest loop never takes a lock; plus the since-boot counters served on `/metrics`.
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Telemetry batches handed to the socket.
+pub static BATCHES_SENT: AtomicU64 = AtomicU64::new(0);
+/// Faults broadcast by the injector.
+pub static FAULTS_INJECTED: AtomicU64 = AtomicU64::new(0);
+/// Downlink passes lost to a late (>5ms) init.
+pub static DOWNLINK_MISSED_INITS: AtomicU64 = AtomicU64::new(0);
+/// Sends held back by a closed window while the oldest reading was past `--max-age-ms`.
+pub static AGE_SLA_VIOLATIONS: AtomicU64 = AtomicU64::new(0);
+
+// Readings dropped per priority label ("normal", "critical", ...)
+static DROPS: Lazy<DashMap<String, AtomicU64>> = Lazy::new(DashMap::new);
+
+pub fn count(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_drop(prio: &str) {
+    DROPS.entry(prio.to_string()).or_default().fetch_add(1, Ordering::Relaxed);
+}
+
+/// Drop counts per priority, sorted by label.
+pub fn drop_snapshot() -> Vec<(String, u64)> {
+    let mut v: Vec<_> = DROPS.iter().map(|e| (e.key().clone(), e.value().load(Ordering::Relaxed))).collect();
+    v.sort();
+    v
+}
+
+/// Bucket upper bounds (ms); one extra overflow bucket sits past the last.
+pub const BOUNDS_MS: [f64; 15] = [
+    0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0,
+];
+const BUCKETS: usize = BOUNDS_MS.len() + 1;
+
+pub struct LatencyHistogram {
+    counts: [AtomicU64; BUCKETS],
+    sum_us: AtomicU64, // for the mean; negative latencies add nothing
+}
+
+/// Ingest-loop latencies since the last `latency.csv` row.
+pub static LATENCY: LatencyHistogram = LatencyHistogram::new();
+/// The same latencies since the last `summary.csv` row.
+pub static SUMMARY_LATENCY: LatencyHistogram = LatencyHistogram::new();
+
+impl LatencyHistogram {
+    pub const fn new() -> Self {
+        Self { counts: [const { AtomicU64::new(0) }; BUCKETS], sum_us: AtomicU64::new(0) }
+    }
+
+    /// Negative latencies (skewed timestamps) count toward the first bucket.
+    pub fn record(&self, ms: f64) {
+        let idx = BOUNDS_MS.iter().position(|&b| ms <= b).unwrap_or(BUCKETS - 1);
+        self.counts[idx].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add((ms.max(0.0) * 1000.0) as u64, Ordering::Relaxed);
+    }
+
+    /// Current counts, resetting them for the next window.
+    pub fn take(&self) -> [u64; BUCKETS] {
+        self.sum_us.store(0, Ordering::Relaxed);
+        std::array::from_fn(|i| self.counts[i].swap(0, Ordering::Relaxed))
+    }
+
+    /// `take`, plus the window's mean latency (None if nothing was recorded).
+    pub fn take_with_mean(&self) -> ([u64; BUCKETS], Option<f64>) {
+        let sum_ms = self.sum_us.swap(0, Ordering::Relaxed) as f64 / 1000.0;
+        let counts: [u64; BUCKETS] = std::array::from_fn(|i| self.counts[i].swap(0, Ordering::Relaxed));
+        let n: u64 = counts.iter().sum();
+        (counts, (n > 0).then(|| sum_ms / n as f64))
+    }
+}
+
+/// Upper bound of the bucket holding the `p`-th percentile (infinite for the overflow
+/// bucket); None if nothing was recorded.
+pub fn percentile(counts: &[u64; BUCKETS], p: f64) -> Option<f64> {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let rank = ((p / 100.0) * total as f64).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (i, &c) in counts.iter().enumerate() {
+        seen += c;
+        if seen >= rank {
+            return Some(BOUNDS_MS.get(i).copied().unwrap_or(f64::INFINITY));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_land_in_expected_buckets() {
+        let h = LatencyHistogram::new();
+        // 90 fast, 8 medium, 2 slow
+        for _ in 0..90 {
+            h.record(0.8);
+        }
+        for _ in 0..8 {
+            h.record(15.0);
+        }
+        h.record(150.0);
+        h.record(9000.0);
+
+        let counts = h.take();
+        assert_eq!(percentile(&counts, 50.0), Some(1.0));
+        assert_eq!(percentile(&counts, 95.0), Some(20.0));
+        assert_eq!(percentile(&counts, 99.0), Some(200.0));
+        assert_eq!(percentile(&counts, 100.0), Some(f64::INFINITY));
+
+        // take() reset the window
+        assert_eq!(percentile(&h.take(), 50.0), None);
+
+        h.record(2.0);
+        h.record(4.0);
+        assert_eq!(h.take_with_mean().1, Some(3.0));
+        assert_eq!(h.take_with_mean().1, None);
+    }
+}
@@ -1,2 +1,4 @@
+pub mod blackbox;
 pub mod csv;
 pub mod metrics;
+pub mod summary;
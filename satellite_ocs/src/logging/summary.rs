@@ -0,0 +1,132 @@
+//! Mission health at a glance (`--summary-secs`): every interval, one log line and one
+//! `summary.csv` row of rates and gauges read from the counters the other subsystems keep.
+use std::sync::atomic::Ordering;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+use tracing::info;
+
+use crate::logging::{csv, metrics};
+
+/// Priority labels as recorded by `metrics::record_drop`, in column order.
+const PRIORITIES: [&str; 4] = ["emergency", "critical", "important", "normal"];
+
+/// Cumulative counters as of the previous row.
+#[derive(Debug, Default)]
+struct Totals {
+    batches: u64,
+    drops: [u64; 4],
+    misses: u64,
+}
+
+impl Totals {
+    fn now() -> Self {
+        let drops = metrics::drop_snapshot();
+        let dropped = |prio: &str| drops.iter().find(|(p, _)| p == prio).map_or(0, |(_, n)| *n);
+        Self {
+            batches: metrics::BATCHES_SENT.load(Ordering::Relaxed),
+            drops: PRIORITIES.map(dropped),
+            misses: crate::scheduler::deadline_miss_snapshot().values().sum(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Summary {
+    batches_per_s: f64,
+    drops_per_s: [f64; 4],
+    latency_mean_ms: Option<f64>,
+    latency_p95_ms: Option<f64>,
+    deadline_misses: u64,
+    buffer_fill_pct: f64,
+    downlink: &'static str,
+}
+
+/// Figures for the `elapsed` since `prev` (which moves on to now); logged and written out.
+async fn summarize(prev: &mut Totals, elapsed: Duration) -> Summary {
+    let cur = Totals::now();
+    let secs = elapsed.as_secs_f64().max(1e-3);
+    let rate = |now: u64, before: u64| now.saturating_sub(before) as f64 / secs;
+    let (counts, latency_mean_ms) = metrics::SUMMARY_LATENCY.take_with_mean();
+    let s = Summary {
+        batches_per_s: rate(cur.batches, prev.batches),
+        drops_per_s: std::array::from_fn(|i| rate(cur.drops[i], prev.drops[i])),
+        latency_mean_ms,
+        latency_p95_ms: metrics::percentile(&counts, 95.0),
+        deadline_misses: cur.misses.saturating_sub(prev.misses),
+        buffer_fill_pct: match crate::telemetry::BUFFER.get() {
+            Some(buf) => buf.fill_pct().await,
+            None => 0.0,
+        },
+        downlink: match crate::downlink::DL.get() {
+            Some(dl) => dl.state().await,
+            None => "off",
+        },
+    };
+    *prev = cur;
+
+    info!(
+        batches_per_s = format_args!("{:.2}", s.batches_per_s),
+        drops_per_s = ?s.drops_per_s,
+        latency_mean_ms = ?s.latency_mean_ms,
+        latency_p95_ms = ?s.latency_p95_ms,
+        deadline_misses = s.deadline_misses,
+        buffer_fill_pct = format_args!("{:.1}", s.buffer_fill_pct),
+        downlink = s.downlink,
+        "summary"
+    );
+    csv::log_summary(
+        secs,
+        s.batches_per_s,
+        s.drops_per_s,
+        s.latency_mean_ms.unwrap_or(f64::NAN),
+        s.latency_p95_ms.unwrap_or(f64::NAN),
+        s.deadline_misses,
+        s.buffer_fill_pct,
+        s.downlink,
+    )
+    .await;
+    s
+}
+
+/// One summary every `interval_secs`.
+pub fn spawn(interval_secs: u64) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = time::interval(Duration::from_secs(interval_secs));
+        ticker.tick().await; // the first tick is immediate
+        let mut prev = Totals::now();
+        let mut last = Instant::now();
+        loop {
+            ticker.tick().await;
+            let now = Instant::now();
+            summarize(&mut prev, now - last).await;
+            last = now;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn summary_row_reflects_recent_activity() {
+        let mut prev = Totals::now();
+        for _ in 0..3 {
+            metrics::count(&metrics::BATCHES_SENT);
+        }
+        metrics::record_drop("critical");
+        metrics::SUMMARY_LATENCY.record(4.0);
+        crate::scheduler::record_deadline_miss("summary_test");
+
+        let s = summarize(&mut prev, Duration::from_secs(1)).await;
+        assert!(s.batches_per_s >= 3.0, "{s:?}");
+        assert!(s.drops_per_s[1] >= 1.0, "{s:?}");
+        assert!(s.latency_mean_ms.is_some_and(|m| m > 0.0) && s.latency_p95_ms.is_some(), "{s:?}");
+        assert!(s.deadline_misses >= 1, "{s:?}");
+
+        csv::flush_all().await;
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("summary.csv")).unwrap();
+        assert!(text.starts_with("ts,interval_s,batches_per_s,drops_per_s_emergency,"));
+        assert!(text.lines().count() >= 2);
+    }
+}
@@ -12,14 +12,17 @@ mod logging;
 mod util;
 mod downlink;
 mod faults;
+mod shutdown;
 
 use anyhow::Result;
+use net::transport::Transport;
 use std::sync::Arc;
 use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let started = std::time::Instant::now(); // heartbeat uptime
     // -------- logging ----------
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -32,58 +35,126 @@ async fn main() -> Result<()> {
         .init();
 
     // -------- config + crypto ----------
-    let cfg = config::Cli::parse_and_build_config()?;
+    let (cfg, command) = config::Cli::parse_and_build_config()?;
+    logging::csv::set_dir(&cfg.log_dir);
+    if let Some(config::CliCommand::DecodeLog { path }) = &command {
+        for rec in logging::csv::read_binary_log(path)? {
+            let ts = chrono::DateTime::from_timestamp_micros(rec.ts_micros).unwrap_or_default();
+            let cols: Vec<String> = rec.values.iter().map(ToString::to_string).collect();
+            println!("{},{}", ts.to_rfc3339(), cols.join(","));
+        }
+        return Ok(());
+    }
     let crypto = crypto::Crypto::from_config(&cfg)?;
+    if let Some(config::CliCommand::Replay(args)) = command {
+        telemetry::csv_replay::run(&cfg, &crypto, &args).await?;
+        return Ok(());
+    }
     info!(?cfg, "Satellite OCS starting");
+    logging::csv::set_rotate_bytes(cfg.log_rotate_bytes);
+    logging::csv::set_format(cfg.log_format);
+    logging::csv::set_binary_logs(&cfg.binary_logs).map_err(anyhow::Error::msg)?;
+    scheduler::timing::init_clock(cfg.clock_drift_ppm);
 
     // -------- sockets + framing ----------
     // Expect net::udp::connect(&cfg) to bind local socket and connect to GCS
     let (tx_sock_raw, rx_sock_raw) = net::udp::connect(&cfg).await?;
-    // tokio::net::UdpSocket has no try_clone(); share via Arc
-    let tx_sock = Arc::new(tx_sock_raw);
-    let rx_sock = Arc::new(rx_sock_raw);
+    // tokio::net::UdpSocket has no try_clone(); share via Arc.
+    // The tx side is rebound in place after repeated send errors.
+    // Optional link emulation (--link-*) loses, delays or duplicates frames on the way out.
+    let link = net::link_emu::LinkEmu::from_config(&cfg).map_err(anyhow::Error::msg)?;
+    // Everything past this point sees only the `Transport` interface.
+    let tx_sock: Arc<dyn Transport> = Arc::new(net::udp::TxSocket::new(tx_sock_raw, cfg.gcs_addr.clone()).with_link(link));
+    let rx_sock: Arc<dyn Transport> = Arc::new(rx_sock_raw);
+    // Capability handshake before anything else reads the rx socket or sends telemetry
+    if cfg.handshake_timeout_ms > 0 {
+        let wait = std::time::Duration::from_millis(cfg.handshake_timeout_ms);
+        net::handshake::run(&crypto, tx_sock.as_ref(), rx_sock.as_ref(), wait).await?;
+    }
 
     // length-prefixed frame helper
     let framer = net::framing::Framer::default();
 
     // -------- telemetry buffer before producers ----------
-    telemetry::init_priority_buffer(cfg.max_batch * 8); // e.g., 8 batches deep
+    telemetry::init_priority_buffer(cfg.max_batch * 8, cfg.aging_ms, cfg.drop_policy, cfg.bucket_caps); // e.g., 8 batches deep
+    telemetry::history::init(cfg.history_depth);
 
     // -------- background services ----------
+    // Every long-running task, aborted once the batcher has drained
+    let mut tasks = Vec::new();
     // Downlink visibility window simulator (5ms init rule, 30ms prep check)
-    downlink::init_and_spawn();
+    tasks.push(downlink::init_and_spawn(&cfg).map_err(anyhow::Error::msg)?);
 
-    // Fault injector (every 60s; recovery deadline 200ms)
-    faults::init_and_spawn();
+    // Fault injector (schedule or 60s rotation; recovery deadline 200ms)
+    tasks.push(faults::init_and_spawn(&cfg).map_err(anyhow::Error::msg)?);
+    // Flight recorder: dump the last events to <log dir>/blackbox.csv on Abort
+    logging::blackbox::spawn_dump_on_abort();
+    // Periodic health summary (--summary-secs)
+    if cfg.summary_secs > 0 {
+        tasks.push(logging::summary::spawn(cfg.summary_secs));
+    }
+    // Prometheus scrape endpoint (--metrics-port)
+    if let Some(port) = cfg.metrics_port {
+        net::metrics_http::spawn(port).await?;
+    }
 
     // -------- spawn subsystems ----------
     // 1) Telemetry batcher (installs CHANNEL and EMER_TX)
-    telemetry::spawn_batcher(cfg.clone(), crypto.clone(), tx_sock.clone(), framer.clone()).await;
+    let batcher =
+        telemetry::spawn_batcher(cfg.clone(), crypto.clone(), tx_sock.clone(), framer.clone()).await;
 
-    // 2) Sensors (thermal / power / attitude)
-    sensors::spawn_all(cfg.clone()).await;
+    // 2) Sensors (thermal / power / attitude / radiation)
+    tasks.extend(sensors::spawn_all(cfg.clone()).await?);
+    tasks.push(health::watchdog::spawn_watchdog(&cfg));
 
     // 3) RM scheduler (data compression, health monitor, antenna alignment)
-    let _ = tokio::spawn(scheduler::rm::spawn_rm(cfg.clone()));
+    tasks.push(tokio::spawn(scheduler::rm::spawn_rm(cfg.clone())));
 
     // 4) Command receiver/handler (decrypts, ACKs)
-    commands::spawn_receiver(
+    tasks.push(commands::spawn_receiver(
         cfg.clone(),
         crypto.clone(),
-        rx_sock.clone(), // Arc<UdpSocket>
-        tx_sock.clone(), // Arc<UdpSocket>
+        rx_sock.clone(), // Arc<dyn Transport> (UDP)
+        tx_sock.clone(), // Arc<dyn Transport> (UDP, rebinding)
         framer,          // moved in
-    ).await;
+        telemetry::EMER_TX.get().cloned(),
+    ).await);
 
     // 5) Heartbeat sender (SystemHealth)
-    health::spawn_heartbeat(cfg.clone(), crypto.clone(), tx_sock.clone()).await;
+    tasks.push(health::spawn_heartbeat(
+        cfg.clone(),
+        crypto.clone(),
+        tx_sock.clone(),
+        started,
+        health::SchedulerCpu(health::SysinfoMetrics::new()),
+    ).await);
 
     info!("OCS running. Press Ctrl+C to stop…");
 
     // -------- graceful shutdown ----------
-    if let Err(e) = tokio::signal::ctrl_c().await {
-        warn!(?e, "failed to install Ctrl+C handler");
+    // Ctrl+C, or --run-for-secs elapsing (bounded CI runs)
+    let run_for = std::time::Duration::from_secs(cfg.run_for_secs);
+    tokio::select! {
+        res = tokio::signal::ctrl_c() => {
+            if let Err(e) = res {
+                warn!(?e, "failed to install Ctrl+C handler");
+            }
+            info!("shutdown signal received; stopping producers");
+        }
+        _ = tokio::time::sleep(run_for), if cfg.run_for_secs > 0 => {
+            info!(secs = cfg.run_for_secs, "run duration elapsed; stopping producers");
+        }
+    }
+    shutdown::trigger();
+
+    // batcher sends what it can of the buffer, then logs are flushed to disk
+    if tokio::time::timeout(std::time::Duration::from_secs(2), batcher).await.is_err() {
+        warn!("batcher did not finish draining in time");
+    }
+    for task in tasks {
+        task.abort();
     }
-    info!("shutdown signal received; exiting.");
+    logging::csv::flush_all().await;
+    info!("exiting.");
     Ok(())
 }
@@ -3,10 +3,17 @@ pub struct Framer;
 
 impl Framer {
     pub fn deframe<'a>(&self, buf: &'a [u8]) -> anyhow::Result<&'a [u8]> {
-        if buf.len() < 4 { anyhow::bail!("short"); }
-        let len = u32::from_be_bytes([buf[0],buf[1],buf[2],buf[3]]) as usize;
-        if buf.len() < 4 + len { anyhow::bail!("incomplete"); }
-        Ok(&buf[..4+len])
+        // zero, oversized or overflowing lengths are errors, not "incomplete"
+        let Some(total) = shared_protocol::framed_len(buf)? else { anyhow::bail!("short"); };
+        if buf.len() < total { anyhow::bail!("incomplete"); }
+        Ok(&buf[..total])
+    }
+
+    /// The first frame's payload (prefix stripped) and how many bytes it took (4 + len),
+    /// so a caller can step through several frames packed in one buffer.
+    pub fn deframe_consumed<'a>(&self, buf: &'a [u8]) -> anyhow::Result<(&'a [u8], usize)> {
+        let framed = self.deframe(buf)?;
+        Ok((&framed[4..], framed.len()))
     }
 
     pub fn frame(&self, data: &[u8]) -> Vec<u8> {
@@ -16,3 +23,27 @@ impl Framer {
         framed
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concatenated_frames_parse_in_sequence() {
+        let framer = Framer;
+        let buf = [framer.frame(b"first"), framer.frame(b"second!")].concat();
+
+        let mut rest = &buf[..];
+        let mut got = Vec::new();
+        while !rest.is_empty() {
+            let (payload, consumed) = framer.deframe_consumed(rest).unwrap();
+            got.push(payload.to_vec());
+            rest = &rest[consumed..];
+        }
+        assert_eq!(got, [b"first".to_vec(), b"second!".to_vec()]);
+
+        // a truncated second frame is reported, not skipped
+        let (_, consumed) = framer.deframe_consumed(&buf[..buf.len() - 1]).unwrap();
+        assert!(framer.deframe_consumed(&buf[consumed..buf.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Capability handshake (`--handshake-timeout-ms`): before any telemetry, advertise what
+//! this build speaks and wait for ground's advertisement; both sides settle on the same
+//! protocol version, codec, AEAD and compression (`Capabilities::negotiate`).
+use anyhow::{anyhow, Result};
+use shared_protocol::{CommunicationPacket, Negotiated, PacketPayload, Reassembler, Source};
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::crypto::Crypto;
+use crate::net::transport::Transport;
+
+/// The advertisement is re-sent this often until ground answers.
+const RESEND: Duration = Duration::from_millis(500);
+
+/// `Ok(None)` if ground stayed silent for `wait` (the configured settings stay in force);
+/// an error if it answered with nothing in common.
+pub async fn run(crypto: &Crypto, tx: &dyn Transport, rx: &dyn Transport, wait: Duration) -> Result<Option<Negotiated>> {
+    let deadline = Instant::now() + wait;
+    let mut reassembler = Reassembler::default();
+    let mut buf = vec![0u8; 64 * 1024];
+    while Instant::now() < deadline {
+        let hello = CommunicationPacket::new_handshake(crypto.capabilities(), Source::Satellite);
+        tx.send(&crypto.seal(&hello)?).await?;
+
+        let until = deadline.min(Instant::now() + RESEND);
+        while let Ok(res) = time::timeout_at(until, rx.recv(&mut buf)).await {
+            let Some(frame) = reassembler.push(&buf[..res?]) else { continue };
+            match crypto.open(&frame).map(|pkt| pkt.payload) {
+                Ok(PacketPayload::Handshake(offer)) => {
+                    let n = crypto.negotiate(&offer).map_err(|e| anyhow!("handshake with ground failed: {e}"))?;
+                    info!(?n, "handshake: capabilities negotiated");
+                    return Ok(Some(n));
+                }
+                Ok(other) => warn!(?other, "handshake: ignoring traffic before ground answered"),
+                Err(e) => warn!(%e, "handshake: unreadable frame"),
+            }
+        }
+    }
+    warn!(wait_ms = wait.as_millis() as u64, "handshake: no answer from ground; using configured settings");
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::net::udp::TxSocket;
+    use shared_protocol::{Capabilities, PROTOCOL_VERSION};
+    use tokio::net::UdpSocket;
+
+    async fn ground_answers(offer: Capabilities) -> Result<Option<Negotiated>> {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let ground_crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let (sat_tx, sat_rx) = (UdpSocket::bind("127.0.0.1:0").await.unwrap(), UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        sat_tx.connect(ground.local_addr().unwrap()).await.unwrap();
+        let reply_to = sat_rx.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 64 * 1024];
+            let mut reassembler = Reassembler::default();
+            let frame = loop {
+                let n = ground.recv(&mut buf).await.unwrap();
+                if let Some(frame) = reassembler.push(&buf[..n]) {
+                    break frame;
+                }
+            };
+            let PacketPayload::Handshake(_) = ground_crypto.open(&frame).unwrap().payload else { panic!("expected a handshake") };
+            let reply = ground_crypto.seal(&CommunicationPacket::new_handshake(offer, Source::GroundControl)).unwrap();
+            ground.connect(reply_to).await.unwrap();
+            crate::net::udp::send_frame(&ground, &reply).await.unwrap();
+        });
+        run(&crypto, &TxSocket::from(sat_tx), &sat_rx, Duration::from_secs(2)).await
+    }
+
+    #[tokio::test]
+    async fn ground_answer_settles_the_link_or_fails_startup() {
+        let n = ground_answers(Capabilities { compression: false, ..Capabilities::all() }).await.unwrap().unwrap();
+        assert_eq!((n.protocol_version, n.compress), (PROTOCOL_VERSION, false));
+
+        let err = ground_answers(Capabilities { protocol_versions: vec![PROTOCOL_VERSION + 1], ..Capabilities::all() })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("incompatible protocol versions"), "{err}");
+    }
+}
@@ -0,0 +1,67 @@
+//! Link emulation (`--link-drop-pct`, `--link-dup-pct`, `--link-latency-ms`, `--link-jitter-ms`):
+//! decides, per outgoing frame, whether the simulated space link loses it, delays it or
+//! delivers it twice. Off unless one of the flags is set; seeded from `--rng-seed`.
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use tokio::time::Duration;
+
+use crate::config::Config;
+
+/// What the link does with one frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fate {
+    Drop,
+    Deliver { delay: Duration, copies: u8 },
+}
+
+#[derive(Debug)]
+pub struct LinkEmu {
+    drop_pct: f64,
+    dup_pct: f64,
+    latency: Duration,
+    jitter_ms: u64,
+    rng: ChaCha8Rng,
+}
+
+impl LinkEmu {
+    /// `None` when every impairment is zero, so the send path stays untouched.
+    pub fn new(
+        drop_pct: f64,
+        dup_pct: f64,
+        latency_ms: u64,
+        jitter_ms: u64,
+        seed: Option<u64>,
+    ) -> Result<Option<Self>, String> {
+        for (name, pct) in [("drop", drop_pct), ("dup", dup_pct)] {
+            if !(0.0..=100.0).contains(&pct) {
+                return Err(format!("link {name} percentage {pct} outside 0..=100"));
+            }
+        }
+        if drop_pct == 0.0 && dup_pct == 0.0 && latency_ms == 0 && jitter_ms == 0 {
+            return Ok(None);
+        }
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_rng(&mut rand::rng()),
+        };
+        Ok(Some(Self { drop_pct, dup_pct, latency: Duration::from_millis(latency_ms), jitter_ms, rng }))
+    }
+
+    pub fn from_config(cfg: &Config) -> Result<Option<Self>, String> {
+        Self::new(cfg.link_drop_pct, cfg.link_dup_pct, cfg.link_latency_ms, cfg.link_jitter_ms, cfg.rng_seed)
+    }
+
+    fn chance(&mut self, pct: f64) -> bool {
+        pct > 0.0 && self.rng.random_range(0.0..100.0) < pct
+    }
+
+    /// Roll the dice for the next frame.
+    pub fn fate(&mut self) -> Fate {
+        if self.chance(self.drop_pct) {
+            return Fate::Drop;
+        }
+        let jitter = if self.jitter_ms > 0 { self.rng.random_range(0..=self.jitter_ms) } else { 0 };
+        let copies = if self.chance(self.dup_pct) { 2 } else { 1 };
+        Fate::Deliver { delay: self.latency + Duration::from_millis(jitter), copies }
+    }
+}
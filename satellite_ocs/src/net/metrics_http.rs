@@ -0,0 +1,122 @@
+//! Optional Prometheus scrape endpoint (`--metrics-port`): a bare `GET /metrics` over
+//! tokio TCP, answered in the text exposition format.
+use crate::logging::metrics::{self, AGE_SLA_VIOLATIONS, BATCHES_SENT, DOWNLINK_MISSED_INITS, FAULTS_INJECTED};
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// Bind `0.0.0.0:port` (0 = any free port) and serve scrapes in the background.
+pub async fn spawn(port: u16) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    let addr = listener.local_addr()?;
+    info!(%addr, "metrics: serving /metrics");
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle(stream));
+                }
+                Err(e) => warn!(%e, "metrics: accept failed"),
+            }
+        }
+    });
+    Ok(addr)
+}
+
+/// One request per connection; anything but `GET /metrics` is a 404.
+async fn handle(mut stream: TcpStream) {
+    let mut req = [0u8; 1024];
+    let n = stream.read(&mut req).await.unwrap_or(0);
+    let request_line = std::str::from_utf8(&req[..n]).unwrap_or("").lines().next().unwrap_or("");
+    let resp = if request_line.starts_with("GET /metrics ") {
+        let body = render().await;
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+    let _ = stream.write_all(resp.as_bytes()).await;
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: &AtomicU64) {
+    header(out, name, "counter", help);
+    let _ = writeln!(out, "{name} {}", value.load(Ordering::Relaxed));
+}
+
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Current values of every exported metric.
+pub async fn render() -> String {
+    let mut out = String::new();
+    counter(&mut out, "ocs_batches_sent_total", "Telemetry batches sent", &BATCHES_SENT);
+
+    header(&mut out, "ocs_readings_dropped_total", "counter", "Readings dropped, by priority");
+    for (prio, n) in metrics::drop_snapshot() {
+        let _ = writeln!(out, "ocs_readings_dropped_total{{priority=\"{}\"}} {n}", label(&prio));
+    }
+
+    header(&mut out, "ocs_deadline_misses_total", "counter", "Deadline misses, by task");
+    let mut misses: Vec<_> = crate::scheduler::deadline_miss_snapshot().into_iter().collect();
+    misses.sort();
+    for (task, n) in misses {
+        let _ = writeln!(out, "ocs_deadline_misses_total{{task=\"{}\"}} {n}", label(&task));
+    }
+
+    counter(&mut out, "ocs_faults_injected_total", "Faults broadcast by the injector", &FAULTS_INJECTED);
+    counter(&mut out, "ocs_downlink_missed_inits_total", "Downlink passes missed (init > 5ms)", &DOWNLINK_MISSED_INITS);
+    counter(&mut out, "ocs_age_sla_violations_total", "Readings past --max-age-ms held by a closed window", &AGE_SLA_VIOLATIONS);
+
+    header(&mut out, "ocs_buffer_fill_percent", "gauge", "Priority buffer fill");
+    if let Some(buf) = crate::telemetry::BUFFER.get() {
+        let _ = writeln!(out, "ocs_buffer_fill_percent {:.1}", buf.fill_pct().await);
+    }
+    header(&mut out, "ocs_degradation_level", "gauge", "Degradation ladder rung (0 = normal .. 3 = emergency)");
+    let _ = writeln!(out, "ocs_degradation_level {}", crate::telemetry::degrade::level() as u8);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scrape_lists_the_exported_metrics() {
+        metrics::record_drop("normal");
+        crate::scheduler::record_deadline_miss("metrics_scrape_test");
+        let addr = spawn(0).await.unwrap();
+
+        let mut stream = TcpStream::connect(("127.0.0.1", addr.port())).await.unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: ocs\r\n\r\n").await.unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.unwrap();
+
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        for name in [
+            "ocs_batches_sent_total ",
+            "ocs_readings_dropped_total{priority=\"normal\"} ",
+            "ocs_deadline_misses_total{task=\"metrics_scrape_test\"} 1",
+            "ocs_faults_injected_total ",
+            "ocs_downlink_missed_inits_total ",
+            "# TYPE ocs_buffer_fill_percent gauge",
+        ] {
+            assert!(resp.contains(name), "missing {name}: {resp}");
+        }
+
+        let mut stream = TcpStream::connect(("127.0.0.1", addr.port())).await.unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.unwrap();
+        assert!(resp.starts_with("HTTP/1.1 404"), "{resp}");
+    }
+}
@@ -1,2 +1,7 @@
 pub mod udp;
+pub mod handshake;
+pub mod link_emu;
+pub mod tcp;
+pub mod transport;
 pub mod framing;
+pub mod metrics_http;
@@ -0,0 +1,101 @@
+use crate::config::Config;
+use crate::net::framing::Framer;
+use crate::net::transport::Transport;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+/// Length-prefixed frames over a TCP byte stream (reliable command channel).
+pub struct FramedTcp<S = TcpStream> {
+    stream: S,
+    framer: Framer,
+    buf: Vec<u8>,
+}
+
+impl<S> FramedTcp<S> {
+    pub fn new(stream: S, framer: Framer) -> Self {
+        Self { stream, framer, buf: Vec::new() }
+    }
+}
+
+impl<S: AsyncRead + Unpin> FramedTcp<S> {
+    /// Next complete frame (length prefix included), or `None` on a clean EOF.
+    pub async fn next_frame(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            if let Ok(frame) = self.framer.deframe(&self.buf) {
+                let n = frame.len();
+                return Ok(Some(self.buf.drain(..n).collect()));
+            }
+            if let Err(e) = shared_protocol::framed_len(&self.buf) {
+                bail!("bad tcp frame: {e}");
+            }
+
+            let mut chunk = [0u8; 4096];
+            let n = self.stream.read(&mut chunk).await?;
+            if n == 0 {
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                bail!("tcp closed mid-frame ({} bytes pending)", self.buf.len());
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> FramedTcp<S> {
+    /// Write an already length-prefixed frame (e.g. from `Crypto::seal`).
+    pub async fn send_frame(&mut self, framed: &[u8]) -> Result<()> {
+        self.stream.write_all(framed).await?;
+        Ok(())
+    }
+}
+
+/// The command channel as a `Transport`. Each half has its own lock, so a `recv` waiting
+/// for ground doesn't hold up an ACK going the other way.
+pub struct TcpTransport {
+    rx: Mutex<FramedTcp<OwnedReadHalf>>,
+    tx: Mutex<FramedTcp<OwnedWriteHalf>>,
+}
+
+impl TcpTransport {
+    pub fn new(stream: TcpStream, framer: Framer) -> Self {
+        let (rx, tx) = stream.into_split();
+        Self { rx: Mutex::new(FramedTcp::new(rx, framer.clone())), tx: Mutex::new(FramedTcp::new(tx, framer)) }
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    async fn send(&self, frame: &[u8]) -> std::io::Result<()> {
+        self.tx.lock().await.send_frame(frame).await.map_err(std::io::Error::other)
+    }
+
+    /// One whole frame per call; 0 on a clean close.
+    async fn recv(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some(frame) = self.rx.lock().await.next_frame().await.map_err(std::io::Error::other)? else {
+            return Ok(0);
+        };
+        let dst = buf
+            .get_mut(..frame.len())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "tcp frame larger than buffer"))?;
+        dst.copy_from_slice(&frame);
+        Ok(frame.len())
+    }
+}
+
+/// Connect to the ground station's TCP command endpoint (`--cmd-tcp-addr`).
+pub async fn connect_command_channel(cfg: &Config) -> Result<TcpTransport> {
+    let addr = cfg
+        .cmd_tcp_addr
+        .as_deref()
+        .context("no TCP command address configured")?;
+    let stream = TcpStream::connect(addr)
+        .await
+        .with_context(|| format!("connect command channel {addr}"))?;
+    stream.set_nodelay(true)?;
+    Ok(TcpTransport::new(stream, Framer))
+}
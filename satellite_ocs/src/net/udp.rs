@@ -1,10 +1,345 @@
 use crate::config::Config;
+use crate::net::link_emu::{Fate, LinkEmu};
 use anyhow::Result;
+use once_cell::sync::OnceCell;
+use shared_protocol::{framed_len, EncryptedFrame, Fragmenter};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::net::UdpSocket;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+// Splits frames above the MTU (frame ids are unique per process)
+static FRAGMENTER: OnceCell<Fragmenter> = OnceCell::new();
+
+/// Consecutive send errors before the tx socket is rebuilt.
+const FAILURES_BEFORE_REBIND: u32 = 3;
+/// Wait between rebind attempts: doubles per failed attempt, reset on success.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
 
 pub async fn connect(cfg: &Config) -> Result<(UdpSocket, UdpSocket)> {
-    let tx = UdpSocket::bind("0.0.0.0:0").await?;
-    tx.connect(&cfg.gcs_addr).await?;
-    let rx = UdpSocket::bind(&cfg.bind_addr).await?;
+    let _ = FRAGMENTER.set(Fragmenter::new(cfg.mtu));
+    let tx = reconnect(&cfg.gcs_addr).await?;
+    let rx = if !cfg.udp_reuse_addr && cfg.udp_recv_buffer == 0 {
+        UdpSocket::bind(&cfg.bind_addr).await?
+    } else {
+        bind_with(&cfg.bind_addr, cfg.udp_reuse_addr, cfg.udp_recv_buffer)?
+    };
     Ok((tx, rx))
 }
+
+/// Bind with socket options tokio doesn't expose: `SO_REUSEADDR`, and a receive buffer
+/// of `recv_buffer` bytes (0 keeps the kernel default) against drops during bursts.
+pub fn bind_with(addr: &str, reuse_addr: bool, recv_buffer: usize) -> std::io::Result<UdpSocket> {
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("no address for {addr}")))?;
+    let sock = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    sock.set_reuse_address(reuse_addr)?;
+    if recv_buffer > 0 {
+        sock.set_recv_buffer_size(recv_buffer)?;
+        // the kernel may round or cap it (Linux doubles it, up to rmem_max)
+        info!(requested = recv_buffer, effective = sock.recv_buffer_size()?, "udp: receive buffer sized");
+    }
+    sock.set_nonblocking(true)?;
+    sock.bind(&addr.into())?;
+    UdpSocket::from_std(sock.into())
+}
+
+/// A fresh tx socket: bound to an ephemeral port and connected to `gcs_addr`.
+pub async fn reconnect(gcs_addr: &str) -> std::io::Result<UdpSocket> {
+    let tx = UdpSocket::bind("0.0.0.0:0").await?;
+    tx.connect(gcs_addr).await?;
+    Ok(tx)
+}
+
+/// Send a sealed frame on a connected socket, fragmenting it when it exceeds the MTU.
+pub async fn send_frame(sock: &UdpSocket, frame: &[u8]) -> std::io::Result<()> {
+    let datagrams = FRAGMENTER
+        .get_or_init(Fragmenter::default)
+        .fragment(frame)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    for d in &datagrams {
+        sock.send(d).await?;
+    }
+    Ok(())
+}
+
+/// packets.csv row for a sealed frame, taken from its clear header (type and sequence number).
+async fn log_sent(frame: &[u8]) {
+    let Ok(Some(total)) = framed_len(frame) else { return };
+    if let Some(body) = frame.get(4..total)
+        && let Ok(sealed) = EncryptedFrame::from_bytes(body)
+    {
+        let kind = format!("{:?}", sealed.header.packet_type).to_lowercase();
+        crate::logging::csv::log_packet(&kind, sealed.header.sequence_number, frame.len()).await;
+    }
+}
+
+#[derive(Debug)]
+struct Rebind {
+    failures: u32,
+    backoff: Duration,
+    not_before: Option<Instant>,
+}
+
+/// The shared tx socket. Repeated send errors (interface down, route change) swap in
+/// a freshly bound one; every task sending through the same `Arc` picks it up.
+#[derive(Debug)]
+pub struct TxSocket {
+    sock: RwLock<Arc<UdpSocket>>,
+    gcs_addr: String,
+    rebind: Mutex<Rebind>,
+    attempts: AtomicU64,
+    link: Option<Mutex<LinkEmu>>,
+}
+
+impl TxSocket {
+    pub fn new(sock: UdpSocket, gcs_addr: impl Into<String>) -> Self {
+        Self {
+            sock: RwLock::new(Arc::new(sock)),
+            gcs_addr: gcs_addr.into(),
+            rebind: Mutex::new(Rebind { failures: 0, backoff: INITIAL_BACKOFF, not_before: None }),
+            attempts: AtomicU64::new(0),
+            link: None,
+        }
+    }
+
+    /// Route every frame through a simulated lossy link (see `link_emu`); `None` sends directly.
+    pub fn with_link(mut self, link: Option<LinkEmu>) -> Self {
+        self.link = link.map(Mutex::new);
+        self
+    }
+
+    /// The socket currently in use.
+    pub fn current(&self) -> Arc<UdpSocket> {
+        self.sock.read().unwrap().clone()
+    }
+
+    /// Rebind attempts so far.
+    #[cfg(test)]
+    pub fn reconnects(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// `send_frame` on the current socket; errors count towards a rebind. Every outbound
+    /// frame passes through here, so this is also where packets.csv is written.
+    pub async fn send_frame(&self, frame: &[u8]) -> std::io::Result<()> {
+        log_sent(frame).await;
+        let fate = match &self.link {
+            Some(link) => link.lock().unwrap().fate(),
+            None => Fate::Deliver { delay: Duration::ZERO, copies: 1 },
+        };
+        let res = match fate {
+            // lost in flight: the sender can't tell
+            Fate::Drop => {
+                debug!(bytes = frame.len(), "link emulation: frame dropped");
+                return Ok(());
+            }
+            Fate::Deliver { delay, copies } if delay.is_zero() => {
+                let mut res = Ok(());
+                for _ in 0..copies {
+                    res = send_frame(&self.current(), frame).await;
+                }
+                res
+            }
+            // delivered later without holding up the caller; errors there don't count
+            // towards a rebind
+            Fate::Deliver { delay, copies } => {
+                let (sock, frame) = (self.current(), frame.to_vec());
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    for _ in 0..copies {
+                        if let Err(e) = send_frame(&sock, &frame).await {
+                            warn!(%e, "link emulation: delayed send failed");
+                        }
+                    }
+                });
+                return Ok(());
+            }
+        };
+        self.after_send(&res).await;
+        res
+    }
+
+    async fn after_send(&self, res: &std::io::Result<()>) {
+        {
+            let mut st = self.rebind.lock().unwrap();
+            if res.is_ok() {
+                st.failures = 0;
+                return;
+            }
+            st.failures += 1;
+            let now = Instant::now();
+            if st.failures < FAILURES_BEFORE_REBIND || st.not_before.is_some_and(|t| now < t) {
+                return;
+            }
+            st.not_before = Some(now + st.backoff);
+            st.backoff = (st.backoff * 2).min(MAX_BACKOFF);
+        }
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        warn!(gcs = %self.gcs_addr, "tx socket failing; rebinding");
+        match reconnect(&self.gcs_addr).await {
+            Ok(sock) => {
+                *self.sock.write().unwrap() = Arc::new(sock);
+                let mut st = self.rebind.lock().unwrap();
+                st.failures = 0;
+                st.backoff = INITIAL_BACKOFF;
+                info!(gcs = %self.gcs_addr, "tx socket rebound");
+            }
+            Err(e) => warn!(%e, gcs = %self.gcs_addr, "tx socket rebind failed"),
+        }
+    }
+}
+
+/// Wraps a socket already connected to the ground; the peer address is the rebind target.
+impl From<UdpSocket> for TxSocket {
+    fn from(sock: UdpSocket) -> Self {
+        let peer = sock.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+        Self::new(sock, peer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Crypto;
+    use chrono::Utc;
+    use shared_protocol::{
+        Command, CommandAcknowledgment, CommunicationPacket, EmergencyData, Severity, Source, SystemHealth,
+        ThermalSensor,
+    };
+    use socket2::SockRef;
+
+    #[tokio::test]
+    async fn reuse_addr_lets_a_second_socket_bind_the_same_address() {
+        let first = bind_with("127.0.0.1:0", true, 0).unwrap();
+        let addr = first.local_addr().unwrap().to_string();
+        let second = bind_with(&addr, true, 64 * 1024).unwrap();
+        assert_eq!(second.local_addr().unwrap(), first.local_addr().unwrap());
+        assert!(SockRef::from(&second).recv_buffer_size().unwrap() >= 64 * 1024);
+
+        // without the option on both sides the address is taken
+        let err = bind_with(&addr, false, 0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+        let plain = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let err = bind_with(&plain.local_addr().unwrap().to_string(), true, 0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    }
+
+    #[tokio::test]
+    async fn emulated_link_drops_or_delivers_by_probability() {
+        let recv_all = |ground: UdpSocket| async move {
+            let mut buf = [0u8; 64];
+            let mut n = 0;
+            while tokio::time::timeout(Duration::from_millis(100), ground.recv(&mut buf)).await.is_ok() {
+                n += 1;
+            }
+            n
+        };
+        for (drop_pct, dup_pct, latency_ms, expected) in [(100.0, 0.0, 0, 0), (0.0, 0.0, 20, 10), (0.0, 100.0, 0, 20)] {
+            let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            sat.connect(ground.local_addr().unwrap()).await.unwrap();
+            let link = LinkEmu::new(drop_pct, dup_pct, latency_ms, 0, Some(7)).unwrap();
+            assert!(link.is_some());
+            let tx = TxSocket::from(sat).with_link(link);
+            for i in 0..10u8 {
+                tx.send_frame(&[i]).await.unwrap();
+            }
+            assert_eq!(recv_all(ground).await, expected, "drop {drop_pct}% dup {dup_pct}%");
+        }
+        assert!(LinkEmu::new(0.0, 0.0, 0, 0, None).unwrap().is_none());
+        assert!(LinkEmu::new(101.0, 0.0, 0, 0, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn repeated_send_errors_rebind_with_backoff() {
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+        let tx = TxSocket::from(sat);
+        let before = tx.current().local_addr().unwrap();
+        let fail = || Err(std::io::Error::other("network unreachable"));
+
+        for _ in 0..FAILURES_BEFORE_REBIND - 1 {
+            tx.after_send(&fail()).await;
+        }
+        assert_eq!(tx.reconnects(), 0);
+        tx.after_send(&fail()).await;
+        assert_eq!(tx.reconnects(), 1);
+        assert_ne!(tx.current().local_addr().unwrap(), before);
+
+        // still inside the backoff window: no second attempt yet
+        for _ in 0..FAILURES_BEFORE_REBIND {
+            tx.after_send(&fail()).await;
+        }
+        assert_eq!(tx.reconnects(), 1);
+
+        tx.send_frame(b"still connected").await.unwrap();
+        let mut buf = [0u8; 64];
+        assert!(ground.recv(&mut buf).await.unwrap() > 0);
+    }
+
+    #[tokio::test]
+    async fn every_sent_packet_type_lands_in_packets_csv() {
+        let crypto = Crypto::from_config(&Config::test_default()).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let tx = TxSocket::new(reconnect(&ground.local_addr().unwrap().to_string()).await.unwrap(), "unused");
+        let ack = CommandAcknowledgment {
+            command_id: "c1".into(),
+            status: "completed".into(),
+            execution_timestamp: None,
+            completion_timestamp: None,
+            error_message: None,
+            execution_time_ms: 0.0,
+        };
+        let alert = EmergencyData {
+            alert_id: "a1".into(),
+            severity: Severity::Critical,
+            alert_type: "thermal".into(),
+            description: String::new(),
+            affected_systems: vec![],
+            recommended_actions: vec![],
+            auto_recovery_attempted: false,
+            timestamp: Utc::now(),
+            metadata: Default::default(),
+        };
+        let health = SystemHealth {
+            overall_status: "nominal".into(),
+            cpu_usage_percent: 1.0,
+            memory_usage_percent: 1.0,
+            disk_usage_percent: 0.0,
+            uptime_seconds: 0,
+            active_tasks: 0,
+            failed_tasks: 0,
+            timestamp: Utc::now(),
+            deadline_misses: Default::default(),
+            ingress_backpressure: 0,
+        };
+        let packets = [
+            CommunicationPacket::new_telemetry(vec![ThermalSensor::new(1, "CPU").create_reading(60.0, 0)], Source::Satellite),
+            CommunicationPacket::new_command(Command::clock_correction(5.0), Source::Satellite),
+            CommunicationPacket::new_ack(ack, Source::Satellite),
+            CommunicationPacket::new_emergency(alert, Source::Satellite),
+            CommunicationPacket::new_heartbeat(health, Source::Satellite),
+        ];
+        let mut rows = Vec::new();
+        for (pkt, kind) in packets.iter().zip(["telemetry", "command", "ack", "emergency", "heartbeat"]) {
+            let frame = crypto.seal(pkt).unwrap();
+            tx.send_frame(&frame).await.unwrap();
+            rows.push(format!(",{kind},{},{}", pkt.header.sequence_number, frame.len()));
+        }
+
+        crate::logging::csv::flush_all().await;
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("packets.csv")).unwrap();
+        assert!(text.starts_with("ts,packet_type,seq,bytes\n"));
+        for row in rows {
+            assert!(text.lines().any(|l| l.ends_with(&row)), "missing {row}");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Simulated execution times (`--exec-model`): each released job draws how long it actually
+//! runs from a distribution around its task's WCET, so the scheduler sees variable demand and
+//! the occasional overrun. Seeded from `--rng-seed` so a run can be replayed.
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::{Distribution, Normal};
+
+/// Shortest draw, as a fraction of WCET; keeps a wide normal from producing empty jobs.
+const MIN_FRAC: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExecModel {
+    /// Every job runs exactly its WCET.
+    #[default]
+    Fixed,
+    /// Uniform in [bcet, wcet], with bcet = `bcet_frac` × WCET.
+    Uniform { bcet_frac: f64 },
+    /// Normal(`mean_frac` × WCET, `sd_frac` × WCET), clamped to `max_frac` × WCET;
+    /// `max_frac` > 1 lets a job overrun its budgeted WCET.
+    Normal { mean_frac: f64, sd_frac: f64, max_frac: f64 },
+}
+
+impl ExecModel {
+    /// Inclusive range every draw for a task with this WCET falls in.
+    pub fn bounds(&self, wcet_ms: f64) -> (f64, f64) {
+        match *self {
+            ExecModel::Fixed => (wcet_ms, wcet_ms),
+            ExecModel::Uniform { bcet_frac } => (bcet_frac * wcet_ms, wcet_ms),
+            ExecModel::Normal { max_frac, .. } => (MIN_FRAC * wcet_ms, max_frac * wcet_ms),
+        }
+    }
+}
+
+impl std::str::FromStr for ExecModel {
+    type Err = String;
+
+    /// "fixed", "uniform:BCET_FRAC" or "normal:MEAN_FRAC:SD_FRAC:MAX_FRAC",
+    /// e.g. "uniform:0.5" or "normal:0.7:0.2:1.5".
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut parts = s.split(':').map(str::trim);
+        let kind = parts.next().unwrap_or_default();
+        let nums = parts
+            .map(|p| p.parse::<f64>().map_err(|e| format!("exec model '{s}': {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let model = match (kind, nums.as_slice()) {
+            ("fixed", []) => ExecModel::Fixed,
+            ("uniform", &[bcet_frac]) if (0.0..=1.0).contains(&bcet_frac) => {
+                ExecModel::Uniform { bcet_frac }
+            }
+            ("normal", &[mean_frac, sd_frac, max_frac])
+                if mean_frac > 0.0 && sd_frac >= 0.0 && max_frac >= MIN_FRAC =>
+            {
+                ExecModel::Normal { mean_frac, sd_frac, max_frac }
+            }
+            _ => {
+                return Err(format!(
+                    "exec model '{s}': expected fixed, uniform:BCET_FRAC or normal:MEAN:SD:MAX"
+                ))
+            }
+        };
+        Ok(model)
+    }
+}
+
+/// Per-scheduler draw state.
+pub struct ExecTimes {
+    model: ExecModel,
+    rng: ChaCha8Rng,
+}
+
+impl ExecTimes {
+    pub fn new(model: ExecModel, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_rng(&mut rand::rng()),
+        };
+        Self { model, rng }
+    }
+
+    /// Execution time (ms) for the next job of a task with this WCET.
+    pub fn draw(&mut self, wcet_ms: f64) -> f64 {
+        let (lo, hi) = self.model.bounds(wcet_ms);
+        match self.model {
+            ExecModel::Fixed => wcet_ms,
+            ExecModel::Uniform { .. } => {
+                if hi > lo { self.rng.random_range(lo..=hi) } else { hi }
+            }
+            ExecModel::Normal { mean_frac, sd_frac, .. } => {
+                // sd_frac was validated non-negative, so this cannot fail
+                let n = Normal::new(mean_frac * wcet_ms, sd_frac * wcet_ms)
+                    .expect("non-negative std dev");
+                n.sample(&mut self.rng).clamp(lo, hi)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_model_and_rejects_junk() {
+        assert_eq!("fixed".parse::<ExecModel>(), Ok(ExecModel::Fixed));
+        assert_eq!("uniform:0.5".parse::<ExecModel>(), Ok(ExecModel::Uniform { bcet_frac: 0.5 }));
+        assert_eq!(
+            "normal:0.7:0.2:1.5".parse::<ExecModel>(),
+            Ok(ExecModel::Normal { mean_frac: 0.7, sd_frac: 0.2, max_frac: 1.5 })
+        );
+        for bad in ["", "uniform", "uniform:1.5", "normal:0.7:0.2", "normal:0.7:-1:1.5", "gamma:1"] {
+            assert!(bad.parse::<ExecModel>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn draws_vary_within_bounds_and_sometimes_overrun_the_deadline() {
+        // 8ms WCET against a 10ms deadline: only the normal tail past 1.25×WCET misses
+        let (wcet, deadline) = (8.0, 10.0);
+        for model in [ExecModel::Uniform { bcet_frac: 0.5 }, ExecModel::Normal { mean_frac: 0.8, sd_frac: 0.3, max_frac: 1.5 }] {
+            let (lo, hi) = model.bounds(wcet);
+            let mut exec = ExecTimes::new(model, Some(42));
+            let draws: Vec<f64> = (0..2000).map(|_| exec.draw(wcet)).collect();
+            assert!(draws.iter().all(|d| (lo..=hi).contains(d)), "{model:?}");
+            let (min, max) = draws.iter().fold((f64::MAX, f64::MIN), |(a, b), &d| (a.min(d), b.max(d)));
+            assert!(max - min > 0.25 * wcet, "{model:?}: runtimes barely vary");
+
+            let misses = draws.iter().filter(|&&d| d > deadline).count();
+            match model {
+                ExecModel::Uniform { .. } => assert_eq!(misses, 0),
+                _ => assert!(misses > 0 && misses < draws.len() / 5, "{misses} misses"),
+            }
+            // same seed, same sequence
+            let mut again = ExecTimes::new(model, Some(42));
+            assert!(draws.iter().all(|&d| again.draw(wcet) == d));
+        }
+        let mut fixed = ExecTimes::new(ExecModel::Fixed, None);
+        assert_eq!(fixed.draw(wcet), wcet);
+    }
+}
@@ -1,9 +1,130 @@
+pub mod exec;
 pub mod timing;
 // src/scheduler/mod.rs
 pub mod rm;
 
+use crate::logging::blackbox::{self, Event, EventKind};
+
 // A tiny preemption hook: thermal sensor can send here to preempt running work.
+use dashmap::DashMap;
 use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
 
 pub static PREEMPT_CH: OnceCell<mpsc::Sender<()>> = OnceCell::new();
+
+/// Thermal side of `PREEMPT_CH`: requests a thermal_control job at most once per
+/// `--thermal-min-interarrival-ms`, the spacing the schedulability check assumes.
+pub struct PreemptTrigger {
+    min_gap: Duration,
+    last: Option<Instant>,
+}
+
+impl PreemptTrigger {
+    pub fn new(min_interarrival_ms: u64) -> Self {
+        Self { min_gap: Duration::from_millis(min_interarrival_ms), last: None }
+    }
+
+    /// True if a job was requested; false while rate-limited, before the scheduler is
+    /// up, or when a request is already pending.
+    pub fn fire(&mut self, now: Instant) -> bool {
+        if self.last.is_some_and(|t| now.duration_since(t) < self.min_gap) {
+            return false;
+        }
+        let Some(tx) = PREEMPT_CH.get() else { return false };
+        if tx.try_send(()).is_err() {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+// Periodic tasks the scheduler is currently running (read by heartbeat)
+static ACTIVE_TASKS: AtomicU32 = AtomicU32::new(0);
+
+pub fn set_active_tasks(n: u32) {
+    ACTIVE_TASKS.store(n, Ordering::Relaxed);
+}
+
+pub fn active_tasks() -> u32 {
+    ACTIVE_TASKS.load(Ordering::Relaxed)
+}
+
+// Scheduler-measured CPU utilization (%) of the last accounting window, as f64 bits;
+// NaN until the first window closes
+static CPU_UTILIZATION: AtomicU64 = AtomicU64::new(u64::MAX);
+
+pub fn set_cpu_utilization(pct: f64) {
+    CPU_UTILIZATION.store(pct.to_bits(), Ordering::Relaxed);
+}
+
+pub fn cpu_utilization() -> Option<f64> {
+    let pct = f64::from_bits(CPU_UTILIZATION.load(Ordering::Relaxed));
+    (!pct.is_nan()).then_some(pct)
+}
+
+// Deadline misses per task name (written by the scheduler, read by heartbeat)
+static DEADLINE_MISSES: OnceCell<DashMap<String, AtomicU64>> = OnceCell::new();
+
+fn deadline_misses() -> &'static DashMap<String, AtomicU64> {
+    DEADLINE_MISSES.get_or_init(DashMap::new)
+}
+
+/// Count one deadline miss for `task`.
+pub fn record_deadline_miss(task: &str) {
+    blackbox::record(Event::new(EventKind::DeadlineMiss, task, "deadline missed"));
+    let map = deadline_misses();
+    if let Some(c) = map.get(task) {
+        c.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    map.entry(task.to_string())
+        .or_default()
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+/// Current miss counts for every task that has missed at least once.
+pub fn deadline_miss_snapshot() -> HashMap<String, u64> {
+    deadline_misses()
+        .iter()
+        .map(|e| (e.key().clone(), e.value().load(Ordering::Relaxed)))
+        .collect()
+}
+
+/// Dispatch policy for the periodic task set (selected at startup).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SchedPolicy {
+    /// Static priorities: shorter period wins.
+    #[default]
+    #[value(name = "rm")]
+    RateMonotonic,
+    /// Dynamic priorities: earliest absolute deadline wins.
+    Edf,
+}
+
+impl SchedPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchedPolicy::RateMonotonic => "rm",
+            SchedPolicy::Edf => "edf",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_miss_increments_counter() {
+        // unique name: the counter map is process-wide
+        let task = "test_deadline_miss_task";
+        let before = deadline_miss_snapshot().get(task).copied().unwrap_or(0);
+        record_deadline_miss(task);
+        record_deadline_miss(task);
+        assert_eq!(deadline_miss_snapshot()[task], before + 2);
+    }
+}
@@ -1,6 +1,7 @@
 // src/scheduler/rm.rs
 use crate::{config::Config, logging};
-use super::PREEMPT_CH;
+use super::exec::ExecTimes;
+use super::{SchedPolicy, PREEMPT_CH};
 
 use std::cmp::Ordering;
 use std::time::Duration as StdDuration;
@@ -39,21 +40,82 @@ impl RtTask {
     }
 }
 
+// Sporadic thermal_control job: simulated work and relative deadline
+const THERMAL_WCET_MS: f64 = 2.0;
+const THERMAL_DEADLINE_MS: u64 = 20;
+
 #[derive(Debug)]
 struct Job {
     task_idx: usize,
     release: Instant,
     deadline: Instant,
     seq: u64,
+    exec_ms: f64,            // drawn execution time (see exec::ExecModel)
     remaining_ms: f64,
     preemptions: u32,
+    started: Option<Instant>, // first dispatch; kept across preemptions
+}
+
+/// Total processor utilization of the periodic task set: sum(C_i / T_i).
+fn rm_utilization(tasks: &[RtTask]) -> f64 {
+    tasks
+        .iter()
+        .map(|t| t.wcet_ms / (t.period.as_secs_f64() * 1e3))
+        .sum()
+}
+
+/// Liu-Layland sufficient bound for RM: n * (2^(1/n) - 1).
+fn liu_layland_bound(n: usize) -> f64 {
+    if n == 0 {
+        return 1.0;
+    }
+    let n = n as f64;
+    n * (2f64.powf(1.0 / n) - 1.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Schedulability {
+    utilization: f64,
+    bound: f64,
+    feasible: bool,
+}
+
+/// Periodic tasks plus the sporadic thermal job (treated as periodic at its
+/// minimum inter-arrival time) checked against the Liu-Layland bound.
+fn check_schedulability(tasks: &[RtTask], thermal_min_interarrival_ms: u64) -> Schedulability {
+    let thermal_u = THERMAL_WCET_MS / thermal_min_interarrival_ms.max(1) as f64;
+    let utilization = rm_utilization(tasks) + thermal_u;
+    let bound = liu_layland_bound(tasks.len() + 1);
+    Schedulability { utilization, bound, feasible: utilization <= bound }
+}
+
+/// Ready-queue order: thermal_control (task_idx == usize::MAX) always first,
+/// then by policy — RM: static `rm_priority`, EDF: absolute deadline.
+fn job_order(policy: SchedPolicy, tasks: &[RtTask], a: &Job, b: &Job) -> Ordering {
+    let a_thermal = a.task_idx == usize::MAX;
+    let b_thermal = b.task_idx == usize::MAX;
+    match (a_thermal, b_thermal) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (true, true) => a.deadline.cmp(&b.deadline),
+        (false, false) => match policy {
+            SchedPolicy::RateMonotonic => tasks[a.task_idx]
+                .rm_priority
+                .cmp(&tasks[b.task_idx].rm_priority)
+                .then(a.deadline.cmp(&b.deadline)),
+            SchedPolicy::Edf => a.deadline.cmp(&b.deadline),
+        },
+    }
 }
 
 /// Spawn the RM scheduler loop.
 /// - Schedules: antenna_alignment(50ms), data_compression(100ms), health_monitor(1000ms)
 /// - Preemption: a sporadic, highest-priority thermal_control job is injected when PREEMPT_CH fires.
+/// - `cfg.sched_policy` selects RM (static priority) or EDF (earliest deadline) dispatch.
 pub async fn spawn_rm(cfg: Config) {
     let now = Instant::now();
+    let policy = cfg.sched_policy;
+    info!(policy = policy.as_str(), "scheduler: starting");
 
     // RM priority by period (lower number = higher priority)
     // Moderately increased WCET to account for async overhead
@@ -63,6 +125,24 @@ pub async fn spawn_rm(cfg: Config) {
         RtTask::new("health_monitor",   1000, 2.0, 3, now),   // low - increased from 1.0
     ];
 
+    // Schedulability: warn up front rather than waiting for violations
+    let sched = check_schedulability(&tasks, cfg.thermal_min_interarrival_ms);
+    if sched.feasible {
+        info!(
+            utilization = format_args!("{:.3}", sched.utilization),
+            bound = format_args!("{:.3}", sched.bound),
+            "scheduler: task set within Liu-Layland bound"
+        );
+    } else {
+        warn!(
+            utilization = format_args!("{:.3}", sched.utilization),
+            bound = format_args!("{:.3}", sched.bound),
+            "scheduler: utilization exceeds Liu-Layland bound; deadlines may be missed"
+        );
+    }
+
+    super::set_active_tasks(tasks.len() as u32);
+
     // Preemption channel (thermal control trigger)
     let (tx_preempt, mut rx_preempt) = mpsc::channel::<()>(16);
     let _ = PREEMPT_CH.set(tx_preempt);
@@ -71,21 +151,29 @@ pub async fn spawn_rm(cfg: Config) {
     let mut ready: Vec<Job> = Vec::new();
 
     // CPU accounting (scheduler-level utilization)
+    let cpu_window = Duration::from_millis(cfg.cpu_window_ms.max(1));
     let mut win_start = Instant::now();
     let mut active_ms_acc: f64 = 0.0;
 
     // Helper: push newly-released jobs into ready queue
+    // per-job execution times (--exec-model, seeded by --rng-seed)
+    let mut exec = ExecTimes::new(cfg.exec_model, cfg.rng_seed);
+    info!(model = ?cfg.exec_model, "scheduler: execution time model selected");
+
     let mut release_due = |tasks: &mut [RtTask], ready: &mut Vec<Job>, now: Instant| {
         for (idx, t) in tasks.iter_mut().enumerate() {
             if now >= t.next_release {
                 t.seq = t.seq.wrapping_add(1);
+                let exec_ms = exec.draw(t.wcet_ms);
                 let job = Job {
                     task_idx: idx,
                     release: t.next_release,
                     deadline: t.next_deadline,
                     seq: t.seq,
-                    remaining_ms: t.wcet_ms,
+                    exec_ms,
+                    remaining_ms: exec_ms,
                     preemptions: 0,
+                    started: None,
                 };
                 ready.push(job);
                 // schedule next release/deadline
@@ -93,41 +181,31 @@ pub async fn spawn_rm(cfg: Config) {
                 t.next_deadline += t.deadline;
             }
         }
-        // RM order: by task priority (rm_priority), then earliest deadline
-        ready.sort_by(|a, b| {
-            let pa = tasks[a.task_idx].rm_priority;
-            let pb = tasks[b.task_idx].rm_priority;
-            match pa.cmp(&pb) {
-                Ordering::Equal => a.deadline.cmp(&b.deadline),
-                other => other,
-            }
-        });
+        // RM: by rm_priority then deadline; EDF: by deadline
+        ready.sort_by(|a, b| job_order(policy, tasks, a, b));
     };
 
     // Simulate the high-priority "thermal_control" sporadic job
-    let mut spawn_thermal_job = |ready: &mut Vec<Job>, now: Instant| {
+    let spawn_thermal_job = |tasks: &[RtTask], ready: &mut Vec<Job>, now: Instant| {
         // Use a synthetic "task index" = !0 to mark thermal_control
         let job = Job {
             task_idx: usize::MAX,
             release: now,
-            deadline: now + Duration::from_millis(20), // tight deadline
+            deadline: now + Duration::from_millis(THERMAL_DEADLINE_MS), // tight deadline
             seq: 0,
-            remaining_ms: 2.0, // simulate ~2ms of control work
+            exec_ms: THERMAL_WCET_MS,
+            remaining_ms: THERMAL_WCET_MS, // simulate ~2ms of control work
             preemptions: 0,
+            started: None,
         };
         ready.push(job);
-        // Ensure it bubbles to the top (it is highest priority)
-        ready.sort_by(|a, b| {
-            // thermal first
-            if a.task_idx == usize::MAX && b.task_idx != usize::MAX { Ordering::Less }
-            else if b.task_idx == usize::MAX && a.task_idx != usize::MAX { Ordering::Greater }
-            else { Ordering::Equal }
-        });
+        // Ensure it bubbles to the top (it is highest priority under both policies)
+        ready.sort_by(|a, b| job_order(policy, tasks, a, b));
         info!("RM: thermal_control job injected (preemption)");
     };
 
     // Main scheduler loop
-    loop {
+    'sched: loop {
         let nowi = Instant::now();
 
         // 1) Release periodic jobs that are due
@@ -135,13 +213,13 @@ pub async fn spawn_rm(cfg: Config) {
 
         // 2) Inject thermal preemption job if requested
         if rx_preempt.try_recv().is_ok() {
-            spawn_thermal_job(&mut ready, nowi);
+            spawn_thermal_job(&tasks, &mut ready, nowi);
         }
 
         // 3) If no jobs ready, idle until the next release or preempt signal
         if ready.is_empty() {
-            // CPU window emit every 1s even when idle
-            maybe_emit_cpu(&mut win_start, &mut active_ms_acc).await;
+            // CPU window emit even when idle
+            maybe_emit_cpu(&mut win_start, &mut active_ms_acc, cpu_window, Instant::now()).await;
 
             // Sleep until the earliest next release (min next_release over tasks)
             if let Some(sleep_until) = tasks.iter().map(|t| t.next_release).min() {
@@ -149,7 +227,7 @@ pub async fn spawn_rm(cfg: Config) {
                     _ = time::sleep_until(sleep_until) => {},
                     // wake early if thermal preemption arrives
                     _ = rx_preempt.recv() => {
-                        spawn_thermal_job(&mut ready, Instant::now());
+                        spawn_thermal_job(&tasks, &mut ready, Instant::now());
                     }
                 }
             } else {
@@ -162,20 +240,21 @@ pub async fn spawn_rm(cfg: Config) {
         let mut job = ready.remove(0);
         let spec_is_thermal = job.task_idx == usize::MAX;
         let (task_name, deadline_dur) = if spec_is_thermal {
-            ("thermal_control", Duration::from_millis(20))
+            ("thermal_control", Duration::from_millis(THERMAL_DEADLINE_MS))
         } else {
             let t = &tasks[job.task_idx];
             (t.name, t.deadline)
         };
 
-        let actual_start = Instant::now();
+        // a resumed job keeps the start (and the work) of its first dispatch
+        let actual_start = *job.started.get_or_insert_with(Instant::now);
         let expected_start = job.release;
         let start_delay_ms =
             (actual_start.saturating_duration_since(expected_start)).as_secs_f64() * 1e3;
 
         // 5) Run cooperatively in slices; preempt if a higher-priority job arrives
         const SLICE_MS: f64 = 0.5;
-        let mut ran_ms: f64 = 0.0;
+        let mut ran_ms: f64 = job.exec_ms - job.remaining_ms;
 
         // Process the job in larger chunks to reduce overhead
         while job.remaining_ms > 0.0 {
@@ -194,40 +273,32 @@ pub async fn spawn_rm(cfg: Config) {
 
                 // thermal preempt?
                 if rx_preempt.try_recv().is_ok() {
-                    spawn_thermal_job(&mut ready, nowi);
+                    spawn_thermal_job(&tasks, &mut ready, nowi);
                 }
 
-                // RM preemption: if a *higher-priority* job is now ready, preempt current
+                // Preemption: if a *higher-priority* job is now ready, preempt current
                 if let Some(next) = ready.first() {
                     let higher_prio = if next.task_idx == usize::MAX {
                         true // thermal always higher
                     } else if spec_is_thermal {
                         false
                     } else {
-                        let p_cur = tasks[job.task_idx].rm_priority;
-                        let p_nxt = tasks[next.task_idx].rm_priority;
-                        p_nxt < p_cur
+                        match policy {
+                            SchedPolicy::RateMonotonic => {
+                                let p_cur = tasks[job.task_idx].rm_priority;
+                                let p_nxt = tasks[next.task_idx].rm_priority;
+                                p_nxt < p_cur
+                            }
+                            SchedPolicy::Edf => next.deadline < job.deadline,
+                        }
                     };
                     if higher_prio {
                         job.preemptions += 1;
                         // put current job back into the ready queue
                         ready.push(job);
-                        ready.sort_by(|a, b| {
-                            // thermal first, then RM priority
-                            if a.task_idx == usize::MAX && b.task_idx != usize::MAX { Ordering::Less }
-                            else if b.task_idx == usize::MAX && a.task_idx != usize::MAX { Ordering::Greater }
-                            else {
-                                let pa = if a.task_idx == usize::MAX { 0 } else { tasks[a.task_idx].rm_priority };
-                                let pb = if b.task_idx == usize::MAX { 0 } else { tasks[b.task_idx].rm_priority };
-                                match pa.cmp(&pb) {
-                                    Ordering::Equal => a.deadline.cmp(&b.deadline),
-                                    other => other,
-                                }
-                            }
-                        });
-                        // Reschedule
-                        job = ready.remove(0);
-                        continue;
+                        ready.sort_by(|a, b| job_order(policy, &tasks, a, b));
+                        // Reschedule from the top, so the preemptor is logged under its own name
+                        continue 'sched;
                     }
                 }
             }
@@ -248,11 +319,21 @@ pub async fn spawn_rm(cfg: Config) {
             start_delay_ms,
             completion_delay_ms,
             ran_ms,
+            job.exec_ms,
             job.preemptions,
             deadline_dur.as_secs_f64() * 1e3,
+            policy.as_str(),
         ).await;
 
+        // the alignment job is what keeps the antenna on target (see Downlink::align)
+        if task_name == "antenna_alignment"
+            && let Some(dl) = crate::downlink::DL.get()
+        {
+            dl.align().await;
+        }
+
         if completion_delay_ms > 0.0 {
+            super::record_deadline_miss(task_name);
             warn!(
                 task = task_name,
                 seq = job.seq,
@@ -262,19 +343,105 @@ pub async fn spawn_rm(cfg: Config) {
             );
         }
 
-        // 7) Periodic CPU row (once per ~1s window)
-        maybe_emit_cpu(&mut win_start, &mut active_ms_acc).await;
+        // 7) Periodic CPU row (once per `cpu_window_ms`)
+        maybe_emit_cpu(&mut win_start, &mut active_ms_acc, cpu_window, Instant::now()).await;
     }
 }
 
-async fn maybe_emit_cpu(win_start: &mut Instant, active_ms_acc: &mut f64) {
-    let win = win_start.elapsed();
-    if win >= Duration::from_secs(1) {
+/// Close the accounting window once `window` has passed: cpu.csv row plus the
+/// utilization the heartbeat reports.
+async fn maybe_emit_cpu(win_start: &mut Instant, active_ms_acc: &mut f64, window: Duration, now: Instant) {
+    let win = now.duration_since(*win_start);
+    if win >= window {
         let window_ms = win.as_secs_f64() * 1e3;
         let active_ms = *active_ms_acc;
         let idle_ms = (window_ms - active_ms).max(0.0);
         crate::logging::csv::log_cpu(window_ms as u64, active_ms, idle_ms).await;
-        *win_start = Instant::now();
+        super::set_cpu_utilization((active_ms / window_ms * 100.0).min(100.0));
+        *win_start = now;
         *active_ms_acc = 0.0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(task_idx: usize, deadline: Instant) -> Job {
+        Job { task_idx, release: deadline, deadline, seq: 0, exec_ms: 1.0, remaining_ms: 1.0, preemptions: 0, started: None }
+    }
+
+    fn order(policy: SchedPolicy) -> Vec<usize> {
+        let now = Instant::now();
+        let tasks = vec![
+            RtTask::new("antenna_alignment", 50, 3.0, 1, now),
+            RtTask::new("health_monitor", 1000, 2.0, 3, now),
+        ];
+        // the low-RM-priority job is the one closest to its deadline
+        let mut ready = [
+            job(0, now + Duration::from_millis(40)),
+            job(1, now + Duration::from_millis(5)),
+            job(usize::MAX, now + Duration::from_millis(20)),
+        ];
+        ready.sort_by(|a, b| job_order(policy, &tasks, a, b));
+        ready.iter().map(|j| j.task_idx).collect()
+    }
+
+    #[test]
+    fn edf_orders_by_deadline_with_thermal_first() {
+        assert_eq!(order(SchedPolicy::Edf), vec![usize::MAX, 1, 0]);
+        assert_eq!(order(SchedPolicy::RateMonotonic), vec![usize::MAX, 0, 1]);
+    }
+
+    #[test]
+    fn default_task_set_is_feasible() {
+        let now = Instant::now();
+        let tasks = vec![
+            RtTask::new("antenna_alignment", 50, 3.0, 1, now),
+            RtTask::new("data_compression", 100, 6.0, 2, now),
+            RtTask::new("health_monitor", 1000, 2.0, 3, now),
+        ];
+        let s = check_schedulability(&tasks, 100);
+        assert!((rm_utilization(&tasks) - 0.122).abs() < 1e-9);
+        assert!(s.feasible, "{s:?}");
+    }
+
+    #[test]
+    fn overloaded_task_set_exceeds_bound() {
+        let now = Instant::now();
+        let tasks = vec![
+            RtTask::new("antenna_alignment", 50, 30.0, 1, now),
+            RtTask::new("data_compression", 100, 40.0, 2, now),
+        ];
+        let s = check_schedulability(&tasks, 10);
+        assert!(s.utilization > s.bound);
+        assert!(!s.feasible);
+    }
+
+    #[tokio::test]
+    async fn closed_window_reports_scheduler_utilization_in_health() {
+        let t0 = Instant::now();
+        let window = Duration::from_millis(200);
+        let (mut start, mut active) = (t0, 50.0); // 50 ms busy
+
+        // window still open: nothing published or reset
+        maybe_emit_cpu(&mut start, &mut active, window, t0 + Duration::from_millis(100)).await;
+        assert_eq!(active, 50.0);
+
+        maybe_emit_cpu(&mut start, &mut active, window, t0 + window).await;
+        let pct = crate::scheduler::cpu_utilization().unwrap();
+        assert!((pct - 25.0).abs() < 0.01, "{pct}");
+        assert_eq!((start, active), (t0 + window, 0.0));
+
+        // the heartbeat's CPU figure now follows the scheduler
+        use crate::health::heartbeat::MetricsSource;
+        struct Idle;
+        impl MetricsSource for Idle {
+            fn sample(&mut self) -> (f64, f64) {
+                (0.0, 1.0)
+            }
+        }
+        let (cpu, mem) = crate::health::SchedulerCpu(Idle).sample();
+        assert!((cpu - 25.0).abs() < 0.01 && mem == 1.0);
+    }
+}
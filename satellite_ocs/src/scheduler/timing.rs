@@ -1,4 +1,7 @@
-use std::time::Instant;
+use once_cell::sync::Lazy;
+use shared_protocol::SensorReading;
+use std::sync::Mutex;
+use tokio::time::Instant;
 
 pub struct Tick {
     pub start: Instant,
@@ -11,3 +14,172 @@ impl Tick {
         actual - self.period_ns as i128
     }
 }
+
+/// Phase accounting against absolute release times `epoch + (n - base_seq) * period`.
+/// `drift` is how far sample n is from its ideal release (it accumulates if every
+/// cycle runs late); `jitter` is how much that offset moved since the previous sample.
+pub struct PhaseTracker {
+    epoch: Instant,
+    base_seq: u64,
+    period: std::time::Duration,
+    last_drift_ms: Option<f64>,
+    last_start: Option<Instant>,
+}
+
+impl PhaseTracker {
+    /// `epoch` is the ideal release time of sequence 0.
+    pub fn new(epoch: Instant, period: std::time::Duration) -> Self {
+        Self { epoch, base_seq: 0, period, last_drift_ms: None, last_start: None }
+    }
+
+    /// Restart the reference after the period changes: `seq` is ideally released `at`.
+    pub fn rebase(&mut self, seq: u64, at: Instant, period: std::time::Duration) {
+        self.epoch = at;
+        self.base_seq = seq;
+        self.period = period;
+        self.last_drift_ms = None;
+        self.last_start = None;
+    }
+
+    /// Whole periods that went by without a release since the previous cycle started
+    /// (0 on schedule). The interval uses `MissedTickBehavior::Delay`, so a stall shifts
+    /// later ticks instead of bursting; the caller advances `seq` by this much. The first
+    /// cycle after `new`/`rebase` reports 0.
+    pub fn catch_up(&mut self, start: Instant) -> u64 {
+        let elapsed = self.last_start.map_or(std::time::Duration::ZERO, |prev| start.saturating_duration_since(prev));
+        self.last_start = Some(start);
+        let periods = elapsed.as_nanos() / self.period.as_nanos().max(1);
+        periods.saturating_sub(1) as u64
+    }
+
+    /// (jitter_ms, drift_ms) for sample `seq` that started at `start`.
+    pub fn sample(&mut self, seq: u64, start: Instant) -> (f64, f64) {
+        let n = seq.saturating_sub(self.base_seq) as u32;
+        let ideal = self.epoch + self.period * n;
+        let drift_ms = if start >= ideal {
+            start.duration_since(ideal).as_secs_f64() * 1000.0
+        } else {
+            -(ideal.duration_since(start).as_secs_f64() * 1000.0)
+        };
+        let jitter_ms = self.last_drift_ms.map_or(0.0, |prev| (drift_ms - prev).abs());
+        self.last_drift_ms = Some(drift_ms);
+        (jitter_ms, drift_ms)
+    }
+}
+
+/// Simulated on-board clock: runs fast (positive ppm) or slow against ground time, so
+/// its offset grows with elapsed time until a ClockCorrection command steps it back.
+#[derive(Debug, Clone)]
+pub struct ClockModel {
+    drift_ppm: f64,
+    since: Instant,   // drift accumulates from here
+    base_ms: f64,     // offset at `since`
+}
+
+impl ClockModel {
+    pub fn new(drift_ppm: f64, start: Instant) -> Self {
+        Self { drift_ppm, since: start, base_ms: 0.0 }
+    }
+
+    /// Satellite time minus ground time (ms) at `at`.
+    pub fn offset_ms(&self, at: Instant) -> f64 {
+        let elapsed_ms = at.saturating_duration_since(self.since).as_secs_f64() * 1000.0;
+        self.base_ms + elapsed_ms * self.drift_ppm / 1e6
+    }
+
+    /// Step the clock by `by_ms` at `at`; drift keeps accumulating from there.
+    pub fn correct(&mut self, by_ms: f64, at: Instant) {
+        self.base_ms = self.offset_ms(at) + by_ms;
+        self.since = at;
+    }
+}
+
+static CLOCK: Lazy<Mutex<ClockModel>> = Lazy::new(|| Mutex::new(ClockModel::new(0.0, Instant::now())));
+
+fn clock() -> std::sync::MutexGuard<'static, ClockModel> {
+    CLOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start the on-board clock drifting at `drift_ppm` (call once from main).
+pub fn init_clock(drift_ppm: f64) {
+    *clock() = ClockModel::new(drift_ppm, Instant::now());
+}
+
+/// Apply a ClockCorrection command; returns the offset left right after the step.
+pub fn correct_clock(by_ms: f64) -> Result<f64, String> {
+    if !by_ms.is_finite() {
+        return Err(format!("clock correction not finite: {by_ms}"));
+    }
+    let now = Instant::now();
+    let mut c = clock();
+    c.correct(by_ms, now);
+    Ok(c.offset_ms(now))
+}
+
+/// Stamp a reading sampled at `at` with the on-board clock: its timestamp and
+/// `drift_ms` carry the current clock offset.
+pub fn stamp(r: &mut SensorReading, at: Instant) {
+    let offset_ms = clock().offset_ms(at);
+    if offset_ms != 0.0 {
+        r.timestamp += chrono::Duration::microseconds((offset_ms * 1000.0) as i64);
+        r.drift_ms += offset_ms;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn drift_accumulates_under_constant_delay_while_jitter_stays_bounded() {
+        let epoch = Instant::now();
+        let period = Duration::from_millis(50);
+        let mut phase = PhaseTracker::new(epoch, period);
+        let late = Duration::from_millis(2); // every cycle runs 2 ms long
+
+        let mut last = (0.0, 0.0);
+        for n in 0..100u32 {
+            last = phase.sample(n as u64, epoch + (period + late) * n);
+            assert!(last.0 <= 2.0 + 1e-6, "jitter {} at n={n}", last.0);
+        }
+        assert!((last.1 - 198.0).abs() < 1e-6, "drift {}", last.1);
+
+        // after a period change the reference restarts at the given release
+        phase.rebase(100, epoch + Duration::from_secs(10), Duration::from_millis(25));
+        assert_eq!(phase.sample(100, epoch + Duration::from_secs(10)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn clock_drift_accumulates_until_corrected() {
+        let t0 = Instant::now();
+        let mut clock = ClockModel::new(50.0, t0); // 50 ppm fast
+        assert_eq!(clock.offset_ms(t0), 0.0);
+        let hour = t0 + Duration::from_secs(3600);
+        assert!((clock.offset_ms(hour) - 180.0).abs() < 1e-6);
+
+        // ground measured +180 ms and sends the negation
+        clock.correct(-clock.offset_ms(hour), hour);
+        assert!(clock.offset_ms(hour).abs() < 1e-9);
+        assert!((clock.offset_ms(hour + Duration::from_secs(60)) - 3.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn clock_correction_command_steps_the_shared_clock() {
+        use crate::commands::executor;
+        use tokio::sync::mpsc;
+
+        let (tx, mut rx) = mpsc::channel(4);
+        executor::execute(shared_protocol::Command::clock_correction(-25.0), tx).await;
+        let mut last = None;
+        while let Ok(ack) = rx.try_recv() {
+            last = Some(ack.status);
+        }
+        assert_eq!(last.as_deref(), Some("completed"));
+        let mut r = shared_protocol::ThermalSensor::new(1, "CPU").create_reading(60.0, 0);
+        stamp(&mut r, Instant::now());
+        assert!(r.drift_ms < -24.0, "{}", r.drift_ms);
+        let left_ms = clock().offset_ms(Instant::now());
+        correct_clock(-left_ms).unwrap(); // leave it at 0 for other tests
+    }
+}
@@ -1,58 +1,97 @@
 use shared_protocol::{AttitudeSensor, SensorReading};
+use tokio::task::JoinHandle;
 use tokio::time::{self, Duration, Instant};
 use tracing::{info, warn};
 
 // fault bus
-use crate::faults::{self, FaultEvent};
+use crate::faults::FaultEvent;
+use crate::health::watchdog;
+use crate::logging::blackbox::{self, Event, EventKind};
+use crate::scheduler::timing::{self, PhaseTracker};
+use super::jitter::{self, Jitter};
+use super::{rate, thresholds, FaultChange, FaultState, SensorDef};
 
-pub fn spawn() {
-    let sensor = AttitudeSensor::new(3, "IMU");
+pub fn spawn(def: &SensorDef, mut jitter: Option<Jitter>) -> Result<JoinHandle<()>, String> {
+    let mut sensor = AttitudeSensor::new(def.id, &def.location);
+    if let Some(ms) = def.interval_ms {
+        sensor.sampling_interval_ms = ms;
+    }
+    def.configure(&mut sensor)?;
+    thresholds::register(&sensor);
 
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         let mut seq = 0u64;
-        let period = Duration::from_millis(sensor.sampling_interval_ms);
+        let mut period = Duration::from_millis(sensor.sampling_interval_ms);
+        let dog = watchdog::register(format!("attitude#{}", sensor.sensor_id), period);
         let mut ticker = time::interval(period);
         ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
         // prime
         ticker.tick().await;
         let mut last_start = Instant::now();
+        let mut phase = PhaseTracker::new(last_start + period, period);
 
         // fault state
-        let mut faults_rx = faults::subscribe();
-        let mut cur_fault_id: Option<String> = None;
+        let mut fault = FaultState::new();
         let mut pause_until: Option<Instant> = None;
+        let mut shutdown_rx = crate::shutdown::subscribe();
+        let mut rate_rx = rate::subscribe();
 
         loop {
+            if crate::shutdown::requested(&mut shutdown_rx) {
+                info!("attitude: stopping (shutdown)");
+                break;
+            }
+
             // drain fault events
-            if let Some(rx) = faults_rx.as_mut() {
-                loop {
-                    match rx.try_recv() {
-                        Ok(FaultEvent::AttitudePause { fault_id, for_ms }) => {
-                            cur_fault_id = Some(fault_id);
-                            pause_until = Some(Instant::now() + Duration::from_millis(for_ms));
-                            warn!(for_ms, "attitude: injected pause fault");
-                        }
-                        Ok(FaultEvent::Recover { fault_id }) => {
-                            if cur_fault_id.as_deref() == Some(fault_id.as_str()) {
-                                pause_until = None;
-                                faults::ack_recovered(&fault_id, "attitude").await;
-                                info!("attitude: recovered");
-                                cur_fault_id = None;
-                            }
+            while let Some(ev) = fault.next_event("attitude") {
+                match ev {
+                    FaultEvent::AttitudePause { fault_id, for_ms } => {
+                        fault.carry(fault_id);
+                        pause_until = Some(Instant::now() + Duration::from_millis(for_ms));
+                        warn!(for_ms, "attitude: injected pause fault");
+                    }
+                    ev => match fault.on_event("attitude", ev).await {
+                        Some(FaultChange::Recovered) => {
+                            pause_until = None;
                         }
-                        Ok(FaultEvent::Abort { reason }) => {
-                            warn!(%reason, "attitude: mission abort received");
+                        Some(FaultChange::SafeMode) => {
+                            rate::enter_safe_mode(&mut ticker, &mut period, "attitude");
+                            phase.rebase(seq, Instant::now(), period);
                         }
-                        Ok(_) => {}
-                        Err(tokio::sync::broadcast::error::TryRecvError::Empty) => break,
-                        Err(_) => break,
-                    }
+                        None => {}
+                    },
                 }
             }
 
+            // sampling period set by ground command (param2)
+            if let Some(new) = rate::take(&mut rate_rx, "attitude")
+                && rate::retune(&mut ticker, &mut period, new, fault.safe_mode(), "attitude")
+            {
+                phase.rebase(seq, Instant::now(), period);
+            }
+
             ticker.tick().await;
+            // simulated scheduling jitter (--sensor-jitter-ms)
+            jitter::delay(&mut jitter).await;
             let start = Instant::now();
+            // whole periods lost to a stall: seq follows ideal releases, not executions
+            let skipped = phase.catch_up(start);
+            if skipped > 0 {
+                warn!(skipped, "attitude: missed releases");
+                blackbox::record(Event::new(EventKind::SensorMiss, "attitude", format!("{skipped} releases skipped at seq {seq}")));
+                seq = seq.wrapping_add(skipped);
+            }
+            dog.pet(period);
+            let mut fault_status: Option<&str> = None;
+
+            // dropout fault: produce nothing this cycle
+            if fault.should_skip(start) {
+                super::log_dropout("attitude", sensor.sensor_id, seq, phase.sample(seq, start), skipped).await;
+                last_start = start;
+                seq = seq.wrapping_add(1);
+                continue;
+            }
 
             // if paused by a fault, skip producing/sending this cycle
             if let Some(until) = pause_until {
@@ -68,6 +107,7 @@ pub fn spawn() {
                         actual_ms = format_args!("{:.3}", actual_ms),
                         ideal_ms = format_args!("{:.3}", ideal_ms),
                     );
+                    super::log_skipped("attitude", sensor.sensor_id, seq, phase.sample(seq, start), "paused", skipped).await;
                     last_start = start;
                     seq = seq.wrapping_add(1);
                     continue;
@@ -79,18 +119,21 @@ pub fn spawn() {
             let pitch = ((seq as f64 * 0.07) % 6.0) - 3.0;
             let yaw = ((seq as f64 * 0.05) % 6.0) - 3.0;
 
+            // thresholds may have been changed by ground (SetThreshold)
+            thresholds::refresh(&mut sensor);
             let mut r: SensorReading = sensor.create_reading(roll, pitch, yaw, seq);
 
+            // clock skew fault: shift the sample timestamp
+            if fault.skew(start, &mut r.timestamp) {
+                fault_status = Some("fault_clock_skew");
+            }
+
             // timing
             let actual_ms = start.duration_since(last_start).as_secs_f64() * 1000.0;
             let ideal_ms = period.as_secs_f64() * 1000.0;
-            if seq == 0 {
-                r.jitter_ms = 0.0;
-                r.drift_ms = 0.0;
-            } else {
-                r.jitter_ms = (actual_ms - ideal_ms).abs();
-                r.drift_ms = actual_ms - ideal_ms;
-            }
+            // jitter/drift against the absolute release schedule, not the previous sample
+            (r.jitter_ms, r.drift_ms) = phase.sample(seq, start);
+            timing::stamp(&mut r, start);
             r.processing_latency_ms = 0.0;
 
             info!(
@@ -105,22 +148,16 @@ pub fn spawn() {
                 jitter_ms = format_args!("{:.3}", r.jitter_ms),
                 drift_ms = format_args!("{:.3}", r.drift_ms),
             );
+            super::log_reading("attitude", &r, fault_status, skipped).await;
 
-            let tx = match crate::telemetry::CHANNEL.get() {
-                Some(tx) => tx.clone(),
-                None => {
-                    warn!("telemetry channel not ready");
-                    seq = seq.wrapping_add(1);
-                    last_start = start;
-                    continue;
-                }
-            };
-            if let Err(e) = tx.send(r).await {
-                warn!(?e, "attitude: failed to enqueue reading");
+            // never await here: a full channel costs this reading, not the next period
+            if let Err(e) = crate::telemetry::try_enqueue(r) {
+                warn!(%e, "attitude: reading not enqueued");
             }
 
             last_start = start;
             seq = seq.wrapping_add(1);
         }
     });
+    Ok(task)
 }
@@ -0,0 +1,62 @@
+//! Simulated scheduling jitter (`--sensor-jitter-ms`): a bounded random sleep before each
+//! sample, so `jitter_ms` and the miss detection see something other than Tokio's clean
+//! timer. Unrelated to the fault bus; seeded from `--rng-seed` and the sensor id.
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use tokio::time::{self, Duration};
+
+use crate::config::Config;
+
+pub struct Jitter {
+    max_us: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Jitter {
+    /// `None` when `max_ms` is 0 (injection off).
+    pub fn new(max_ms: f64, seed: Option<u64>, sensor_id: u32) -> Option<Self> {
+        let max_us = (max_ms.max(0.0) * 1000.0) as u64;
+        if max_us == 0 {
+            return None;
+        }
+        let rng = match seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed ^ u64::from(sensor_id).rotate_left(32)),
+            None => ChaCha8Rng::from_rng(&mut rand::rng()),
+        };
+        Some(Self { max_us, rng })
+    }
+
+    /// Uniform in `0..=max`.
+    pub fn next_delay(&mut self) -> Duration {
+        Duration::from_micros(self.rng.random_range(0..=self.max_us))
+    }
+}
+
+pub fn from_config(cfg: &Config, sensor_id: u32) -> Option<Jitter> {
+    Jitter::new(cfg.sensor_jitter_ms, cfg.rng_seed, sensor_id)
+}
+
+/// Sleep before sampling, if injection is on.
+pub async fn delay(jitter: &mut Option<Jitter>) {
+    if let Some(j) = jitter {
+        time::sleep(j.next_delay()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_are_bounded_and_repeat_per_seed() {
+        let draw = |seed, id| {
+            let mut j = Jitter::new(2.0, Some(seed), id).unwrap();
+            (0..50).map(|_| j.next_delay()).collect::<Vec<_>>()
+        };
+        let a = draw(7, 1);
+        assert!(a.iter().all(|d| *d <= Duration::from_millis(2)));
+        assert_eq!(a, draw(7, 1));
+        assert_ne!(a, draw(7, 2)); // each sensor gets its own stream
+        assert!(Jitter::new(0.0, Some(7), 1).is_none());
+    }
+}
@@ -1,11 +1,488 @@
 pub mod thermal;
 pub mod power;
 pub mod attitude;
+pub mod radiation;
+pub mod jitter;
+pub mod profile;
+pub mod rate;
+pub mod thresholds;
+
+use shared_protocol::{SensorReading, Timestamp};
+use std::collections::HashSet;
+use std::str::FromStr;
+use tokio::sync::broadcast;
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
 
 use crate::config::Config;
+use crate::faults::{self, FaultEvent};
+use crate::health::watchdog;
+use crate::logging::{self, blackbox::{self, Event, EventKind}};
+use crate::scheduler::timing::{self, PhaseTracker};
+use jitter::Jitter;
+
+/// The stock suite: one sensor of each type.
+pub const DEFAULT_SENSORS: &str = "thermal:1:CPU,power:2:Main Bus,attitude:3:IMU,radiation:7:Payload Bay";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Thermal,
+    Power,
+    Attitude,
+    Radiation,
+}
+
+/// One sensor task from `--sensors`: "kind:id:location[:interval_ms[:t0:t1]]".
+/// `t0:t1` are the two thresholds in the sensor's own order (see `thresholds::Adjustable`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorDef {
+    pub kind: SensorKind,
+    pub id: u32,
+    pub location: String,
+    pub interval_ms: Option<u64>,
+    pub thresholds: Option<[f64; 2]>,
+}
+
+impl FromStr for SensorDef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = s.trim().split(':').map(str::trim).collect();
+        if !matches!(parts.len(), 3 | 4 | 6) {
+            return Err(format!("sensor spec '{s}': expected kind:id:location[:interval_ms[:t0:t1]]"));
+        }
+        let kind = match parts[0] {
+            "thermal" => SensorKind::Thermal,
+            "power" => SensorKind::Power,
+            "attitude" => SensorKind::Attitude,
+            "radiation" => SensorKind::Radiation,
+            other => return Err(format!("sensor spec '{s}': unknown kind '{other}'")),
+        };
+        let id = parts[1].parse().map_err(|e| format!("sensor spec '{s}': bad id: {e}"))?;
+        let interval_ms = match parts.get(3) {
+            Some(ms) => {
+                let ms: u64 = ms.parse().map_err(|e| format!("sensor spec '{s}': bad interval: {e}"))?;
+                if !(rate::MIN_PERIOD_MS..=rate::MAX_PERIOD_MS).contains(&(ms as f64)) {
+                    return Err(format!("sensor spec '{s}': interval {ms} ms out of range"));
+                }
+                Some(ms)
+            }
+            None => None,
+        };
+        let thresholds = match parts.get(4..6) {
+            Some([a, b]) => {
+                let parse = |v: &str| v.parse::<f64>().map_err(|e| format!("sensor spec '{s}': bad threshold: {e}"));
+                Some([parse(a)?, parse(b)?])
+            }
+            _ => None,
+        };
+        Ok(Self { kind, id, location: parts[2].to_string(), interval_ms, thresholds })
+    }
+}
+
+impl SensorDef {
+    /// Apply the spec's thresholds (if any), checked against the sensor's ordering.
+    fn configure(&self, sensor: &mut impl thresholds::Adjustable) -> Result<(), String> {
+        if let Some([a, b]) = self.thresholds {
+            let ordered = if sensor.rising() { a < b } else { a > b };
+            if !ordered {
+                let [(n0, _), (n1, _)] = sensor.thresholds();
+                return Err(format!("sensor {}: {n0} ({a}) and {n1} ({b}) are out of order", self.id));
+            }
+            sensor.set_thresholds([a, b]);
+        }
+        Ok(())
+    }
+}
+
+/// One task per `cfg.sensors` entry; ids must be unique (thresholds are keyed by id).
+pub async fn spawn_all(cfg: Config) -> anyhow::Result<Vec<tokio::task::JoinHandle<()>>> {
+    let mut ids = HashSet::new();
+    let mut tasks = Vec::with_capacity(cfg.sensors.len());
+    for def in &cfg.sensors {
+        if !ids.insert(def.id) {
+            anyhow::bail!("duplicate sensor id {}", def.id);
+        }
+        let jitter = jitter::from_config(&cfg, def.id);
+        let task = match def.kind {
+            SensorKind::Thermal => thermal::spawn(&cfg, def, jitter),
+            SensorKind::Power => power::spawn(def, jitter),
+            SensorKind::Attitude => attitude::spawn(def, jitter),
+            SensorKind::Radiation => radiation::spawn(def, jitter),
+        }
+        .map_err(anyhow::Error::msg)?;
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/// Fault state every sensor loop shares: the fault it is carrying, the dropout and
+/// clock skew faults any sensor can be hit with, and safe mode. A sensor's own faults
+/// (thermal delay, power corruption, attitude pause) stay in its loop and only `carry`
+/// their id here.
+struct FaultState {
+    rx: Option<broadcast::Receiver<FaultEvent>>,
+    cur_fault_id: Option<String>,
+    dropout_until: Option<Instant>,
+    skew: Option<(i64, Instant)>, // (shift_ms, until)
+    safe_mode: bool,
+}
+
+/// What the sensor loop itself has to act on after `FaultState::on_event`.
+#[derive(Debug, PartialEq)]
+enum FaultChange {
+    /// The carried fault was cleared (and ACKed): reset the sensor's own fault state.
+    Recovered,
+    /// First `Abort`: stretch the period (`rate::enter_safe_mode`).
+    SafeMode,
+}
+
+impl FaultState {
+    /// Subscribes to the fault bus; stays quiet if the injector isn't running.
+    fn new() -> Self {
+        Self { rx: faults::subscribe(), cur_fault_id: None, dropout_until: None, skew: None, safe_mode: false }
+    }
+
+    /// Non-blocking drain step: the next fault event, or None once the bus is empty.
+    fn next_event(&mut self, name: &str) -> Option<FaultEvent> {
+        faults::next_event(self.rx.as_mut()?, name, self.cur_fault_id.as_deref())
+    }
+
+    /// A sensor-specific fault is now active; its `Recover` is matched by `fault_id`.
+    fn carry(&mut self, fault_id: String) {
+        self.cur_fault_id = Some(fault_id);
+    }
+
+    /// Apply a dropout or clock skew aimed at `name`, the `Recover` for the carried fault,
+    /// or an `Abort`; anything else is ignored.
+    async fn on_event(&mut self, name: &str, ev: FaultEvent) -> Option<FaultChange> {
+        match ev {
+            FaultEvent::SensorDropout { fault_id, target, for_ms } if target == name => {
+                self.carry(fault_id);
+                self.dropout_until = Some(Instant::now() + Duration::from_millis(for_ms));
+                warn!(for_ms, "{name}: injected dropout fault");
+            }
+            FaultEvent::ClockSkew { fault_id, target, skew_ms, for_ms } if target == name => {
+                self.carry(fault_id);
+                self.skew = Some((skew_ms, Instant::now() + Duration::from_millis(for_ms)));
+                warn!(skew_ms, for_ms, "{name}: injected clock skew fault");
+            }
+            FaultEvent::Recover { fault_id } if self.cur_fault_id.as_deref() == Some(fault_id.as_str()) => {
+                self.dropout_until = None;
+                self.skew = None;
+                faults::ack_recovered(&fault_id, name).await;
+                info!("{name}: recovered");
+                self.cur_fault_id = None;
+                return Some(FaultChange::Recovered);
+            }
+            FaultEvent::Abort { reason } => {
+                warn!(%reason, "{name}: mission abort received");
+                if !self.safe_mode {
+                    self.safe_mode = true;
+                    return Some(FaultChange::SafeMode);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// A mission abort has put the sensor in safe mode (for good).
+    fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// A dropout is active: the cycle released at `now` produces nothing.
+    fn should_skip(&self, now: Instant) -> bool {
+        self.dropout_until.is_some_and(|until| now < until)
+    }
+
+    /// Shift a sample timestamp by the clock skew active at `now`; returns whether it did.
+    fn skew(&self, now: Instant, ts: &mut Timestamp) -> bool {
+        match self.skew {
+            Some((skew_ms, until)) if now < until => {
+                *ts += chrono::Duration::milliseconds(skew_ms);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The periodic loop of a sensor with no faults or rate commands of its own: release
+/// timing, watchdog and shutdown, the shared faults and safe mode, then sensors.csv and
+/// telemetry. `sample(seq, elapsed)` makes the reading for release `seq`, `elapsed` after
+/// the previous one; it also runs on dropout cycles (the reading is dropped), so state it
+/// accumulates keeps up.
+async fn run_loop(
+    name: &'static str,
+    sensor_id: u32,
+    mut period: Duration,
+    mut jitter: Option<Jitter>,
+    mut sample: impl FnMut(u64, Duration) -> SensorReading,
+) {
+    let mut seq = 0u64;
+    let dog = watchdog::register(format!("{name}#{sensor_id}"), period);
+    let mut ticker = time::interval(period);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+    // prime
+    ticker.tick().await;
+    let mut last_start = Instant::now();
+    let mut phase = PhaseTracker::new(last_start + period, period);
+    let mut fault = FaultState::new();
+    let mut shutdown_rx = crate::shutdown::subscribe();
+
+    loop {
+        if crate::shutdown::requested(&mut shutdown_rx) {
+            info!("{name}: stopping (shutdown)");
+            break;
+        }
+
+        // drain fault events
+        while let Some(ev) = fault.next_event(name) {
+            if fault.on_event(name, ev).await == Some(FaultChange::SafeMode) {
+                rate::enter_safe_mode(&mut ticker, &mut period, name);
+                phase.rebase(seq, Instant::now(), period);
+            }
+        }
+
+        ticker.tick().await;
+        // simulated scheduling jitter (--sensor-jitter-ms)
+        jitter::delay(&mut jitter).await;
+        let start = Instant::now();
+        // whole periods lost to a stall: seq follows ideal releases, not executions
+        let skipped = phase.catch_up(start);
+        if skipped > 0 {
+            warn!(skipped, "{name}: missed releases");
+            blackbox::record(Event::new(EventKind::SensorMiss, name, format!("{skipped} releases skipped at seq {seq}")));
+            seq = seq.wrapping_add(skipped);
+        }
+        dog.pet(period);
+
+        let mut r = sample(seq, start.duration_since(last_start));
+
+        // dropout fault: produce nothing this cycle
+        if fault.should_skip(start) {
+            log_dropout(name, sensor_id, seq, phase.sample(seq, start), skipped).await;
+            last_start = start;
+            seq = seq.wrapping_add(1);
+            continue;
+        }
+
+        // clock skew fault: shift the sample timestamp
+        let fault_status = fault.skew(start, &mut r.timestamp).then_some("fault_clock_skew");
+
+        // timing
+        let actual_ms = start.duration_since(last_start).as_secs_f64() * 1000.0;
+        let ideal_ms = period.as_secs_f64() * 1000.0;
+        // jitter/drift against the absolute release schedule, not the previous sample
+        (r.jitter_ms, r.drift_ms) = phase.sample(seq, start);
+        timing::stamp(&mut r, start);
+        r.processing_latency_ms = 0.0;
+
+        info!(
+            event = "sensor_sample",
+            kind = name,
+            seq = seq,
+            value1 = format_args!("{:.4}", r.value1),
+            value2 = format_args!("{:.4}", r.value2),
+            actual_ms = format_args!("{:.3}", actual_ms),
+            ideal_ms = format_args!("{:.3}", ideal_ms),
+            jitter_ms = format_args!("{:.3}", r.jitter_ms),
+            drift_ms = format_args!("{:.3}", r.drift_ms),
+        );
+        log_reading(name, &r, fault_status, skipped).await;
+
+        // never await here: a full channel costs this reading, not the next period
+        if let Err(e) = crate::telemetry::try_enqueue(r) {
+            warn!(%e, "{name}: reading not enqueued");
+        }
+
+        last_start = start;
+        seq = seq.wrapping_add(1);
+    }
+}
+
+/// Log a cycle lost to a dropout fault (sensors.csv status "dropout").
+async fn log_dropout(sensor: &str, sensor_id: u32, seq: u64, timing: (f64, f64), skipped: u64) {
+    info!(event = "sensor_sample", kind = sensor, seq = seq, dropout = true);
+    log_skipped(sensor, sensor_id, seq, timing, "dropout", skipped).await;
+}
+
+/// sensors.csv row for a produced reading; `fault` replaces its status while a fault is active.
+/// `skipped` is the number of ideal releases missed just before this one.
+async fn log_reading(sensor: &str, r: &SensorReading, fault: Option<&str>, skipped: u64) {
+    let status = match fault {
+        Some(f) => f.to_string(),
+        None => format!("{:?}", r.status).to_lowercase(),
+    };
+    logging::csv::log_sensor_reading(
+        sensor,
+        r.sensor_id,
+        r.sequence_number,
+        r.jitter_ms,
+        r.drift_ms,
+        r.processing_latency_ms,
+        &format!("{:?}", r.priority).to_lowercase(),
+        &status,
+        skipped,
+    )
+    .await;
+}
+
+/// sensors.csv row for a cycle that produced no reading (dropout, pause); priority is left empty.
+async fn log_skipped(
+    sensor: &str,
+    sensor_id: u32,
+    seq: u64,
+    (jitter_ms, drift_ms): (f64, f64),
+    status: &str,
+    skipped: u64,
+) {
+    logging::csv::log_sensor_reading(sensor, sensor_id, seq, jitter_ms, drift_ms, 0.0, "", status, skipped).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jitter::Jitter, power, thermal, FaultChange, FaultState, SensorDef, SensorKind};
+    use crate::config::Config;
+    use crate::faults::FaultEvent;
+    use crate::scheduler::timing::PhaseTracker;
+    use tokio::time::{sleep, Duration, Instant};
+
+    #[tokio::test]
+    async fn running_sensor_writes_rows_to_sensors_csv() {
+        power::spawn(&"power:2:Main Bus".parse().unwrap(), None).unwrap();
+        sleep(Duration::from_millis(450)).await;
+
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("sensors.csv")).unwrap();
+        assert!(text.starts_with("ts,sensor,seq,"));
+        let rows = text
+            .lines()
+            .filter(|l| l.split(',').nth(1) == Some("power"))
+            .count();
+        assert!(rows >= 2, "only {rows} power rows");
+    }
+
+    #[test]
+    fn sensor_spec_parses_optional_interval_and_thresholds() {
+        let def: SensorDef = "thermal:4:Battery Bay:100:60:80".parse().unwrap();
+        assert_eq!(def.kind, SensorKind::Thermal);
+        assert_eq!((def.id, def.location.as_str()), (4, "Battery Bay"));
+        assert_eq!((def.interval_ms, def.thresholds), (Some(100), Some([60.0, 80.0])));
+        assert!("thermal:4".parse::<SensorDef>().is_err());
+        assert!("sonar:4:Hull".parse::<SensorDef>().is_err());
+        assert_eq!(Config::test_default().sensors.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn two_thermal_sensors_both_produce_readings() {
+        let cfg = Config::test_default();
+        for spec in ["thermal:811:Radiator A", "thermal:812:Radiator B"] {
+            thermal::spawn(&cfg, &spec.parse().unwrap(), None).unwrap();
+        }
+        sleep(Duration::from_millis(400)).await;
+
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("sensors.csv")).unwrap();
+        let header: Vec<&str> = text.lines().next().unwrap().split(',').collect();
+        let id_col = header.iter().position(|h| *h == "sensor_id").unwrap();
+        for id in ["811", "812"] {
+            let rows = text
+                .lines()
+                .filter(|l| l.split(',').nth(1) == Some("thermal") && l.split(',').nth(id_col) == Some(id))
+                .count();
+            assert!(rows >= 2, "only {rows} rows from thermal sensor {id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn injected_jitter_shows_up_in_recorded_jitter_ms() {
+        let jitter = Jitter::new(8.0, Some(3), 821);
+        power::spawn(&"power:821:Jitter Bus:20".parse().unwrap(), jitter).unwrap();
+        sleep(Duration::from_millis(500)).await;
+        crate::logging::csv::flush_all().await;
+
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("sensors.csv")).unwrap();
+        let header: Vec<&str> = text.lines().next().unwrap().split(',').collect();
+        let col = |name: &str| header.iter().position(|h| *h == name).unwrap();
+        let (id_col, jitter_col) = (col("sensor_id"), col("jitter_ms"));
+        let samples: Vec<f64> = text
+            .lines()
+            .map(|l| l.split(',').collect::<Vec<_>>())
+            .filter(|c| c.get(id_col) == Some(&"821"))
+            .map(|c| c[jitter_col].parse().unwrap())
+            .collect();
+        assert!(samples.len() >= 10, "only {} rows", samples.len());
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let var = samples.iter().map(|j| (j - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        // uniform 0..=8 ms has variance ~5.3 ms²; Tokio's own timer noise is far below 1
+        assert!(var > 1.0, "variance {var:.3} ms² from {samples:?}");
+    }
+
+    #[tokio::test]
+    async fn fault_state_holds_dropout_and_skew_until_their_recover() {
+        let mut fault = FaultState::new();
+        let dropout = |target: &str| FaultEvent::SensorDropout { fault_id: "f1".into(), target: target.into(), for_ms: 1000 };
+        // aimed at another sensor
+        assert_eq!(fault.on_event("thermal", dropout("power")).await, None);
+        assert!(!fault.should_skip(Instant::now()));
+
+        fault.on_event("thermal", dropout("thermal")).await;
+        assert!(fault.should_skip(Instant::now()));
+        let skew = FaultEvent::ClockSkew { fault_id: "f2".into(), target: "thermal".into(), skew_ms: 250, for_ms: 1000 };
+        fault.on_event("thermal", skew).await;
+        let mut ts = chrono::Utc::now();
+        let before = ts;
+        assert!(fault.skew(Instant::now(), &mut ts));
+        assert_eq!(ts - before, chrono::Duration::milliseconds(250));
+
+        // only the Recover for the carried fault (the latest one) clears it
+        assert_eq!(fault.on_event("thermal", FaultEvent::Recover { fault_id: "f1".into() }).await, None);
+        assert!(fault.should_skip(Instant::now()));
+        let recover = FaultEvent::Recover { fault_id: "f2".into() };
+        assert_eq!(fault.on_event("thermal", recover).await, Some(FaultChange::Recovered));
+        assert!(!fault.should_skip(Instant::now()));
+        assert!(!fault.skew(Instant::now(), &mut ts));
+
+        // safe mode is entered once
+        let abort = || FaultEvent::Abort { reason: "test".into() };
+        assert_eq!(fault.on_event("thermal", abort()).await, Some(FaultChange::SafeMode));
+        assert_eq!(fault.on_event("thermal", abort()).await, None);
+        assert!(fault.safe_mode());
+    }
+
+    #[tokio::test]
+    async fn long_delay_advances_seq_and_logs_skipped_cycles() {
+        let period = Duration::from_millis(10);
+        let t0 = Instant::now();
+        let mut phase = PhaseTracker::new(t0, period);
+        let mut seq = 0u64;
+        assert_eq!(phase.catch_up(t0), 0);
+
+        // seq 1 on time, then a 45 ms stall: releases 2..=4 never ran
+        seq += 1 + phase.catch_up(t0 + period);
+        assert_eq!(seq, 1);
+        let start = t0 + period + Duration::from_millis(45);
+        let skipped = phase.catch_up(start);
+        assert_eq!(skipped, 3);
+        seq += 1 + skipped;
+        assert_eq!(seq, 5);
+
+        // drift is measured against seq 5's ideal release, not seq 2's
+        let (_, drift) = phase.sample(seq, start);
+        assert!((drift - 5.0).abs() < 1e-6, "drift {drift}");
 
-pub async fn spawn_all(_cfg: Config) {
-    thermal::spawn();
-    power::spawn();
-    attitude::spawn();
+        super::log_skipped("skip_test", 0, seq, (0.0, drift), "dropout", skipped).await;
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("sensors.csv")).unwrap();
+        let row = text
+            .lines()
+            .find(|l| l.split(',').nth(1) == Some("skip_test"))
+            .expect("skip_test row");
+        let cols: Vec<&str> = row.split(',').collect();
+        let header: Vec<&str> = text.lines().next().unwrap().split(',').collect();
+        let col = |name: &str| cols[header.iter().position(|h| *h == name).unwrap()];
+        assert_eq!(col("seq"), "5");
+        assert_eq!(col("skipped_cycles"), "3");
+    }
 }
@@ -1,58 +1,105 @@
 use shared_protocol::{PowerSensor, SensorReading};
+use tokio::task::JoinHandle;
 use tokio::time::{self, Duration, Instant};
 use tracing::{info, warn};
 
 // fault bus
-use crate::faults::{self, FaultEvent};
+use crate::faults::{CorruptMode, FaultEvent};
+use crate::health::watchdog;
+use crate::logging::blackbox::{self, Event, EventKind};
+use crate::scheduler::timing::{self, PhaseTracker};
+use super::jitter::{self, Jitter};
+use super::{rate, thresholds, FaultChange, FaultState, SensorDef};
 
-pub fn spawn() {
-    let sensor = PowerSensor::new(2, "Main Bus");
+/// Garble (battery %, V, A) according to the fault mode.
+fn corrupt(mode: CorruptMode, batt_pct: f64, voltage: f64, _current: f64) -> (f64, f64, f64) {
+    match mode {
+        CorruptMode::Full => (-5.0, 0.0, -10.0), // invalid → Quality::Invalid expected
+        CorruptMode::PartialCurrent => (batt_pct, voltage, 250.0), // current spike only
+    }
+}
+
+pub fn spawn(def: &SensorDef, mut jitter: Option<Jitter>) -> Result<JoinHandle<()>, String> {
+    let mut sensor = PowerSensor::new(def.id, &def.location);
+    if let Some(ms) = def.interval_ms {
+        sensor.sampling_interval_ms = ms;
+    }
+    def.configure(&mut sensor)?;
+    thresholds::register(&sensor);
 
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         let mut seq = 0u64;
-        let period = Duration::from_millis(sensor.sampling_interval_ms);
+        let mut period = Duration::from_millis(sensor.sampling_interval_ms);
+        let dog = watchdog::register(format!("power#{}", sensor.sensor_id), period);
         let mut ticker = time::interval(period);
         ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
         // prime
         ticker.tick().await;
         let mut last_start = Instant::now();
+        let mut phase = PhaseTracker::new(last_start + period, period);
 
         // fault state
-        let mut faults_rx = faults::subscribe();
-        let mut cur_fault_id: Option<String> = None;
-        let mut corrupt_until: Option<Instant> = None;
+        let mut fault = FaultState::new();
+        let mut corrupt_until: Option<(CorruptMode, Instant)> = None;
+        let mut shutdown_rx = crate::shutdown::subscribe();
+        let mut rate_rx = rate::subscribe();
 
         loop {
+            if crate::shutdown::requested(&mut shutdown_rx) {
+                info!("power: stopping (shutdown)");
+                break;
+            }
+
             // drain fault events
-            if let Some(rx) = faults_rx.as_mut() {
-                loop {
-                    match rx.try_recv() {
-                        Ok(FaultEvent::PowerCorrupt { fault_id, for_ms }) => {
-                            cur_fault_id = Some(fault_id);
-                            corrupt_until = Some(Instant::now() + Duration::from_millis(for_ms));
-                            warn!(for_ms, "power: injected corrupt fault");
-                        }
-                        Ok(FaultEvent::Recover { fault_id }) => {
-                            if cur_fault_id.as_deref() == Some(fault_id.as_str()) {
-                                corrupt_until = None;
-                                faults::ack_recovered(&fault_id, "power").await;
-                                info!("power: recovered");
-                                cur_fault_id = None;
-                            }
+            while let Some(ev) = fault.next_event("power") {
+                match ev {
+                    FaultEvent::PowerCorrupt { fault_id, for_ms, mode } => {
+                        fault.carry(fault_id);
+                        corrupt_until = Some((mode, Instant::now() + Duration::from_millis(for_ms)));
+                        warn!(for_ms, ?mode, "power: injected corrupt fault");
+                    }
+                    ev => match fault.on_event("power", ev).await {
+                        Some(FaultChange::Recovered) => {
+                            corrupt_until = None;
                         }
-                        Ok(FaultEvent::Abort { reason }) => {
-                            warn!(%reason, "power: mission abort received");
+                        Some(FaultChange::SafeMode) => {
+                            rate::enter_safe_mode(&mut ticker, &mut period, "power");
+                            phase.rebase(seq, Instant::now(), period);
                         }
-                        Ok(_) => {}
-                        Err(tokio::sync::broadcast::error::TryRecvError::Empty) => break,
-                        Err(_) => break,
-                    }
+                        None => {}
+                    },
                 }
             }
 
+            // sampling period set by ground command (param2)
+            if let Some(new) = rate::take(&mut rate_rx, "power")
+                && rate::retune(&mut ticker, &mut period, new, fault.safe_mode(), "power")
+            {
+                phase.rebase(seq, Instant::now(), period);
+            }
+
             ticker.tick().await;
+            // simulated scheduling jitter (--sensor-jitter-ms)
+            jitter::delay(&mut jitter).await;
             let start = Instant::now();
+            // whole periods lost to a stall: seq follows ideal releases, not executions
+            let skipped = phase.catch_up(start);
+            if skipped > 0 {
+                warn!(skipped, "power: missed releases");
+                blackbox::record(Event::new(EventKind::SensorMiss, "power", format!("{skipped} releases skipped at seq {seq}")));
+                seq = seq.wrapping_add(skipped);
+            }
+            dog.pet(period);
+            let mut fault_status: Option<&str> = None;
+
+            // dropout fault: produce nothing this cycle
+            if fault.should_skip(start) {
+                super::log_dropout("power", sensor.sensor_id, seq, phase.sample(seq, start), skipped).await;
+                last_start = start;
+                seq = seq.wrapping_add(1);
+                continue;
+            }
 
             // simulated nominal values
             let mut batt_pct = 95.0 - (seq as f64 * 0.05);
@@ -60,14 +107,15 @@ pub fn spawn() {
             let mut current = 2.1;
 
             // if fault active, corrupt values
-            if let Some(until) = corrupt_until {
-                if Instant::now() < until {
-                    batt_pct = -5.0;      // invalid → Quality::Invalid expected
-                    voltage = 0.0;
-                    current = -10.0;
-                }
+            if let Some((mode, until)) = corrupt_until
+                && Instant::now() < until
+            {
+                (batt_pct, voltage, current) = corrupt(mode, batt_pct, voltage, current);
+                fault_status = Some("fault_corrupt");
             }
 
+            // thresholds may have been changed by ground (SetThreshold)
+            thresholds::refresh(&mut sensor);
             let mut r: SensorReading = sensor.create_reading(
                 batt_pct,
                 voltage,
@@ -76,16 +124,17 @@ pub fn spawn() {
                 seq,
             );
 
+            // clock skew fault: shift the sample timestamp
+            if fault.skew(start, &mut r.timestamp) {
+                fault_status = Some("fault_clock_skew");
+            }
+
             // timing
             let actual_ms = start.duration_since(last_start).as_secs_f64() * 1000.0;
             let ideal_ms = period.as_secs_f64() * 1000.0;
-            if seq == 0 {
-                r.jitter_ms = 0.0;
-                r.drift_ms = 0.0;
-            } else {
-                r.jitter_ms = (actual_ms - ideal_ms).abs();
-                r.drift_ms = actual_ms - ideal_ms;
-            }
+            // jitter/drift against the absolute release schedule, not the previous sample
+            (r.jitter_ms, r.drift_ms) = phase.sample(seq, start);
+            timing::stamp(&mut r, start);
             r.processing_latency_ms = 0.0;
 
             info!(
@@ -100,22 +149,39 @@ pub fn spawn() {
                 jitter_ms = format_args!("{:.3}", r.jitter_ms),
                 drift_ms = format_args!("{:.3}", r.drift_ms),
             );
+            super::log_reading("power", &r, fault_status, skipped).await;
 
-            let tx = match crate::telemetry::CHANNEL.get() {
-                Some(tx) => tx.clone(),
-                None => {
-                    warn!("telemetry channel not ready");
-                    seq = seq.wrapping_add(1);
-                    last_start = start;
-                    continue;
-                }
-            };
-            if let Err(e) = tx.send(r).await {
-                warn!(?e, "power: failed to enqueue reading");
+            // never await here: a full channel costs this reading, not the next period
+            if let Err(e) = crate::telemetry::try_enqueue(r) {
+                warn!(%e, "power: reading not enqueued");
             }
 
             last_start = start;
             seq = seq.wrapping_add(1);
         }
     });
+    Ok(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::Quality;
+
+    fn quality_under(mode: CorruptMode) -> Quality {
+        let sensor = PowerSensor::new(2, "Main Bus");
+        let (b, v, a) = corrupt(mode, 90.0, 12.3, 2.1);
+        sensor.create_reading(b, v, a, v * a, 0).quality
+    }
+
+    #[test]
+    fn full_corruption_is_invalid() {
+        assert_eq!(quality_under(CorruptMode::Full), Quality::Invalid);
+    }
+
+    #[test]
+    fn partial_current_corruption_passes_single_field_check() {
+        // only the battery range is checked, so a bad current alone still reads as Good
+        assert_eq!(quality_under(CorruptMode::PartialCurrent), Quality::Good);
+    }
 }
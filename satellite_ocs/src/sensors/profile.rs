@@ -0,0 +1,129 @@
+// Temperature models for the thermal sensor (selected with --thermal-profile)
+use crate::config::Config;
+
+/// Produces the simulated temperature (°C) for sample `seq`.
+pub trait TempProfile: Send {
+    fn next(&mut self, seq: u64) -> f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum TempProfileKind {
+    /// 60–68°C sawtooth; never leaves the warning band.
+    #[default]
+    Sawtooth,
+    /// Climbs past the emergency threshold, then resets.
+    Ramp,
+    /// Temperatures from `--thermal-replay-csv` ("t_ms,temp_c" rows).
+    Replay,
+}
+
+pub struct Sawtooth;
+
+impl TempProfile for Sawtooth {
+    fn next(&mut self, seq: u64) -> f64 {
+        60.0 + ((seq % 40) as f64 * 0.2)
+    }
+}
+
+/// base → peak in `step` increments per sample, then back to base.
+pub struct Ramp {
+    pub base: f64,
+    pub peak: f64,
+    pub step: f64,
+}
+
+impl Default for Ramp {
+    fn default() -> Self {
+        // 0.1°C per 50ms sample: 60 → 90°C in 15s
+        Self { base: 60.0, peak: 90.0, step: 0.1 }
+    }
+}
+
+impl TempProfile for Ramp {
+    fn next(&mut self, seq: u64) -> f64 {
+        let steps = ((self.peak - self.base) / self.step).ceil().max(1.0) as u64 + 1;
+        self.base + (seq % steps) as f64 * self.step
+    }
+}
+
+/// Plays back recorded temperatures, looping at the end. Sample `seq` is taken
+/// at `seq * period_ms` and uses the latest row at or before that time.
+pub struct Replay {
+    rows: Vec<(u64, f64)>, // (t_ms, temp_c), sorted by t_ms
+    period_ms: u64,
+}
+
+impl Replay {
+    pub fn from_csv(text: &str, period_ms: u64) -> Result<Self, String> {
+        let mut rows = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (t, temp) = line
+                .split_once(',')
+                .ok_or_else(|| format!("line {}: expected t_ms,temp_c", n + 1))?;
+            match (t.trim().parse::<u64>(), temp.trim().parse::<f64>()) {
+                (Ok(t), Ok(temp)) => rows.push((t, temp)),
+                _ if n == 0 => continue, // header
+                _ => return Err(format!("line {}: bad row '{line}'", n + 1)),
+            }
+        }
+        if rows.is_empty() {
+            return Err("replay csv has no rows".into());
+        }
+        rows.sort_by_key(|r| r.0);
+        Ok(Self { rows, period_ms: period_ms.max(1) })
+    }
+}
+
+impl TempProfile for Replay {
+    fn next(&mut self, seq: u64) -> f64 {
+        // the last row is held as long as the gap before it (one period for a single row)
+        let hold = match self.rows.as_slice() {
+            [.., (prev, _), (last, _)] => (last - prev).max(self.period_ms),
+            _ => self.period_ms,
+        };
+        let span = self.rows.last().map(|r| r.0).unwrap_or(0) + hold;
+        let t = seq.wrapping_mul(self.period_ms) % span;
+        let idx = self.rows.partition_point(|r| r.0 <= t).saturating_sub(1);
+        self.rows[idx].1
+    }
+}
+
+/// Build the profile selected in `cfg`; `period_ms` is the thermal sampling period.
+pub fn from_config(cfg: &Config, period_ms: u64) -> Result<Box<dyn TempProfile>, String> {
+    Ok(match cfg.thermal_profile {
+        TempProfileKind::Sawtooth => Box::new(Sawtooth),
+        TempProfileKind::Ramp => Box::new(Ramp::default()),
+        TempProfileKind::Replay => {
+            let path = cfg
+                .thermal_replay_csv
+                .as_deref()
+                .ok_or("--thermal-profile replay needs --thermal-replay-csv")?;
+            let text = std::fs::read_to_string(path).map_err(|e| format!("read {path}: {e}"))?;
+            Box::new(Replay::from_csv(&text, period_ms)?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::ThermalSensor;
+
+    #[test]
+    fn ramp_crosses_emergency_threshold() {
+        let sensor = ThermalSensor::new(1, "CPU");
+        let mut ramp = Ramp::default();
+        assert!((0..1000).any(|seq| ramp.next(seq) > sensor.emergency_threshold));
+    }
+
+    #[test]
+    fn replay_holds_last_sample_and_loops() {
+        let mut p = Replay::from_csv("t_ms,temp_c\n0,61.0\n100,82.5\n200,86.0\n", 50).unwrap();
+        let temps: Vec<f64> = (0..7).map(|s| p.next(s)).collect();
+        assert_eq!(temps, [61.0, 61.0, 82.5, 82.5, 86.0, 86.0, 61.0]);
+    }
+}
@@ -0,0 +1,57 @@
+use shared_protocol::RadiationSensor;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::jitter::Jitter;
+use super::{thresholds, SensorDef};
+
+/// Simulated dose rate (mGy/h): quiet background with a periodic trapped-belt pass.
+fn dose_rate(seq: u64) -> f64 {
+    if seq % 90 < 10 { 2.5 } else { 0.4 }
+}
+
+pub fn spawn(def: &SensorDef, jitter: Option<Jitter>) -> Result<JoinHandle<()>, String> {
+    let mut sensor = RadiationSensor::new(def.id, &def.location);
+    if let Some(ms) = def.interval_ms {
+        sensor.sampling_interval_ms = ms;
+    }
+    def.configure(&mut sensor)?;
+    thresholds::register(&sensor);
+
+    let (sensor_id, period) = (sensor.sensor_id, Duration::from_millis(sensor.sampling_interval_ms));
+    let mut total_dose = 0.0_f64; // mGy accumulated since boot
+    Ok(tokio::spawn(super::run_loop("radiation", sensor_id, period, jitter, move |seq, elapsed| {
+        // dose accrues whether or not the sample is reported
+        let dose_rate = dose_rate(seq);
+        total_dose += dose_rate * elapsed.as_secs_f64() / 3600.0;
+
+        // thresholds may have been changed by ground (SetThreshold)
+        thresholds::refresh(&mut sensor);
+        sensor.create_reading(dose_rate, total_dose, seq)
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::sleep;
+
+    #[tokio::test]
+    async fn dosimeter_reports_an_accumulating_dose() {
+        spawn(&"radiation:731:Test Bay:20".parse().unwrap(), None).unwrap();
+        sleep(Duration::from_millis(300)).await;
+        crate::logging::csv::flush_all().await;
+
+        let text = std::fs::read_to_string(crate::logging::csv::log_path("sensors.csv")).unwrap();
+        let header: Vec<&str> = text.lines().next().unwrap().split(',').collect();
+        let id_col = header.iter().position(|h| *h == "sensor_id").unwrap();
+        let rows = text
+            .lines()
+            .filter(|l| l.split(',').nth(1) == Some("radiation") && l.split(',').nth(id_col) == Some("731"))
+            .count();
+        assert!(rows >= 5, "only {rows} radiation rows");
+        // early releases fall in the belt pass
+        assert_eq!(dose_rate(3), 2.5);
+        assert_eq!(dose_rate(50), 0.4);
+    }
+}
@@ -0,0 +1,127 @@
+// src/sensors/rate.rs
+// Runtime sampling-rate control: the command handler publishes, sensor loops retune.
+use once_cell::sync::Lazy;
+use shared_protocol::{Command, CommandType};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tokio::time::{self, Duration, Interval};
+use tracing::{info, warn};
+
+/// Accepted `param2` range (ms); anything outside is rejected rather than clamped.
+pub const MIN_PERIOD_MS: f64 = 5.0;
+pub const MAX_PERIOD_MS: f64 = 10_000.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateChange {
+    pub target: &'static str,
+    pub period: Duration,
+}
+
+static RATES: Lazy<broadcast::Sender<RateChange>> = Lazy::new(|| broadcast::channel(16).0);
+
+/// Sensors subscribe before their loop starts.
+pub fn subscribe() -> broadcast::Receiver<RateChange> {
+    RATES.subscribe()
+}
+
+/// Publish the new period carried in `param2` of a *Control command.
+/// Returns the change, or None if the command doesn't retune a sensor.
+pub fn apply(cmd: &Command) -> Option<RateChange> {
+    let target = match cmd.command_type {
+        CommandType::ThermalControl => "thermal",
+        CommandType::PowerControl => "power",
+        CommandType::AttitudeControl => "attitude",
+        _ => return None,
+    };
+    if !(MIN_PERIOD_MS..=MAX_PERIOD_MS).contains(&cmd.param2) {
+        warn!(cmd_id = %cmd.command_id, param2 = cmd.param2, "sampling period out of range; ignored");
+        return None;
+    }
+    let change = RateChange {
+        target,
+        period: Duration::from_micros((cmd.param2 * 1000.0) as u64),
+    };
+    let _ = RATES.send(change.clone());
+    Some(change)
+}
+
+/// Non-blocking: the latest period requested for `target`, if any arrived.
+pub fn take(rx: &mut broadcast::Receiver<RateChange>, target: &str) -> Option<Duration> {
+    let mut latest = None;
+    loop {
+        match rx.try_recv() {
+            Ok(c) if c.target == target => latest = Some(c.period),
+            Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+            Err(_) => break,
+        }
+    }
+    latest
+}
+
+/// `period` as run in safe mode: stretched by `SAFE_MODE_PERIOD_FACTOR`.
+fn in_safe_mode(period: Duration, safe_mode: bool) -> Duration {
+    if safe_mode { period * crate::faults::SAFE_MODE_PERIOD_FACTOR } else { period }
+}
+
+fn set_period(ticker: &mut Interval, period: &mut Duration, new: Duration) {
+    *period = new;
+    *ticker = time::interval(new);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+}
+
+/// Swap in a ticker at `new` (scaled up while in safe mode). Returns false if unchanged.
+pub fn retune(ticker: &mut Interval, period: &mut Duration, new: Duration, safe_mode: bool, name: &str) -> bool {
+    let new = in_safe_mode(new, safe_mode);
+    if new == *period {
+        return false;
+    }
+    info!(
+        from_ms = period.as_millis() as u64,
+        to_ms = new.as_millis() as u64,
+        "{name}: sampling period changed by command"
+    );
+    set_period(ticker, period, new);
+    true
+}
+
+/// Safe state after `Abort`: keep reporting, at a minimal rate.
+pub fn enter_safe_mode(ticker: &mut Interval, period: &mut Duration, name: &str) {
+    set_period(ticker, period, in_safe_mode(*period, true));
+    warn!(period_ms = period.as_millis() as u64, "{name}: entering safe mode");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn thermal_command_retunes_thermal_period_only() {
+        let mut thermal_rx = subscribe();
+        let mut power_rx = subscribe();
+        let mut cmd = Command::thermal_normal_operation(1);
+        cmd.param2 = 25.0;
+        apply(&cmd).unwrap();
+
+        // other tests may publish concurrently; 25 ms must be among what thermal saw
+        let mut seen = Vec::new();
+        while let Ok(c) = thermal_rx.try_recv() {
+            seen.push(c);
+        }
+        assert!(seen.contains(&RateChange { target: "thermal", period: Duration::from_millis(25) }));
+        assert_eq!(take(&mut power_rx, "power"), None);
+
+        let mut period = Duration::from_millis(50);
+        let mut ticker = time::interval(period);
+        assert!(retune(&mut ticker, &mut period, Duration::from_millis(25), false, "thermal"));
+        assert_eq!(period, Duration::from_millis(25));
+        assert_eq!(ticker.period(), Duration::from_millis(25));
+
+        // safe mode stretches the running period, and any later commanded one
+        enter_safe_mode(&mut ticker, &mut period, "thermal");
+        assert_eq!(ticker.period(), Duration::from_millis(25) * crate::faults::SAFE_MODE_PERIOD_FACTOR);
+        assert!(retune(&mut ticker, &mut period, Duration::from_millis(10), true, "thermal"));
+        assert_eq!(period, Duration::from_millis(10) * crate::faults::SAFE_MODE_PERIOD_FACTOR);
+
+        cmd.param2 = 0.5;
+        assert!(apply(&cmd).is_none());
+    }
+}
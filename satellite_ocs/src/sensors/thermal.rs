@@ -1,88 +1,177 @@
-use shared_protocol::{EmergencyData, Severity, SensorReading, ThermalSensor};
+use shared_protocol::{EmergencyData, Severity, SensorReading, Status, ThermalSensor};
+use tokio::task::JoinHandle;
 use tokio::time::{self, Duration, Instant};
 use tracing::{info, warn};
 use chrono::Utc;
 
 // fault bus
-use crate::faults::{self, FaultEvent};
+use crate::faults::FaultEvent;
+use crate::health::watchdog;
+use crate::logging::blackbox::{self, Event, EventKind};
+use crate::scheduler::timing::{self, PhaseTracker};
+use crate::scheduler::PreemptTrigger;
+use crate::config::Config;
+use super::jitter::{self, Jitter};
+use super::{profile, rate, thresholds, FaultChange, FaultState, SensorDef};
 
-pub fn spawn() {
-    let sensor = ThermalSensor::new(1, "CPU");
+/// A Critical/Emergency reading asks the scheduler for a thermal_control job (rate-limited).
+fn preempt_on(r: &SensorReading, trigger: &mut PreemptTrigger, now: Instant) -> bool {
+    matches!(r.status, Status::Critical | Status::Emergency) && trigger.fire(now)
+}
+
+/// What an injected delay does to a cycle that has already used `elapsed` of its period.
+#[derive(Debug, PartialEq)]
+enum FaultDelay {
+    Sleep(Duration),
+    /// The delay would push the cycle past its period: skip it rather than stack it
+    Overrun,
+}
 
-    tokio::spawn(async move {
+fn fault_delay(extra: Duration, elapsed: Duration, period: Duration) -> FaultDelay {
+    if elapsed + extra >= period { FaultDelay::Overrun } else { FaultDelay::Sleep(extra) }
+}
+
+/// Apply an active delay fault to the cycle released at `start`; returns the status for sensors.csv.
+/// A delay that would overrun the period is not slept but logged, so later cycles stay on time.
+async fn apply_fault_delay(extra: Duration, start: Instant, period: Duration, seq: u64) -> &'static str {
+    match fault_delay(extra, start.elapsed(), period) {
+        FaultDelay::Sleep(d) => {
+            time::sleep(d).await;
+            "fault_delay"
+        }
+        FaultDelay::Overrun => {
+            let (extra_ms, period_ms) = (extra.as_millis() as u64, period.as_millis() as u64);
+            warn!(seq, extra_ms, period_ms, "thermal: period overrun due to fault; delay skipped");
+            blackbox::record(Event::new(
+                EventKind::FaultOverrun,
+                "thermal",
+                format!("{extra_ms}ms delay overruns {period_ms}ms period at seq {seq}"),
+            ));
+            "fault_overrun"
+        }
+    }
+}
+
+pub fn spawn(cfg: &Config, def: &SensorDef, mut jitter: Option<Jitter>) -> Result<JoinHandle<()>, String> {
+    let mut sensor = ThermalSensor::new(def.id, &def.location);
+    if let Some(ms) = def.interval_ms {
+        sensor.sampling_interval_ms = ms;
+    }
+    def.configure(&mut sensor)?;
+    thresholds::register(&sensor);
+    let mut profile = profile::from_config(cfg, sensor.sampling_interval_ms)?;
+    info!(profile = ?cfg.thermal_profile, "thermal: temperature model selected");
+    let mut preempt = PreemptTrigger::new(cfg.thermal_min_interarrival_ms);
+
+    let task = tokio::spawn(async move {
         let mut seq = 0u64;
-        let period = Duration::from_millis(sensor.sampling_interval_ms);
+        let mut period = Duration::from_millis(sensor.sampling_interval_ms);
+        let dog = watchdog::register(format!("thermal#{}", sensor.sensor_id), period);
         let mut ticker = time::interval(period);
         ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
         // prime the ticker for stable phase
         ticker.tick().await;
         let mut last_start = Instant::now();
+        let mut phase = PhaseTracker::new(last_start + period, period);
 
         // fault state
-        let mut faults_rx = faults::subscribe();
-        let mut cur_fault_id: Option<String> = None;
+        let mut fault = FaultState::new();
         let mut extra_delay_ms: u64 = 0;
         let mut fault_until: Option<Instant> = None;
+        let mut shutdown_rx = crate::shutdown::subscribe();
+        let mut rate_rx = rate::subscribe();
 
         // safety: missed cycles
         let mut consecutive_misses: u32 = 0;
 
         loop {
+            if crate::shutdown::requested(&mut shutdown_rx) {
+                info!("thermal: stopping (shutdown)");
+                break;
+            }
+
             // non-blocking drain of fault events
-            if let Some(rx) = faults_rx.as_mut() {
-                loop {
-                    match rx.try_recv() {
-                        Ok(FaultEvent::ThermalDelay { fault_id, extra_ms, for_ms }) => {
-                            cur_fault_id = Some(fault_id);
-                            extra_delay_ms = extra_ms;
-                            fault_until = Some(Instant::now() + Duration::from_millis(for_ms));
-                            warn!(extra_ms, for_ms, "thermal: injected delay fault");
-                        }
-                        Ok(FaultEvent::Recover { fault_id }) => {
-                            if cur_fault_id.as_deref() == Some(fault_id.as_str()) {
-                                extra_delay_ms = 0;
-                                fault_until = None;
-                                faults::ack_recovered(&fault_id, "thermal").await;
-                                info!("thermal: recovered");
-                                cur_fault_id = None;
-                            }
+            while let Some(ev) = fault.next_event("thermal") {
+                match ev {
+                    FaultEvent::ThermalDelay { fault_id, extra_ms, for_ms } => {
+                        fault.carry(fault_id);
+                        extra_delay_ms = extra_ms;
+                        fault_until = Some(Instant::now() + Duration::from_millis(for_ms));
+                        warn!(extra_ms, for_ms, "thermal: injected delay fault");
+                    }
+                    ev => match fault.on_event("thermal", ev).await {
+                        Some(FaultChange::Recovered) => {
+                            extra_delay_ms = 0;
+                            fault_until = None;
                         }
-                        Ok(FaultEvent::Abort { reason }) => {
-                            warn!(%reason, "thermal: mission abort received");
+                        Some(FaultChange::SafeMode) => {
+                            rate::enter_safe_mode(&mut ticker, &mut period, "thermal");
+                            phase.rebase(seq, Instant::now(), period);
                         }
-                        Ok(_) => {}
-                        Err(tokio::sync::broadcast::error::TryRecvError::Empty) => break,
-                        Err(_) => break,
-                    }
+                        None => {}
+                    },
                 }
             }
 
+            // sampling period set by ground command (param2)
+            if let Some(new) = rate::take(&mut rate_rx, "thermal")
+                && rate::retune(&mut ticker, &mut period, new, fault.safe_mode(), "thermal")
+            {
+                phase.rebase(seq, Instant::now(), period);
+            }
+
             ticker.tick().await;
+            // simulated scheduling jitter (--sensor-jitter-ms)
+            jitter::delay(&mut jitter).await;
             let start = Instant::now();
+            // whole periods lost to a stall: seq follows ideal releases, not executions
+            let skipped = phase.catch_up(start);
+            if skipped > 0 {
+                warn!(skipped, "thermal: missed releases");
+                blackbox::record(Event::new(EventKind::SensorMiss, "thermal", format!("{skipped} releases skipped at seq {seq}")));
+                seq = seq.wrapping_add(skipped);
+            }
+            dog.pet(period);
+            let mut fault_status: Option<&str> = None;
 
-            // if fault active, add a small delay
+            // dropout fault: produce nothing this cycle
+            if fault.should_skip(start) {
+                super::log_dropout("thermal", sensor.sensor_id, seq, phase.sample(seq, start), skipped).await;
+                last_start = start;
+                seq = seq.wrapping_add(1);
+                continue;
+            }
+
+            // if fault active, add a small delay (skipped if it would overrun the period)
             if let Some(until) = fault_until {
                 if Instant::now() < until && extra_delay_ms > 0 {
-                    tokio::time::sleep(Duration::from_millis(extra_delay_ms)).await;
+                    let extra = Duration::from_millis(extra_delay_ms);
+                    fault_status = Some(apply_fault_delay(extra, start, period, seq).await);
                 }
             }
 
             // simulated temperature
-            let temp_c = 60.0 + ((seq % 40) as f64 * 0.2);
+            let temp_c = profile.next(seq);
 
+            // thresholds may have been changed by ground (SetThreshold)
+            thresholds::refresh(&mut sensor);
             let mut r: SensorReading = sensor.create_reading(temp_c, seq);
+            if preempt_on(&r, &mut preempt, start) {
+                info!(seq, temp_c = format_args!("{:.1}", temp_c), "thermal: preempting for thermal_control");
+            }
+
+            // clock skew fault: shift the sample timestamp
+            if fault.skew(start, &mut r.timestamp) {
+                fault_status = Some("fault_clock_skew");
+            }
 
             // timing
             let actual_ms = start.duration_since(last_start).as_secs_f64() * 1000.0;
             let ideal_ms = period.as_secs_f64() * 1000.0;
-            if seq == 0 {
-                r.jitter_ms = 0.0;
-                r.drift_ms = 0.0;
-            } else {
-                r.jitter_ms = (actual_ms - ideal_ms).abs();
-                r.drift_ms = actual_ms - ideal_ms;
-            }
+            // jitter/drift against the absolute release schedule, not the previous sample
+            (r.jitter_ms, r.drift_ms) = phase.sample(seq, start);
+            timing::stamp(&mut r, start);
             // ingestion sets real read→queue latency; set to 0 here
             r.processing_latency_ms = 0.0;
 
@@ -96,19 +185,17 @@ pub fn spawn() {
                 jitter_ms = format_args!("{:.3}", r.jitter_ms),
                 drift_ms = format_args!("{:.3}", r.drift_ms),
             );
+            super::log_reading("thermal", &r, fault_status, skipped).await;
 
             // enqueue to telemetry
-            let tx = match crate::telemetry::CHANNEL.get() {
-                Some(tx) => tx.clone(),
-                None => {
-                    warn!("telemetry channel not ready");
-                    seq = seq.wrapping_add(1);
-                    last_start = start;
-                    continue;
-                }
-            };
-
-            let send_res = tx.send(r).await;
+            // never await here: a full channel costs this reading (a miss), not the next period
+            let send_res = crate::telemetry::try_enqueue(r);
+            if send_res == Err(crate::telemetry::IngressError::NotReady) {
+                warn!("telemetry channel not ready");
+                seq = seq.wrapping_add(1);
+                last_start = start;
+                continue;
+            }
             if send_res.is_err() || (actual_ms - ideal_ms) > 1.0 {
                 consecutive_misses += 1;
             } else {
@@ -135,6 +222,7 @@ pub fn spawn() {
                         ],
                         auto_recovery_attempted: false,
                         timestamp: Utc::now(),
+                        metadata: Default::default(),
                     };
                     let _ = em_tx.try_send(em);
                 }
@@ -144,4 +232,56 @@ pub fn spawn() {
             seq = seq.wrapping_add(1);
         }
     });
+    Ok(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn critical_reading_injects_a_thermal_control_job() {
+        // the only test that runs the scheduler (it owns PREEMPT_CH)
+        tokio::spawn(crate::scheduler::rm::spawn_rm(Config::test_default()));
+        time::timeout(Duration::from_secs(1), async {
+            while crate::scheduler::PREEMPT_CH.get().is_none() {
+                time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("scheduler not started");
+
+        let sensor = ThermalSensor::new(1, "CPU");
+        let mut trigger = PreemptTrigger::new(100);
+        let now = Instant::now();
+        assert!(!preempt_on(&sensor.create_reading(40.0, 0), &mut trigger, now));
+        assert!(preempt_on(&sensor.create_reading(82.0, 1), &mut trigger, now));
+        // sustained heat: held off until the min interarrival has passed
+        assert!(!preempt_on(&sensor.create_reading(90.0, 2), &mut trigger, now + Duration::from_millis(50)));
+
+        time::sleep(Duration::from_millis(50)).await;
+        crate::logging::csv::flush_all().await;
+        let csv = std::fs::read_to_string(crate::logging::csv::log_path("scheduler.csv")).unwrap();
+        assert!(csv.lines().any(|l| l.contains(",thermal_control,")), "{csv}");
+    }
+
+    #[tokio::test]
+    async fn delay_longer_than_the_period_is_skipped_and_logged() {
+        let period = Duration::from_millis(20);
+        let mut ticker = time::interval(period);
+        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        ticker.tick().await;
+        let t0 = Instant::now();
+        for seq in 0..5 {
+            ticker.tick().await;
+            let status = apply_fault_delay(Duration::from_millis(50), Instant::now(), period, seq).await;
+            assert_eq!(status, "fault_overrun");
+        }
+        // five periods, not five stacked 50ms delays
+        assert!(t0.elapsed() < Duration::from_millis(5 * 20 + 40), "{:?}", t0.elapsed());
+        assert!(blackbox::count(EventKind::FaultOverrun) > 0);
+
+        assert_eq!(apply_fault_delay(Duration::from_millis(5), Instant::now(), period, 5).await, "fault_delay");
+        assert_eq!(fault_delay(Duration::from_millis(10), Duration::from_millis(15), period), FaultDelay::Overrun);
+    }
 }
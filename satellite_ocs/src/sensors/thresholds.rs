@@ -0,0 +1,207 @@
+// src/sensors/thresholds.rs
+// Classification thresholds that ground can change in flight (CommandType::SetThreshold).
+// Sensors register at spawn and refresh their copy before classifying each reading.
+use once_cell::sync::Lazy;
+use shared_protocol::{
+    AttitudeSensor, Command, PowerSensor, RadiationSensor, SensorType, ThermalSensor,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::info;
+
+/// A sensor whose two thresholds can be adjusted.
+pub trait Adjustable {
+    fn id(&self) -> u32;
+    fn sensor_type(&self) -> SensorType;
+    /// (name, value), the less severe level first.
+    fn thresholds(&self) -> [(&'static str, f64); 2];
+    fn set_thresholds(&mut self, values: [f64; 2]);
+    /// Whether the more severe level sits at the higher value (false for battery %).
+    fn rising(&self) -> bool {
+        true
+    }
+}
+
+impl Adjustable for ThermalSensor {
+    fn id(&self) -> u32 { self.sensor_id }
+    fn sensor_type(&self) -> SensorType { SensorType::Thermal }
+    fn thresholds(&self) -> [(&'static str, f64); 2] {
+        [("critical", self.critical_threshold), ("emergency", self.emergency_threshold)]
+    }
+    fn set_thresholds(&mut self, [c, e]: [f64; 2]) {
+        self.critical_threshold = c;
+        self.emergency_threshold = e;
+    }
+}
+
+impl Adjustable for PowerSensor {
+    fn id(&self) -> u32 { self.sensor_id }
+    fn sensor_type(&self) -> SensorType { SensorType::Power }
+    fn thresholds(&self) -> [(&'static str, f64); 2] {
+        [("low", self.low_battery_threshold), ("critical", self.critical_battery_threshold)]
+    }
+    fn set_thresholds(&mut self, [l, c]: [f64; 2]) {
+        self.low_battery_threshold = l;
+        self.critical_battery_threshold = c;
+    }
+    fn rising(&self) -> bool {
+        false
+    }
+}
+
+impl Adjustable for AttitudeSensor {
+    fn id(&self) -> u32 { self.sensor_id }
+    fn sensor_type(&self) -> SensorType { SensorType::Attitude }
+    fn thresholds(&self) -> [(&'static str, f64); 2] {
+        [("max_error", self.max_acceptable_error), ("critical_error", self.critical_error_threshold)]
+    }
+    fn set_thresholds(&mut self, [m, c]: [f64; 2]) {
+        self.max_acceptable_error = m;
+        self.critical_error_threshold = c;
+    }
+}
+
+impl Adjustable for RadiationSensor {
+    fn id(&self) -> u32 { self.sensor_id }
+    fn sensor_type(&self) -> SensorType { SensorType::Radiation }
+    fn thresholds(&self) -> [(&'static str, f64); 2] {
+        [("warning", self.warning_dose), ("critical", self.critical_dose)]
+    }
+    fn set_thresholds(&mut self, [w, c]: [f64; 2]) {
+        self.warning_dose = w;
+        self.critical_dose = c;
+    }
+}
+
+struct Entry {
+    sensor_type: SensorType,
+    names: [&'static str; 2],
+    values: [f64; 2],
+    rising: bool,
+}
+
+static STORE: Lazy<Mutex<HashMap<u32, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn store() -> std::sync::MutexGuard<'static, HashMap<u32, Entry>> {
+    STORE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Publish the sensor's built-in thresholds (call once at spawn).
+pub fn register(sensor: &impl Adjustable) {
+    let [(n0, v0), (n1, v1)] = sensor.thresholds();
+    store().insert(
+        sensor.id(),
+        Entry { sensor_type: sensor.sensor_type(), names: [n0, n1], values: [v0, v1], rising: sensor.rising() },
+    );
+}
+
+/// Copy the current thresholds into the sensor before it classifies a reading.
+pub fn refresh(sensor: &mut impl Adjustable) {
+    if let Some(e) = store().get(&sensor.id()) {
+        sensor.set_thresholds(e.values);
+    }
+}
+
+/// Apply a SetThreshold command; the error is reported in the "failed" ACK.
+pub fn update(cmd: &Command) -> Result<(), String> {
+    let sensor_id = cmd.param1 as u32;
+    let which = cmd.metadata.get("threshold").map(String::as_str).unwrap_or("");
+    let value = cmd.param2;
+    if !value.is_finite() {
+        return Err(format!("threshold value not finite: {value}"));
+    }
+
+    let mut store = store();
+    let e = store
+        .get_mut(&sensor_id)
+        .ok_or_else(|| format!("unknown sensor {sensor_id}"))?;
+    let kind = format!("{:?}", e.sensor_type).to_lowercase();
+    if cmd.metadata.get("sensor_type").is_some_and(|t| *t != kind) {
+        return Err(format!("sensor {sensor_id} is {kind}, not {}", cmd.metadata["sensor_type"]));
+    }
+    let idx = e
+        .names
+        .iter()
+        .position(|n| *n == which)
+        .ok_or_else(|| format!("{kind} has no '{which}' threshold (expected {} or {})", e.names[0], e.names[1]))?;
+
+    let mut values = e.values;
+    values[idx] = value;
+    check_order(e, values)?;
+    e.values = values;
+    info!(sensor_id, %kind, threshold = which, value, "threshold updated by command");
+    Ok(())
+}
+
+/// Set both thresholds of several sensors at once (ConfigUpdate): every pair is checked
+/// first, so on error nothing changes.
+pub fn replace_all(updates: &[(u32, [f64; 2])]) -> Result<(), String> {
+    let mut store = store();
+    for &(sensor_id, values) in updates {
+        let e = store.get(&sensor_id).ok_or_else(|| format!("unknown sensor {sensor_id}"))?;
+        if !values.iter().all(|v| v.is_finite()) {
+            return Err(format!("sensor {sensor_id}: threshold value not finite"));
+        }
+        check_order(e, values).map_err(|m| format!("sensor {sensor_id}: {m}"))?;
+    }
+    for &(sensor_id, values) in updates {
+        if let Some(e) = store.get_mut(&sensor_id) {
+            e.values = values;
+        }
+        info!(sensor_id, ?values, "thresholds replaced by config update");
+    }
+    Ok(())
+}
+
+/// The more severe level must sit beyond the less severe one.
+fn check_order(e: &Entry, values: [f64; 2]) -> Result<(), String> {
+    let ordered = if e.rising { values[0] < values[1] } else { values[0] > values[1] };
+    if ordered {
+        return Ok(());
+    }
+    Err(format!(
+        "{} ({}) must be {} {} ({})",
+        e.names[0],
+        values[0],
+        if e.rising { "below" } else { "above" },
+        e.names[1],
+        values[1]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::executor;
+    use shared_protocol::Status;
+    use tokio::sync::mpsc;
+
+    async fn final_status(cmd: Command) -> String {
+        let (tx, mut rx) = mpsc::channel(4);
+        executor::execute(cmd, tx).await;
+        let mut last = String::new();
+        while let Ok(ack) = rx.try_recv() {
+            last = ack.status;
+        }
+        last
+    }
+
+    #[tokio::test]
+    async fn lowered_critical_threshold_classifies_sooner() {
+        // unique id: the store is process-wide
+        let mut sensor = ThermalSensor::new(901, "Test");
+        register(&sensor);
+        assert_eq!(sensor.create_reading(75.0, 0).status, Status::Warning);
+
+        let cmd = Command::set_threshold(901, SensorType::Thermal, "critical", 70.0);
+        assert_eq!(final_status(cmd).await, "completed");
+        refresh(&mut sensor);
+        assert_eq!(sensor.create_reading(75.0, 1).status, Status::Critical);
+
+        // critical must stay below emergency (85)
+        let bad = Command::set_threshold(901, SensorType::Thermal, "critical", 90.0);
+        assert_eq!(final_status(bad).await, "failed");
+        refresh(&mut sensor);
+        assert_eq!(sensor.critical_threshold, 70.0);
+    }
+}
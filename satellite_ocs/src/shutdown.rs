@@ -0,0 +1,20 @@
+// src/shutdown.rs
+// Process-wide stop signal: main fires it on Ctrl+C; producer loops (sensors, batcher) subscribe.
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+static SHUTDOWN: Lazy<broadcast::Sender<()>> = Lazy::new(|| broadcast::channel(1).0);
+
+/// Subscribe before the task's loop starts; a trigger sent earlier is not seen.
+pub fn subscribe() -> broadcast::Receiver<()> {
+    SHUTDOWN.subscribe()
+}
+
+pub fn trigger() {
+    let _ = SHUTDOWN.send(());
+}
+
+/// Non-blocking check for loops that already poll on a ticker.
+pub fn requested(rx: &mut broadcast::Receiver<()>) -> bool {
+    !matches!(rx.try_recv(), Err(TryRecvError::Empty))
+}
@@ -1,34 +1,142 @@
-use crate::{config::Config, crypto::Crypto, logging};
+use crate::logging::blackbox::{self, Event, EventKind};
+use crate::config::{Config, LiveConfig, LIVE};
+use crate::{crypto::Crypto, logging};
 use chrono::Utc;
 use once_cell::sync::OnceCell;
 use shared_protocol::{
-    CommunicationPacket, EmergencyData, EncryptedFrame, Priority, SensorReading, Source,
+    CommunicationPacket, EmergencyData, EncryptedFrame, Priority, SensorReading, Severity, Source,
 };
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::net::transport::Transport;
 use tokio::{
-    net::UdpSocket,
-    sync::mpsc,
+    sync::{broadcast, mpsc, Notify},
     time::{self, Duration},
 };
 use tracing::info;
 
-use super::prio_buffer::{BufferHandle, InsertResult};
+use super::coalesce::EmergencyCoalescer;
+use super::degrade::{self, Level};
+use super::prio_buffer::{BucketCaps, BufferHandle, DropPolicy, InsertResult};
 
 /// Sensors send readings here; an ingest task moves them into the priority buffer.
 pub static CHANNEL: OnceCell<mpsc::Sender<SensorReading>> = OnceCell::new();
 
+/// Readings refused because the ingress channel was full (since boot).
+static BACKPRESSURE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum IngressError {
+    #[error("telemetry channel not ready")]
+    NotReady,
+    #[error("telemetry channel full (backpressure)")]
+    Full,
+    #[error("telemetry channel closed")]
+    Closed,
+}
+
+/// Non-blocking sensor enqueue: a slow batcher costs a reading, never the sensor's period.
+pub fn try_enqueue(r: SensorReading) -> Result<(), IngressError> {
+    let tx = CHANNEL.get().ok_or(IngressError::NotReady)?;
+    try_enqueue_to(tx, r)
+}
+
+fn try_enqueue_to(tx: &mpsc::Sender<SensorReading>, r: SensorReading) -> Result<(), IngressError> {
+    match tx.try_send(r) {
+        Ok(()) => Ok(()),
+        Err(mpsc::error::TrySendError::Full(r)) => {
+            BACKPRESSURE.fetch_add(1, Ordering::Relaxed);
+            let prio = format!("{:?}", r.priority).to_lowercase();
+            tokio::spawn(async move { log_drop(&prio, "backpressure").await });
+            Err(IngressError::Full)
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => Err(IngressError::Closed),
+    }
+}
+
+pub fn backpressure_count() -> u64 {
+    BACKPRESSURE.load(Ordering::Relaxed)
+}
+
 /// Emergency alerts (e.g., from thermal) go here; batcher sends immediately.
 pub static EMER_TX: OnceCell<mpsc::Sender<EmergencyData>> = OnceCell::new();
 
 /// The priority bounded buffer
 pub static BUFFER: OnceCell<BufferHandle> = OnceCell::new();
 
-/// Initialize the priority buffer (call once from main before spawning sensors)
-pub fn init_priority_buffer(capacity: usize) {
-    let _ = BUFFER.set(BufferHandle::new(capacity));
+/// Readings replayed on a ground DataRequest; sent at once, outside the batch timer.
+static REPLAY_TX: OnceCell<mpsc::Sender<Vec<SensorReading>>> = OnceCell::new();
+
+/// Most readings one DataRequest sends back.
+pub const MAX_REPLAY: usize = 16;
+
+/// Queue the newest readings of `sensor_id` (up to `MAX_REPLAY`) for immediate downlink;
+/// returns how many. The error becomes the DataRequest's "failed" ACK.
+pub fn replay(sensor_id: u32) -> Result<usize, String> {
+    let tx = REPLAY_TX.get().ok_or("telemetry not ready")?;
+    replay_to(tx, sensor_id)
 }
 
-pub async fn spawn_batcher(cfg: Config, crypto: Crypto, tx_sock: Arc<UdpSocket>, framer: crate::net::framing::Framer) {
+fn replay_to(tx: &mpsc::Sender<Vec<SensorReading>>, sensor_id: u32) -> Result<usize, String> {
+    let readings: Vec<_> = super::history::recent(sensor_id, MAX_REPLAY).into_iter().map(Arc::unwrap_or_clone).collect();
+    if readings.is_empty() {
+        return Err(format!("no recent readings for sensor {sensor_id}"));
+    }
+    let n = readings.len();
+    tx.try_send(readings).map_err(|e| format!("replay queue: {e}"))?;
+    Ok(n)
+}
+
+/// Queue the newest reading of every sensor type as one immediate packet (DataRequest
+/// "SNAPSHOT"); unlike `replay`, not tied to one sensor. Returns how many were queued.
+pub fn snapshot() -> Result<usize, String> {
+    let tx = REPLAY_TX.get().ok_or("telemetry not ready")?;
+    snapshot_to(tx)
+}
+
+fn snapshot_to(tx: &mpsc::Sender<Vec<SensorReading>>) -> Result<usize, String> {
+    let readings: Vec<_> = super::history::latest_per_type().into_iter().map(Arc::unwrap_or_clone).collect();
+    if readings.is_empty() {
+        return Err("no readings recorded yet".into());
+    }
+    let n = readings.len();
+    tx.try_send(readings).map_err(|e| format!("snapshot queue: {e}"))?;
+    Ok(n)
+}
+
+/// One telemetry packet (more only if it would be oversized), sealed and sent now.
+async fn send_replay(crypto: &Crypto, sock: &dyn Transport, readings: Vec<SensorReading>) {
+    let n = readings.len();
+    for pkt in CommunicationPacket::new_telemetry_split(readings, Source::Satellite) {
+        match crypto.seal(&pkt) {
+            Ok(bytes) => {
+                log_frame_header(&bytes);
+                let _ = sock.send(&bytes).await;
+            }
+            Err(e) => tracing::warn!(%e, "replay: seal failed"),
+        }
+    }
+    info!(readings = n, "tx replayed telemetry (DataRequest)");
+}
+
+/// Initialize the priority buffer (call once from main before spawning sensors).
+/// `aging_ms` = 0 keeps strict priority order; `bucket_caps` replaces the shared `capacity`.
+pub fn init_priority_buffer(capacity: usize, aging_ms: u64, policy: DropPolicy, bucket_caps: Option<BucketCaps>) {
+    let aging = (aging_ms > 0).then(|| std::time::Duration::from_millis(aging_ms));
+    let mut buf = BufferHandle::with_aging(capacity, aging).with_policy(policy);
+    if let Some(caps) = bucket_caps {
+        buf = buf.with_bucket_caps(caps);
+    }
+    let _ = BUFFER.set(buf);
+}
+
+/// Returns the handle of the send loop, which finishes after draining on shutdown.
+pub async fn spawn_batcher(
+    cfg: Config,
+    crypto: Crypto,
+    tx_sock: Arc<dyn Transport>,
+    framer: crate::net::framing::Framer,
+) -> tokio::task::JoinHandle<()> {
     // 1) sensor ingress channel
     let (tx, mut rx) = mpsc::channel::<SensorReading>(1024);
     let _ = CHANNEL.set(tx);
@@ -37,17 +145,36 @@ pub async fn spawn_batcher(cfg: Config, crypto: Crypto, tx_sock: Arc<UdpSocket>,
     let (em_tx, mut em_rx) = mpsc::channel::<EmergencyData>(32);
     let _ = EMER_TX.set(em_tx);
 
+    // 1c) replay channel (DataRequest)
+    let (replay_tx, mut replay_rx) = mpsc::channel::<Vec<SensorReading>>(8);
+    let _ = REPLAY_TX.set(replay_tx);
+
     // 2) bounded priority buffer
     if BUFFER.get().is_none() {
-        init_priority_buffer(cfg.max_batch * 8);
+        init_priority_buffer(cfg.max_batch * 8, cfg.aging_ms, cfg.drop_policy, cfg.bucket_caps);
     }
     let buf = BUFFER.get().unwrap().clone();
+    let live = LIVE.get_or_init(|| LiveConfig::new(cfg.clone())).clone();
+    // ingest → batcher: the buffer just crossed the flush high-water mark
+    let flush = Arc::new(Notify::new());
+    // ingest → fast-path sender: Emergency readings, one packet each
+    let (fast_tx, fast_rx) = mpsc::channel::<SensorReading>(64);
+    let fast_tx = cfg.emergency_fast_path.then_some(fast_tx);
 
     // 3) Ingest: sensors → bounded buffer (with drop logging)
     tokio::spawn({
         let buf = buf.clone();
+        let flush = flush.clone();
+        let mut ds = Downsampler::new(cfg.downsample_fill_pct);
+        let mut hw = HighWater::new(cfg.flush_fill_pct);
+        let live = live.clone();
         async move {
             while let Some(mut r) = rx.recv().await {
+                {
+                    let cfg = live.get();
+                    ds.threshold_pct = cfg.downsample_fill_pct;
+                    hw.mark_pct = cfg.flush_fill_pct;
+                }
                 // compute read→ingest latency
                 let now = chrono::Utc::now();
                 let dt_ms = (now - r.timestamp)
@@ -55,105 +182,483 @@ pub async fn spawn_batcher(cfg: Config, crypto: Crypto, tx_sock: Arc<UdpSocket>,
                     .map(|us| us as f64 / 1000.0)
                     .unwrap_or(0.0);
                 r.processing_latency_ms = dt_ms;
+                logging::metrics::LATENCY.record(dt_ms);
+                logging::metrics::SUMMARY_LATENCY.record(dt_ms);
 
-                // Insert into bounded buffer; if dropped, log it
-                match buf.push(r).await {
-                    InsertResult::Accepted => {}
-                    InsertResult::Dropped {
-                        dropped_priority, ..
-                    } => {
-                        let prio = format!("{:?}", dropped_priority).to_lowercase();
-                        logging::csv::log_drop(&prio, 1).await;
-                    }
+                route(&buf, &mut ds, fast_tx.as_ref(), r).await;
+                if hw.crossed(buf.fill_pct().await) {
+                    flush.notify_one();
                 }
             }
         }
     });
 
-    // 3b) Emergency sender: send EmergencyData immediately
+    // 3a) Buffer stats for dashboards (depths + cumulative drops)
+    tokio::spawn({
+        let buf = buf.clone();
+        async move {
+            let mut ticker = time::interval(Duration::from_secs(1));
+            loop {
+                ticker.tick().await;
+                let s = buf.stats().await;
+                info!(
+                    event = "buffer_stats",
+                    hi = s.hi,
+                    im = s.im,
+                    lo = s.lo,
+                    dropped_hi = s.total_dropped_hi,
+                    dropped_im = s.total_dropped_im,
+                    dropped_lo = s.total_dropped_lo,
+                );
+            }
+        }
+    });
+
+    // 3b) Emergency sender: send EmergencyData immediately (repeats coalesced)
     {
         let crypto = crypto.clone();
         let tx_sock = tx_sock.clone();
+        let mut coalescer = EmergencyCoalescer::new(Duration::from_millis(cfg.emergency_coalesce_ms));
+        let (repeats, repeat_high) = (cfg.emergency_repeats, cfg.emergency_repeat_high);
+        let spacing = Duration::from_millis(cfg.emergency_repeat_ms);
         tokio::spawn(async move {
             while let Some(em) = em_rx.recv().await {
-                let pkt = CommunicationPacket::new_emergency(em, Source::Satellite);
-                if let Ok(bytes) = crypto.seal(&pkt) {
-                    // peek header for pretty logs
-                    log_frame_header(&bytes);
-                    let _ = tx_sock.send(&bytes).await;
+                let Some(em) = coalescer.admit(em, time::Instant::now()) else {
+                    continue; // duplicate inside the window; counted for the next one
+                };
+                let copies = emergency_copies(em.severity, repeats, repeat_high);
+                if copies > 1 {
+                    // repeats are spaced out; don't hold up the next alert behind them
+                    info!(alert_type = %em.alert_type, copies, "emergency: repeating over udp");
+                    let (crypto, tx_sock) = (crypto.clone(), tx_sock.clone());
+                    tokio::spawn(async move { send_emergency(&crypto, tx_sock.as_ref(), em, copies, spacing).await });
+                } else {
+                    send_emergency(&crypto, tx_sock.as_ref(), em, 1, spacing).await;
                 }
             }
         });
     }
 
-    // 4) Batcher: every batch_ms, pop by priority and send
+    // 3b') Emergency fast path: readings bypass the buffer and batch timer
+    tokio::spawn(run_fast_path(live.clone(), crypto.clone(), tx_sock.clone(), buf.clone(), framer.clone(), fast_rx));
+
+    // 3c) Read→ingest latency percentiles, one latency.csv row per window with samples
+    tokio::spawn(async move {
+        const WINDOW_MS: u64 = 1000;
+        let mut ticker = time::interval(Duration::from_millis(WINDOW_MS));
+        loop {
+            ticker.tick().await;
+            let counts = logging::metrics::LATENCY.take();
+            let pct = |p| logging::metrics::percentile(&counts, p);
+            if let (Some(p50), Some(p95), Some(p99)) = (pct(50.0), pct(95.0), pct(99.0)) {
+                let n = counts.iter().sum();
+                logging::csv::log_latency(WINDOW_MS, n, p50, p95, p99).await;
+            }
+        }
+    });
+
+    // 3d) Replay sender: readings re-requested by ground go out immediately
     {
         let crypto = crypto.clone();
         let tx_sock = tx_sock.clone();
-        let buf_for_send = buf.clone();
         tokio::spawn(async move {
-            let mut batch = Vec::with_capacity(cfg.max_batch);
-            let mut ticker = time::interval(Duration::from_millis(cfg.batch_ms));
+            while let Some(readings) = replay_rx.recv().await {
+                send_replay(&crypto, tx_sock.as_ref(), readings).await;
+            }
+        });
+    }
 
-            loop {
-                tokio::select! {
-                    _ = ticker.tick() => {
-                        if !batch.is_empty() {
-                            send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
-                        } else {
-                            let pull = buf_for_send.pop_many(cfg.max_batch).await;
-                            if !pull.is_empty() {
-                                batch.extend(pull);
-                                send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
-                            }
-                        }
+    // 4) Batcher: every batch_ms (or at once past the high-water mark), pop by priority and send
+    tokio::spawn(run_sender(live, crypto, tx_sock, buf, framer, flush, crate::shutdown::subscribe()))
+}
+
+/// Sends each fast-path reading in its own packet as soon as it arrives (still through
+/// the downlink gate: a closed window defers it to the buffer like any batch).
+async fn run_fast_path(
+    live: LiveConfig,
+    crypto: Crypto,
+    sock: Arc<dyn Transport>,
+    buf: BufferHandle,
+    framer: crate::net::framing::Framer,
+    mut rx: mpsc::Receiver<SensorReading>,
+) {
+    while let Some(r) = rx.recv().await {
+        info!(sensor_id = r.sensor_id, seq = r.sequence_number, "emergency reading: fast path");
+        send(&live.get(), &crypto, &sock, &buf, &mut vec![r], &framer).await;
+    }
+}
+
+/// Reads the live config on every pass; a new batch_ms re-arms the ticker at once.
+async fn run_sender(
+    live: LiveConfig,
+    crypto: Crypto,
+    tx_sock: Arc<dyn Transport>,
+    buf_for_send: BufferHandle,
+    framer: crate::net::framing::Framer,
+    flush: Arc<Notify>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    let mut cfg_rx = live.subscribe();
+    let mut batch_ms = live.get().batch_ms;
+    let mut batch = Vec::new();
+    let mut ticker = time::interval(Duration::from_millis(batch_ms));
+    // latency SLA: look at the oldest reading once per max_age period (0 = off)
+    let max_age_ms = live.get().max_age_ms;
+    let mut age_check = time::interval(Duration::from_millis(max_age_ms.max(1)));
+
+    loop {
+        let cfg = live.get();
+        tokio::select! {
+            Ok(()) = cfg_rx.changed() => {
+                let now_ms = cfg_rx.borrow_and_update().batch_ms;
+                if now_ms != batch_ms {
+                    info!(from = batch_ms, to = now_ms, "batch period changed");
+                    batch_ms = now_ms;
+                    let period = Duration::from_millis(batch_ms);
+                    ticker = time::interval_at(time::Instant::now() + period, period);
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                drain(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+                break;
+            }
+            _ = flush.notified() => {
+                flush_burst(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+            }
+            _ = window_closing() => {
+                let dl = crate::downlink::DL.get();
+                final_flush(dl, &cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+            }
+            _ = age_check.tick(), if max_age_ms > 0 => {
+                let oldest = batch.iter().map(|r| r.timestamp).chain(buf_for_send.oldest_timestamp().await).min();
+                if oldest.is_some_and(|t| age_ms(Utc::now(), t) > max_age_ms as f64) {
+                    if batch.is_empty() {
+                        batch.extend(pop_next(&buf_for_send, &cfg).await);
                     }
-                    else => {
-                        let pull = buf_for_send.pop_many(cfg.max_batch).await;
-                        if !pull.is_empty() {
-                            batch.extend(pull);
-                            if batch.len() >= cfg.max_batch {
-                                send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
-                            }
-                        }
-                        tokio::time::sleep(Duration::from_millis(1)).await;
+                    // goes out if the window allows; otherwise send_via reports the SLA miss
+                    send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+                }
+            }
+            _ = ticker.tick() => {
+                if !batch.is_empty() {
+                    send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+                } else {
+                    let pull = pop_next(&buf_for_send, &cfg).await;
+                    if !pull.is_empty() {
+                        batch.extend(pull);
+                        send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
                     }
                 }
             }
-        });
+            else => {
+                let pull = pop_next(&buf_for_send, &cfg).await;
+                if !pull.is_empty() {
+                    batch.extend(pull);
+                    if batch.len() >= cfg.max_batch {
+                        send(&cfg, &crypto, &tx_sock, &buf_for_send, &mut batch, &framer).await;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    }
+}
+
+/// Pre-close signal from the downlink (never resolves without one).
+async fn window_closing() {
+    match crate::downlink::DL.get() {
+        Some(dl) => dl.closing().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// End-of-window flush: everything buffered goes out as one batch while the window is
+/// still open (split into several packets only if oversized), instead of waiting a
+/// whole interval for the next pass.
+async fn final_flush(
+    dl: Option<&crate::downlink::Downlink>,
+    cfg: &Config,
+    crypto: &Crypto,
+    sock: &Arc<dyn Transport>,
+    buf: &BufferHandle,
+    batch: &mut Vec<SensorReading>,
+    framer: &crate::net::framing::Framer,
+) {
+    batch.extend(buf.pop_many(buf.len().await).await);
+    if batch.is_empty() {
+        return;
+    }
+    let time_left_ms = match dl {
+        Some(dl) => dl.time_to_close().await.map_or(0.0, |d| d.as_secs_f64() * 1e3),
+        None => 0.0,
+    };
+    info!(readings = batch.len(), time_left_ms = format_args!("{time_left_ms:.1}"), "downlink closing: final flush");
+    send_via(dl, cfg, crypto, sock, buf, batch, framer).await;
+}
+
+/// High-water flush: send batches until the fill is back under the mark, without
+/// waiting for the tick. Stops early if the downlink defers (the buffer doesn't shrink).
+async fn flush_burst(
+    cfg: &Config,
+    crypto: &Crypto,
+    sock: &Arc<dyn Transport>,
+    buf: &BufferHandle,
+    batch: &mut Vec<SensorReading>,
+    framer: &crate::net::framing::Framer,
+) {
+    loop {
+        if batch.is_empty() {
+            batch.extend(pop_next(buf, cfg).await);
+        }
+        if batch.is_empty() {
+            return;
+        }
+        let before = buf.len().await + batch.len();
+        send(cfg, crypto, sock, buf, batch, framer).await;
+        if buf.len().await >= before || buf.fill_pct().await < cfg.flush_fill_pct {
+            return;
+        }
+    }
+}
+
+/// Next batch from the buffer: per-priority caps when configured, otherwise the
+/// lower-priority quota.
+async fn pop_next(buf: &BufferHandle, cfg: &Config) -> Vec<SensorReading> {
+    if cfg.batch_caps.is_unbounded() {
+        buf.pop_batch_with_quota(cfg.max_batch, cfg.lower_quota_pct).await
+    } else {
+        buf.pop_many_balanced(cfg.max_batch, cfg.batch_caps).await
+    }
+}
+
+/// Rising edge of the fill level over `mark_pct`: fires once per crossing and re-arms
+/// when the fill drops back below. A mark >= 100 never fires.
+struct HighWater {
+    mark_pct: f64,
+    above: bool,
+}
+
+impl HighWater {
+    fn new(mark_pct: f64) -> Self {
+        Self { mark_pct, above: false }
+    }
+
+    fn crossed(&mut self, fill_pct: f64) -> bool {
+        let above = self.mark_pct < 100.0 && fill_pct >= self.mark_pct;
+        let rising = above && !self.above;
+        self.above = above;
+        rising
+    }
+}
+
+/// Shutdown: send the partial batch and the rest of the buffer while the downlink
+/// accepts it; whatever is deferred stays buffered (and is reported).
+async fn drain(
+    cfg: &Config,
+    crypto: &Crypto,
+    sock: &Arc<dyn Transport>,
+    buf: &BufferHandle,
+    batch: &mut Vec<SensorReading>,
+    framer: &crate::net::framing::Framer,
+) {
+    let mut sent = 0;
+    loop {
+        if batch.is_empty() {
+            // everything goes out anyway; no need for the lower-priority quota
+            batch.extend(buf.pop_many(cfg.max_batch).await);
+        }
+        if batch.is_empty() {
+            break;
+        }
+        let n = batch.len();
+        let before = buf.len().await;
+        send(cfg, crypto, sock, buf, batch, framer).await;
+        let left = buf.len().await;
+        if left > before {
+            tracing::warn!(left, "shutdown: downlink unavailable; readings left in buffer");
+            break;
+        }
+        sent += n;
+    }
+    info!(sent, "shutdown: telemetry drained");
+}
+
+/// Largest decimation applied to Normal readings (1 in N kept).
+const MAX_DECIMATION: u64 = 8;
+
+/// Thins Normal readings once the buffer is past `threshold_pct`: 1 in 2 at the
+/// threshold, one more skipped per extra 10% of fill, up to 1 in `MAX_DECIMATION`.
+struct Downsampler {
+    threshold_pct: f64,
+    normal_seen: u64,
+}
+
+impl Downsampler {
+    fn new(threshold_pct: f64) -> Self {
+        Self { threshold_pct, normal_seen: 0 }
+    }
+
+    fn factor(&self, fill_pct: f64) -> u64 {
+        if self.threshold_pct >= 100.0 || fill_pct < self.threshold_pct {
+            return 1;
+        }
+        (2 + ((fill_pct - self.threshold_pct) / 10.0) as u64).min(MAX_DECIMATION)
+    }
+
+    /// `Some(n)` if this reading should be discarded under 1-in-n decimation.
+    fn discard(&mut self, r: &SensorReading, fill_pct: f64) -> Option<u64> {
+        if r.priority != Priority::Normal {
+            return None; // Critical/Important always go through
+        }
+        let n = self.factor(fill_pct);
+        let keep = self.normal_seen.is_multiple_of(n);
+        self.normal_seen = self.normal_seen.wrapping_add(1);
+        (!keep).then_some(n)
     }
 }
 
+/// Range-check, record for replay, downsample, then insert into the bounded buffer;
+/// every lost reading is logged.
+/// Emergency readings skip the buffer when the fast path is on; everything else is buffered.
+async fn route(buf: &BufferHandle, ds: &mut Downsampler, fast: Option<&mpsc::Sender<SensorReading>>, r: SensorReading) {
+    match fast {
+        Some(fast) if r.priority == Priority::Emergency => {
+            if accept(&r).await {
+                let _ = fast.send(r).await;
+            }
+        }
+        _ => ingest(buf, ds, r).await,
+    }
+}
+
+/// Range check (dropping and logging bad readings), then keep a copy for DataRequest replays.
+async fn accept(r: &SensorReading) -> bool {
+    if let Err(reason) = r.validate_ranges() {
+        tracing::warn!(sensor_id = r.sensor_id, seq = r.sequence_number, %reason, "invalid reading dropped");
+        log_drop(&format!("{:?}", r.priority).to_lowercase(), "invalid").await;
+        return false;
+    }
+    super::history::record(r);
+    true
+}
+
+async fn ingest(buf: &BufferHandle, ds: &mut Downsampler, r: SensorReading) {
+    if !accept(&r).await {
+        return;
+    }
+    if !degrade::admit(r.priority) {
+        let prio = format!("{:?}", r.priority).to_lowercase();
+        log_drop(&prio, &format!("degrade_{}", degrade::level().as_str())).await;
+        return;
+    }
+    if let Some(n) = ds.discard(&r, buf.fill_pct().await) {
+        log_drop("normal", &format!("downsample_1_in_{n}")).await;
+        return;
+    }
+    log_insert(buf.push(r).await).await;
+}
+
+/// Log whichever reading a full buffer cost: an evicted one or the rejected newcomer.
+async fn log_insert(res: InsertResult) {
+    match res {
+        InsertResult::Accepted => {}
+        InsertResult::Dropped { dropped_priority, .. } => {
+            log_drop(&format!("{:?}", dropped_priority).to_lowercase(), "overflow").await;
+        }
+        InsertResult::Rejected { priority } => {
+            log_drop(&format!("{:?}", priority).to_lowercase(), "rejected").await;
+        }
+    }
+}
+
+/// One lost reading: drops.csv row plus a flight-recorder entry.
+async fn log_drop(prio: &str, reason: &str) {
+    blackbox::record(Event::new(EventKind::Drop, prio, reason));
+    logging::metrics::record_drop(prio);
+    logging::csv::log_drop(prio, 1, reason).await;
+}
+
 async fn send(
     cfg: &Config,
     crypto: &Crypto,
-    sock: &Arc<UdpSocket>,
+    sock: &Arc<dyn Transport>,
+    buf: &BufferHandle,
+    batch: &mut Vec<SensorReading>,
+    framer: &crate::net::framing::Framer,
+) {
+    let dl = crate::downlink::DL.get();
+    send_via(dl, cfg, crypto, sock, buf, batch, framer).await
+}
+
+/// Store-and-forward: readings that can't go out now are put back at the front of the
+/// priority buffer, ahead of newer samples (lost only if it overflows). Returns how many
+/// were deferred.
+async fn defer(buf: &BufferHandle, batch: &mut Vec<SensorReading>) -> usize {
+    let n = batch.len();
+    for res in buf.push_front_batch(std::mem::take(batch)).await {
+        log_insert(res).await;
+    }
+    n
+}
+
+/// Sample age in ms at `now`.
+fn age_ms(now: chrono::DateTime<Utc>, sampled: chrono::DateTime<Utc>) -> f64 {
+    (now - sampled).num_microseconds().unwrap_or(0) as f64 / 1000.0
+}
+
+/// A batch held back while its oldest reading is already past `max_age_ms`.
+fn check_age_sla(cfg: &Config, oldest_ms: f64, gate: &str) {
+    if cfg.max_age_ms > 0 && oldest_ms > cfg.max_age_ms as f64 {
+        logging::metrics::count(&logging::metrics::AGE_SLA_VIOLATIONS);
+        blackbox::record(Event::new(EventKind::DownlinkMiss, "latency_sla", format!("oldest {oldest_ms:.0}ms")));
+        tracing::warn!(
+            oldest_ms = format_args!("{oldest_ms:.1}"),
+            max_age_ms = cfg.max_age_ms,
+            gate,
+            "latency SLA violated: downlink unavailable"
+        );
+    }
+}
+
+async fn send_via(
+    dl: Option<&crate::downlink::Downlink>,
+    cfg: &Config,
+    crypto: &Crypto,
+    sock: &Arc<dyn Transport>,
     buf: &BufferHandle,
     batch: &mut Vec<SensorReading>,
     framer: &crate::net::framing::Framer,
 ) {
-    // Compute queue latency (oldest sample age)
+    // Compute queue latency (oldest and mean sample age)
     let now = chrono::Utc::now();
-    let oldest_ms = batch
-        .iter()
-        .map(|r| (now - r.timestamp).num_microseconds().unwrap_or(0) as f64 / 1000.0)
-        .fold(0.0_f64, f64::max);
+    let ages: Vec<f64> = batch.iter().map(|r| age_ms(now, r.timestamp)).collect();
+    let oldest_ms = ages.iter().copied().fold(0.0_f64, f64::max);
+    let avg_ms = if ages.is_empty() { 0.0 } else { ages.iter().sum::<f64>() / ages.len() as f64 };
 
     // Buffer fill percent (for degraded mode)
     let fill_pct = buf.fill_pct().await;
 
     // Downlink gate: must be within window + init ≤ 5ms + prep ≤ 30ms
-    let gate = if let Some(dl) = crate::downlink::DL.get() {
+    let gate = if let Some(dl) = dl {
         dl.pre_send().await
     } else {
         crate::downlink::DownlinkEvent::Ready
     };
+    // sustained fill and a struggling link walk the degradation ladder
+    let link_ok = !matches!(
+        gate,
+        crate::downlink::DownlinkEvent::MissedInit | crate::downlink::DownlinkEvent::ReadyPrepLate { .. }
+    );
+    let level = degrade::observe(fill_pct, link_ok);
 
     match gate {
         crate::downlink::DownlinkEvent::MissedInit => {
-            // treat as missed comms; don't send this batch
-            logging::csv::log_tx_queue(oldest_ms, fill_pct).await;
-            batch.clear();
+            // missed comms for this pass; hold the batch for the next window
+            check_age_sla(cfg, oldest_ms, gate.as_str());
+            let deferred = defer(buf, batch).await;
+            blackbox::record(Event::new(EventKind::DownlinkMiss, "downlink", format!("init missed; {deferred} deferred")));
+            logging::csv::log_tx_queue(oldest_ms, fill_pct, deferred).await;
+            logging::csv::log_downlink(deferred, avg_ms, oldest_ms, fill_pct, gate.as_str(), 0.0).await;
             return;
         }
         crate::downlink::DownlinkEvent::ReadyPrepLate { prep_ms } => {
@@ -164,21 +669,37 @@ async fn send(
             tracing::warn!("downlink: degraded mode active");
         }
         crate::downlink::DownlinkEvent::NotInWindow => {
-            // optional: you can buffer until next tick; here we just skip send
-            logging::csv::log_tx_queue(oldest_ms, fill_pct).await;
+            // no contact: hold the batch until the window reopens
+            check_age_sla(cfg, oldest_ms, gate.as_str());
+            let deferred = defer(buf, batch).await;
+            logging::csv::log_tx_queue(oldest_ms, fill_pct, deferred).await;
             return;
         }
+        crate::downlink::DownlinkEvent::Reacquired => {
+            info!("downlink: sending first batch after missed pass");
+        }
         crate::downlink::DownlinkEvent::Ready => {}
     }
 
-    // Build telemetry packet + encrypt
-    let pkt = CommunicationPacket::new_telemetry(batch.clone(), Source::Satellite);
-    if let Ok(bytes) = crypto.seal(&pkt) {
-        // log encrypted frame header
-        log_frame_header(&bytes);
+    // Build telemetry packet(s) + encrypt; an oversized batch goes out in several.
+    // Readings travel compacted; ground rebuilds the derived fields.
+    let wire: Vec<SensorReading> = batch.iter().cloned().map(|mut r| { r.compact(); r }).collect();
+    let sealed: Result<Vec<_>, _> = CommunicationPacket::new_telemetry_split(wire, Source::Satellite)
+        .iter()
+        .map(|pkt| crypto.seal(pkt))
+        .collect();
+    if let Err(e) = &sealed {
+        tracing::warn!(%e, readings = batch.len(), "telemetry seal failed; batch dropped");
+    }
+    if let Ok(frames) = sealed {
+        for bytes in &frames {
+            // log encrypted frame header
+            log_frame_header(bytes);
 
-        // send
-        let _ = sock.send(&bytes).await;
+            // send
+            let _ = sock.send(bytes).await;
+            logging::metrics::count(&logging::metrics::BATCHES_SENT);
+        }
 
         // priority counts for logs
         let (mut c, mut i, mut n) = (0, 0, 0);
@@ -190,27 +711,51 @@ async fn send(
             }
         }
         logging::csv::log_batch(batch.len(), c, i, n).await;
-        logging::csv::log_tx_queue(oldest_ms, fill_pct).await;
+        logging::csv::log_tx_queue(oldest_ms, fill_pct, 0).await;
+        logging::csv::log_downlink(batch.len(), avg_ms, oldest_ms, fill_pct, gate.as_str(), 0.0).await;
         info!(
             "tx telemetry: total={} (critical={}, important={}, normal={}), queue_oldest_ms={:.3}, fill_pct={:.1}",
             batch.len(), c, i, n, oldest_ms, fill_pct
         );
 
-        // Degraded mode trigger
-        if fill_pct >= 80.0 {
-            if let Some(dl) = crate::downlink::DL.get() {
-                dl.set_degraded(true).await;
-            }
-        } else {
-            if let Some(dl) = crate::downlink::DL.get() {
-                dl.set_degraded(false).await;
-            }
+        // Degraded link mode from Minimal upward
+        if let Some(dl) = crate::downlink::DL.get() {
+            dl.set_degraded(level >= Level::Minimal).await;
         }
     }
 
     batch.clear();
 }
 
+/// UDP copies for an emergency of `severity`: Critical (and High with `repeat_high`)
+/// alerts go out `repeats` times so one lost datagram doesn't lose them; the rest once.
+fn emergency_copies(severity: Severity, repeats: u32, repeat_high: bool) -> u32 {
+    match severity {
+        Severity::Critical => repeats.max(1),
+        Severity::High if repeat_high => repeats.max(1),
+        _ => 1,
+    }
+}
+
+/// Seal `em` once and send the same frame `copies` times, `spacing` apart; ground keeps
+/// the first copy and rejects the rest as replays. Returns the copies sent.
+async fn send_emergency(crypto: &Crypto, sock: &dyn Transport, em: EmergencyData, copies: u32, spacing: Duration) -> u32 {
+    let pkt = CommunicationPacket::new_emergency(em, Source::Satellite);
+    let Ok(bytes) = crypto.seal(&pkt) else { return 0 };
+    // peek header for pretty logs
+    log_frame_header(&bytes);
+    let mut sent = 0;
+    for i in 0..copies {
+        if i > 0 {
+            time::sleep(spacing).await;
+        }
+        if sock.send(&bytes).await.is_ok() {
+            sent += 1;
+        }
+    }
+    sent
+}
+
 fn log_frame_header(bytes: &[u8]) {
     if bytes.len() < 4 {
         return;
@@ -219,7 +764,7 @@ fn log_frame_header(bytes: &[u8]) {
     if bytes.len() < 4 + len {
         return;
     }
-    if let Ok(frame) = serde_json::from_slice::<EncryptedFrame>(&bytes[4..4 + len]) {
+    if let Ok(frame) = EncryptedFrame::from_bytes(&bytes[4..4 + len]) {
         info!(
             event = "tx_frame",
             pkt_type = ?frame.header.packet_type,
@@ -233,3 +778,438 @@ fn log_frame_header(bytes: &[u8]) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::udp::TxSocket;
+    use tokio::net::UdpSocket;
+    use shared_protocol::ThermalSensor;
+
+    #[tokio::test]
+    async fn closed_window_keeps_readings_buffered() {
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let sock = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sock.connect(sock.local_addr().unwrap()).await.unwrap();
+        let sock: Arc<dyn Transport> = Arc::new(TxSocket::from(sock));
+
+        let buf = BufferHandle::new(16);
+        let dl = crate::downlink::Downlink::new(); // starts closed
+        let thermal = ThermalSensor::new(1, "CPU");
+        let mut batch: Vec<_> = (0..3).map(|i| thermal.create_reading(65.0, i)).collect();
+
+        send_via(Some(&dl), &cfg, &crypto, &sock, &buf, &mut batch, &Default::default()).await;
+
+        assert!(batch.is_empty());
+        assert_eq!(buf.len().await, 3);
+    }
+
+    #[tokio::test]
+    async fn aged_reading_behind_a_closed_window_is_an_sla_violation() {
+        let mut cfg = Config::test_default();
+        cfg.max_age_ms = 100; // the only test that enables the SLA
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let sock = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sock.connect(sock.local_addr().unwrap()).await.unwrap();
+        let sock: Arc<dyn Transport> = Arc::new(TxSocket::from(sock));
+        let buf = BufferHandle::new(16);
+        let dl = crate::downlink::Downlink::new(); // closed
+        let mut old = ThermalSensor::new(1, "CPU").create_reading(65.0, 0);
+        old.timestamp -= chrono::Duration::seconds(2);
+
+        let before = logging::metrics::AGE_SLA_VIOLATIONS.load(Ordering::Relaxed);
+        send_via(Some(&dl), &cfg, &crypto, &sock, &buf, &mut vec![old], &Default::default()).await;
+        assert_eq!(logging::metrics::AGE_SLA_VIOLATIONS.load(Ordering::Relaxed), before + 1);
+        assert_eq!(buf.len().await, 1, "still held for the next window");
+        assert!(buf.oldest_timestamp().await.is_some_and(|t| age_ms(Utc::now(), t) >= 2000.0));
+    }
+
+    #[tokio::test]
+    async fn normal_readings_are_decimated_at_high_fill() {
+        let buf = BufferHandle::new(1000);
+        let thermal = ThermalSensor::new(1, "CPU");
+        for i in 0..900 {
+            buf.push(thermal.create_reading(82.0, i)).await; // Critical
+        }
+        // 90% fill, threshold 60% → keep 1 in 5
+        let mut ds = Downsampler::new(60.0);
+        let power = shared_protocol::PowerSensor::new(2, "Main Bus");
+        for i in 0..100 {
+            ingest(&buf, &mut ds, power.create_reading(90.0, 12.0, 2.0, 24.0, i)).await;
+        }
+        assert_eq!(buf.len().await, 900 + 20);
+
+        // Critical readings are never thinned
+        ingest(&buf, &mut ds, thermal.create_reading(82.0, 900)).await;
+        ingest(&buf, &mut ds, thermal.create_reading(82.0, 901)).await;
+        assert_eq!(buf.len().await, 922);
+    }
+
+    #[tokio::test]
+    async fn impossible_readings_are_dropped_at_ingest() {
+        let buf = BufferHandle::new(10);
+        let mut ds = Downsampler::new(100.0);
+        let power = shared_protocol::PowerSensor::new(871, "Ingest Check"); // unique id: history is process-wide
+        ingest(&buf, &mut ds, power.create_reading(f64::NAN, 12.0, 1.0, 12.0, 0)).await;
+        ingest(&buf, &mut ds, power.create_reading(50.0, f64::INFINITY, 1.0, 12.0, 1)).await;
+        ingest(&buf, &mut ds, power.create_reading(140.0, 12.0, 1.0, 12.0, 2)).await;
+        ingest(&buf, &mut ds, power.create_reading(50.0, 12.0, 1.0, 12.0, 3)).await;
+
+        assert_eq!(buf.len().await, 1);
+        let kept: Vec<u64> = super::super::history::recent(871, 10).iter().map(|r| r.sequence_number).collect();
+        assert_eq!(kept, [3]);
+        logging::csv::flush_all().await;
+        let drops = std::fs::read_to_string(crate::logging::csv::log_path("drops.csv")).unwrap();
+        assert!(drops.lines().filter(|l| l.ends_with(",1,invalid")).count() >= 3, "{drops}");
+    }
+
+    #[tokio::test]
+    async fn burst_past_high_water_is_sent_before_the_tick() {
+        let mut cfg = Config::test_default();
+        cfg.batch_ms = 60_000; // only the first, immediate tick fires during the test
+        cfg.max_batch = 20;
+        cfg.flush_fill_pct = 50.0;
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+
+        let buf = BufferHandle::new(100);
+        let flush = Arc::new(Notify::new());
+        let (_stop_tx, stop_rx) = broadcast::channel(1);
+        tokio::spawn(run_sender(
+            LiveConfig::new(cfg.clone()),
+            crypto.clone(),
+            Arc::new(TxSocket::from(sat)),
+            buf.clone(),
+            crate::net::framing::Framer,
+            flush.clone(),
+            stop_rx,
+        ));
+        time::sleep(Duration::from_millis(20)).await;
+
+        // same path as the ingest task
+        let mut ds = Downsampler::new(cfg.downsample_fill_pct);
+        let mut hw = HighWater::new(cfg.flush_fill_pct);
+        let thermal = ThermalSensor::new(1, "CPU");
+        let mut signals = 0;
+        for i in 0..60 {
+            ingest(&buf, &mut ds, thermal.create_reading(82.0, i)).await;
+            if hw.crossed(buf.fill_pct().await) {
+                signals += 1;
+                flush.notify_one();
+            }
+        }
+        assert_eq!(signals, 1);
+
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let mut dgram = vec![0u8; 64 * 1024];
+        let frame = loop {
+            let n = time::timeout(Duration::from_secs(2), ground.recv(&mut dgram))
+                .await
+                .expect("no send before the tick")
+                .unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let shared_protocol::PacketPayload::TelemetryData(got) = crypto.open(&frame).unwrap().payload else {
+            panic!("expected telemetry");
+        };
+        assert_eq!(got.len(), 20);
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(buf.fill_pct().await < 50.0, "flushed back under the mark");
+    }
+
+    #[tokio::test]
+    async fn config_update_retunes_the_tick_all_or_nothing() {
+        let mut cfg = Config::test_default();
+        cfg.batch_ms = 60_000;
+        cfg.flush_fill_pct = 100.0;
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+
+        let live = LiveConfig::new(cfg);
+        let buf = BufferHandle::new(100);
+        let (_stop_tx, stop_rx) = broadcast::channel(1);
+        tokio::spawn(run_sender(
+            live.clone(),
+            crypto.clone(),
+            Arc::new(TxSocket::from(sat)),
+            buf.clone(),
+            crate::net::framing::Framer,
+            Arc::new(Notify::new()),
+            stop_rx,
+        ));
+        time::sleep(Duration::from_millis(20)).await; // immediate first tick: nothing buffered yet
+        let thermal = ThermalSensor::new(1, "CPU");
+        for i in 0..4 {
+            buf.push(thermal.create_reading(65.0, i)).await;
+        }
+        let mut dgram = vec![0u8; 64 * 1024];
+        let recv = time::timeout(Duration::from_millis(150), ground.recv(&mut dgram)).await;
+        assert!(recv.is_err(), "sent before the 60 s tick");
+
+        let bad = [("batch_ms", "20"), ("max_batch", "lots")];
+        let bad = bad.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert!(live.apply(&bad).unwrap_err().contains("max_batch"));
+        assert_eq!(live.get().batch_ms, 60_000, "rejected update left no trace");
+
+        let good = [("batch_ms", "20"), ("max_batch", "8")];
+        live.apply(&good.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()).unwrap();
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let frame = loop {
+            let n = time::timeout(Duration::from_millis(500), ground.recv(&mut dgram))
+                .await
+                .expect("new tick period not picked up")
+                .unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let shared_protocol::PacketPayload::TelemetryData(got) = crypto.open(&frame).unwrap().payload else {
+            panic!("expected telemetry");
+        };
+        assert_eq!(got.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn critical_emergency_is_repeated_and_low_sent_once() {
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+        let sat = TxSocket::from(sat);
+        let alert = |severity| EmergencyData {
+            alert_id: format!("{severity:?}"),
+            severity,
+            alert_type: "thermal".into(),
+            description: String::new(),
+            affected_systems: vec![],
+            recommended_actions: vec![],
+            auto_recovery_attempted: false,
+            timestamp: Utc::now(),
+            metadata: Default::default(),
+        };
+        // whole frames until the link has been quiet for 100ms
+        async fn received(ground: &UdpSocket) -> usize {
+            let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+            let mut dgram = vec![0u8; 64 * 1024];
+            let mut n = 0;
+            while let Ok(len) = time::timeout(Duration::from_millis(100), ground.recv(&mut dgram)).await {
+                if reasm.push(&dgram[..len.unwrap()]).is_some() {
+                    n += 1;
+                }
+            }
+            n
+        }
+
+        let copies = emergency_copies(Severity::Critical, cfg.emergency_repeats, false);
+        assert_eq!(send_emergency(&crypto, &sat, alert(Severity::Critical), copies, Duration::from_millis(5)).await, 3);
+        assert_eq!(received(&ground).await, 3);
+
+        let copies = emergency_copies(Severity::Low, cfg.emergency_repeats, false);
+        assert_eq!(send_emergency(&crypto, &sat, alert(Severity::Low), copies, Duration::from_millis(5)).await, 1);
+        assert_eq!(received(&ground).await, 1);
+
+        assert_eq!(emergency_copies(Severity::High, 3, false), 1);
+        assert_eq!(emergency_copies(Severity::High, 3, true), 3);
+    }
+
+    #[tokio::test]
+    async fn emergency_reading_skips_the_buffer_and_the_tick() {
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+
+        // no run_sender: buffered readings would only leave on a tick
+        let buf = BufferHandle::new(100);
+        let (fast_tx, fast_rx) = mpsc::channel(8);
+        let framer = crate::net::framing::Framer;
+        let live = LiveConfig::new(cfg.clone());
+        tokio::spawn(run_fast_path(live, crypto.clone(), Arc::new(TxSocket::from(sat)), buf.clone(), framer, fast_rx));
+
+        let thermal = ThermalSensor::new(1, "CPU");
+        let mut ds = Downsampler::new(cfg.downsample_fill_pct);
+        let mut normal = thermal.create_reading(40.0, 0);
+        normal.priority = Priority::Normal;
+        let mut urgent = thermal.create_reading(95.0, 1);
+        urgent.priority = Priority::Emergency;
+        route(&buf, &mut ds, Some(&fast_tx), normal).await;
+        route(&buf, &mut ds, Some(&fast_tx), urgent.clone()).await;
+
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let mut dgram = vec![0u8; 64 * 1024];
+        let frame = loop {
+            let n = time::timeout(Duration::from_millis(500), ground.recv(&mut dgram)).await.unwrap().unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let shared_protocol::PacketPayload::TelemetryData(mut got) = crypto.open(&frame).unwrap().payload else {
+            panic!("expected telemetry");
+        };
+        got.iter_mut().for_each(SensorReading::expand); // sent compacted, as ground would rebuild it
+        assert_eq!(got, [urgent]);
+        assert_eq!(buf.len().await, 1, "the Normal reading waits for the tick");
+    }
+
+    #[tokio::test]
+    async fn imminent_close_flushes_the_whole_buffer_in_one_batch() {
+        let mut cfg = Config::test_default();
+        cfg.max_batch = 5;
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+        let sat: Arc<dyn Transport> = Arc::new(TxSocket::from(sat));
+
+        let buf = BufferHandle::new(100);
+        let thermal = ThermalSensor::new(1, "CPU");
+        for i in 0..17 {
+            buf.push(thermal.create_reading(65.0, i)).await;
+        }
+        let dl = crate::downlink::Downlink::new();
+        dl.open(800).await;
+        assert!(dl.time_to_close().await.is_some_and(|t| t <= Duration::from_millis(800)));
+        dl.announce_close();
+        time::timeout(Duration::from_millis(50), dl.closing()).await.expect("pre-close signal");
+
+        final_flush(Some(&dl), &cfg, &crypto, &sat, &buf, &mut Vec::new(), &Default::default()).await;
+        assert_eq!(buf.len().await, 0);
+
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let mut dgram = vec![0u8; 64 * 1024];
+        let frame = loop {
+            let n = time::timeout(Duration::from_secs(2), ground.recv(&mut dgram)).await.unwrap().unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let shared_protocol::PacketPayload::TelemetryData(got) = crypto.open(&frame).unwrap().payload else {
+            panic!("expected telemetry");
+        };
+        assert_eq!(got.len(), 17, "one batch past max_batch ({})", cfg.max_batch);
+    }
+
+    #[test]
+    fn high_water_fires_once_per_crossing() {
+        let mut hw = HighWater::new(50.0);
+        let fired: Vec<bool> = [10.0, 55.0, 70.0, 40.0, 50.0].iter().map(|&f| hw.crossed(f)).collect();
+        assert_eq!(fired, [false, true, false, false, true]);
+        assert!(!HighWater::new(100.0).crossed(100.0));
+    }
+
+    #[tokio::test]
+    async fn data_request_replays_recent_readings_in_one_packet() {
+        // the only test that installs the replay channel
+        let (replay_tx, mut replay_rx) = mpsc::channel(8);
+        REPLAY_TX.set(replay_tx).unwrap();
+
+        let sensor = ThermalSensor::new(962, "Replay Bay"); // unique id: history is process-wide
+        for seq in 0..5 {
+            super::super::history::record(&sensor.create_reading(40.0, seq));
+        }
+        let cmd = shared_protocol::Command::re_request_command(962, shared_protocol::SensorType::Thermal, "crc");
+        let (ack_tx, mut ack_rx) = mpsc::channel(4);
+        crate::commands::executor::execute(cmd, ack_tx).await;
+        let mut last = String::new();
+        while let Ok(ack) = ack_rx.try_recv() {
+            last = ack.status;
+        }
+        assert_eq!(last, "completed");
+
+        // the replay task's half: one sealed telemetry packet
+        let readings = replay_rx.try_recv().expect("replay queued");
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+        send_replay(&crypto, &TxSocket::from(sat), readings).await;
+
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let mut dgram = vec![0u8; 64 * 1024];
+        let frame = loop {
+            let n = time::timeout(Duration::from_secs(2), ground.recv(&mut dgram)).await.unwrap().unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let pkt = crypto.open(&frame).unwrap();
+        let shared_protocol::PacketPayload::TelemetryData(got) = pkt.payload else {
+            panic!("expected telemetry");
+        };
+        let seqs: Vec<u64> = got.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(seqs, [0, 1, 2, 3, 4]);
+        assert!(got.iter().all(|r| r.sensor_id == 962));
+
+        // nothing recorded → failed ACK
+        assert!(replay(963).unwrap_err().contains("no recent readings"));
+    }
+
+    #[tokio::test]
+    async fn snapshot_sends_one_packet_with_one_reading_per_sensor_type() {
+        // unique ids: history is process-wide; other tests may add newer readings of a type
+        super::super::history::record(&ThermalSensor::new(831, "Snap").create_reading(40.0, 0));
+        super::super::history::record(&ThermalSensor::new(832, "Snap").create_reading(41.0, 0));
+        super::super::history::record(&shared_protocol::PowerSensor::new(833, "Snap").create_reading(90.0, 12.0, 2.0, 24.0, 0));
+        super::super::history::record(&shared_protocol::AttitudeSensor::new(834, "Snap").create_reading(1.0, 2.0, 3.0, 0));
+        super::super::history::record(&shared_protocol::RadiationSensor::new(835, "Snap").create_reading(0.4, 5.0, 0));
+
+        let (tx, mut rx) = mpsc::channel(1);
+        assert_eq!(snapshot_to(&tx), Ok(4));
+
+        let cfg = Config::test_default();
+        let crypto = Crypto::from_config(&cfg).unwrap();
+        let ground = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let sat = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sat.connect(ground.local_addr().unwrap()).await.unwrap();
+        send_replay(&crypto, &TxSocket::from(sat), rx.try_recv().unwrap()).await;
+
+        let mut reasm = shared_protocol::Reassembler::new(Duration::from_secs(1));
+        let mut dgram = vec![0u8; 64 * 1024];
+        let frame = loop {
+            let n = time::timeout(Duration::from_secs(2), ground.recv(&mut dgram)).await.unwrap().unwrap();
+            if let Some(frame) = reasm.push(&dgram[..n]) {
+                break frame;
+            }
+        };
+        let shared_protocol::PacketPayload::TelemetryData(got) = crypto.open(&frame).unwrap().payload else {
+            panic!("expected telemetry");
+        };
+        use shared_protocol::SensorType::*;
+        assert_eq!(got.iter().map(|r| r.sensor_type).collect::<Vec<_>>(), [Thermal, Power, Attitude, Radiation]);
+        // nothing else was sent
+        assert!(time::timeout(Duration::from_millis(100), ground.recv(&mut dgram)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn full_ingress_drops_without_stalling_the_sampling_loop() {
+        // nobody drains this channel
+        let (tx, _rx) = mpsc::channel::<SensorReading>(4);
+        let sensor = ThermalSensor::new(1, "CPU");
+        let before = backpressure_count();
+
+        let period = Duration::from_millis(10);
+        let mut ticker = time::interval(period);
+        let started = time::Instant::now();
+        let mut full = 0;
+        for seq in 0..20 {
+            ticker.tick().await;
+            if try_enqueue_to(&tx, sensor.create_reading(40.0, seq)) == Err(IngressError::Full) {
+                full += 1;
+            }
+        }
+        // 20 ticks of 10 ms (the first is immediate): on schedule, not parked on a full queue
+        assert!(started.elapsed() < Duration::from_millis(400), "{:?}", started.elapsed());
+        assert_eq!(full, 16);
+        assert!(backpressure_count() >= before + 16);
+    }
+}
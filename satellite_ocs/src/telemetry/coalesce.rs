@@ -0,0 +1,86 @@
+// Suppress repeats of the same emergency (alert_type + affected_systems) inside a window
+use shared_protocol::{EmergencyData, Severity};
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+use tracing::debug;
+
+/// Metadata key carrying how many repeats were suppressed before this alert.
+pub const COALESCED_COUNT_KEY: &str = "coalesced_count";
+
+struct Seen {
+    sent_at: Instant,
+    severity: Severity,
+    suppressed: u32,
+}
+
+pub struct EmergencyCoalescer {
+    window: Duration, // zero disables coalescing
+    seen: HashMap<(String, Vec<String>), Seen>,
+}
+
+impl EmergencyCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self { window, seen: HashMap::new() }
+    }
+
+    /// `None` if `em` repeats an alert sent less than `window` ago at the same or
+    /// higher severity. Otherwise returns it, tagged with the suppressed count.
+    pub fn admit(&mut self, mut em: EmergencyData, now: Instant) -> Option<EmergencyData> {
+        if self.window.is_zero() {
+            return Some(em);
+        }
+        let key = (em.alert_type.clone(), em.affected_systems.clone());
+        if let Some(s) = self.seen.get_mut(&key) {
+            let escalated = em.severity as u8 > s.severity as u8;
+            if !escalated && now.duration_since(s.sent_at) < self.window {
+                s.suppressed += 1;
+                debug!(alert_type = %em.alert_type, suppressed = s.suppressed, "emergency coalesced");
+                return None;
+            }
+            if s.suppressed > 0 {
+                em.metadata.insert(COALESCED_COUNT_KEY.into(), s.suppressed.to_string());
+            }
+        }
+        self.seen.insert(key, Seen { sent_at: now, severity: em.severity, suppressed: 0 });
+        Some(em)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn alert(severity: Severity) -> EmergencyData {
+        EmergencyData {
+            alert_id: "a".into(),
+            severity,
+            alert_type: "thermal".into(),
+            description: String::new(),
+            affected_systems: vec!["thermal_management".into()],
+            recommended_actions: vec![],
+            auto_recovery_attempted: false,
+            timestamp: Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn identical_burst_is_sent_once_and_counted() {
+        let mut c = EmergencyCoalescer::new(Duration::from_secs(2));
+        let t0 = Instant::now();
+        let sent = (0..5)
+            .filter_map(|i| c.admit(alert(Severity::High), t0 + Duration::from_millis(i * 10)))
+            .count();
+        assert_eq!(sent, 1);
+
+        // escalation bypasses the window and carries the four suppressed repeats
+        let escalated = c.admit(alert(Severity::Critical), t0 + Duration::from_millis(60)).unwrap();
+        assert_eq!(escalated.metadata[COALESCED_COUNT_KEY], "4");
+
+        // after the window the next alert reports the suppressed repeats
+        c.admit(alert(Severity::High), t0 + Duration::from_millis(100));
+        let next = c.admit(alert(Severity::High), t0 + Duration::from_secs(3)).unwrap();
+        assert_eq!(next.metadata[COALESCED_COUNT_KEY], "1");
+    }
+}
@@ -0,0 +1,222 @@
+// Graceful degradation ladder: sustained overload sheds telemetry one rung at a time
+use once_cell::sync::Lazy;
+use shared_protocol::Priority;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::logging::blackbox::{self, Event, EventKind};
+
+/// Buffer fill (%) at which each rung above Normal is entered.
+const ENTER_PCT: [f64; 3] = [60.0, 80.0, 95.0];
+/// A rung is left only once fill is this far below its entry point.
+const HYSTERESIS_PCT: f64 = 15.0;
+/// An unhealthy downlink counts as this much extra fill when deciding to step up.
+const LINK_PENALTY_PCT: f64 = 10.0;
+/// A step up or down must be wanted continuously for this long before it is taken.
+const DWELL: Duration = Duration::from_secs(2);
+/// Important readings kept at Minimal: 1 in N.
+const IMPORTANT_DECIMATION: u64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Everything is sent.
+    Normal,
+    /// Normal readings are dropped.
+    Reduced,
+    /// Normal dropped, Important downsampled.
+    Minimal,
+    /// Only Critical and Emergency readings are sent.
+    Emergency,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Normal => "normal",
+            Level::Reduced => "reduced",
+            Level::Minimal => "minimal",
+            Level::Emergency => "emergency",
+        }
+    }
+
+    fn up(self) -> Option<Level> {
+        match self {
+            Level::Normal => Some(Level::Reduced),
+            Level::Reduced => Some(Level::Minimal),
+            Level::Minimal => Some(Level::Emergency),
+            Level::Emergency => None,
+        }
+    }
+
+    fn down(self) -> Option<Level> {
+        match self {
+            Level::Normal => None,
+            Level::Reduced => Some(Level::Normal),
+            Level::Minimal => Some(Level::Reduced),
+            Level::Emergency => Some(Level::Minimal),
+        }
+    }
+
+    /// Fill at which this rung is entered (Normal: 0).
+    fn enter_pct(self) -> f64 {
+        match self {
+            Level::Normal => 0.0,
+            l => ENTER_PCT[l as usize - 1],
+        }
+    }
+}
+
+pub struct Ladder {
+    level: Level,
+    pending: Option<(Level, Instant)>, // step wanted since
+    important_seen: u64,
+}
+
+impl Ladder {
+    pub fn new() -> Self {
+        Self { level: Level::Normal, pending: None, important_seen: 0 }
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Feed one fill sample and downlink health; returns `(from, to)` when a rung is taken.
+    pub fn observe(&mut self, fill_pct: f64, link_ok: bool, now: Instant) -> Option<(Level, Level)> {
+        let pressure = if link_ok { fill_pct } else { fill_pct + LINK_PENALTY_PCT };
+        let want = match (self.level.up(), self.level.down()) {
+            (Some(up), _) if pressure >= up.enter_pct() => Some(up),
+            (_, Some(down)) if link_ok && fill_pct < self.level.enter_pct() - HYSTERESIS_PCT => Some(down),
+            _ => None,
+        };
+        let Some(want) = want else {
+            self.pending = None; // back inside the band: restart the dwell
+            return None;
+        };
+        match self.pending {
+            Some((l, since)) if l == want => {
+                if now.duration_since(since) < DWELL {
+                    return None;
+                }
+                let from = self.level;
+                self.level = want;
+                self.pending = None;
+                Some((from, want))
+            }
+            _ => {
+                self.pending = Some((want, now));
+                None
+            }
+        }
+    }
+
+    /// Whether a reading of this priority is still sent at the current rung.
+    pub fn admit(&mut self, prio: Priority) -> bool {
+        match prio {
+            Priority::Emergency | Priority::Critical => true,
+            Priority::Normal => self.level == Level::Normal,
+            Priority::Important => match self.level {
+                Level::Normal | Level::Reduced => true,
+                Level::Minimal => {
+                    let keep = self.important_seen.is_multiple_of(IMPORTANT_DECIMATION);
+                    self.important_seen = self.important_seen.wrapping_add(1);
+                    keep
+                }
+                Level::Emergency => false,
+            },
+        }
+    }
+}
+
+impl Default for Ladder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The batcher's ladder: observed on every send attempt, consulted on every ingest.
+static LADDER: Lazy<Mutex<Ladder>> = Lazy::new(|| Mutex::new(Ladder::new()));
+
+pub fn level() -> Level {
+    LADDER.lock().unwrap().level()
+}
+
+/// Step the ladder and record any transition. Returns the (possibly new) level.
+pub fn observe(fill_pct: f64, link_ok: bool) -> Level {
+    let mut ladder = LADDER.lock().unwrap();
+    if let Some((from, to)) = ladder.observe(fill_pct, link_ok, Instant::now()) {
+        let detail = format!("{} -> {} at fill {fill_pct:.1}% (link_ok={link_ok})", from.as_str(), to.as_str());
+        blackbox::record(Event::new(EventKind::Degrade, "batcher", detail));
+        if to > from {
+            warn!(from = from.as_str(), to = to.as_str(), fill_pct, link_ok, "degradation: stepping down service");
+        } else {
+            info!(from = from.as_str(), to = to.as_str(), fill_pct, "degradation: restoring service");
+        }
+    }
+    ladder.level()
+}
+
+pub fn admit(prio: Priority) -> bool {
+    LADDER.lock().unwrap().admit(prio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_walks_the_ladder_with_hysteresis() {
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut l = Ladder::new();
+
+        // a brief spike, or one that toggles around the entry point, never steps up
+        assert_eq!(l.observe(85.0, true, at(0)), None);
+        assert_eq!(l.observe(40.0, true, at(1)), None);
+        assert_eq!(l.observe(62.0, true, at(2)), None);
+        assert_eq!(l.observe(58.0, true, at(3)), None);
+        assert_eq!(l.observe(62.0, true, at(4)), None);
+        assert_eq!(l.observe(58.0, true, at(5)), None);
+        assert_eq!(l.level(), Level::Normal);
+
+        // sustained fill climbs one rung per dwell
+        assert_eq!(l.observe(97.0, true, at(10)), None);
+        assert_eq!(l.observe(97.0, true, at(12)), Some((Level::Normal, Level::Reduced)));
+        l.observe(97.0, true, at(13));
+        assert_eq!(l.observe(97.0, true, at(15)), Some((Level::Reduced, Level::Minimal)));
+        l.observe(97.0, true, at(16));
+        assert_eq!(l.observe(97.0, true, at(18)), Some((Level::Minimal, Level::Emergency)));
+
+        // dipping just under the entry point does not step back down
+        for s in 20..30 {
+            assert_eq!(l.observe(90.0, true, at(s)), None);
+        }
+        // a sick link holds the rung even when fill has cleared the band
+        l.observe(70.0, false, at(30));
+        assert_eq!(l.observe(70.0, false, at(33)), None);
+        assert_eq!(l.level(), Level::Emergency);
+
+        // draining walks back down, again one rung per dwell
+        l.observe(10.0, true, at(40));
+        assert_eq!(l.observe(10.0, true, at(42)), Some((Level::Emergency, Level::Minimal)));
+        l.observe(10.0, true, at(43));
+        assert_eq!(l.observe(10.0, true, at(45)), Some((Level::Minimal, Level::Reduced)));
+        l.observe(10.0, true, at(46));
+        assert_eq!(l.observe(10.0, true, at(48)), Some((Level::Reduced, Level::Normal)));
+    }
+
+    #[test]
+    fn each_rung_sheds_more() {
+        let kept = |level, prio| {
+            let mut l = Ladder { level, pending: None, important_seen: 0 };
+            (0..8).filter(|_| l.admit(prio)).count()
+        };
+        assert_eq!(kept(Level::Normal, Priority::Normal), 8);
+        assert_eq!(kept(Level::Reduced, Priority::Normal), 0);
+        assert_eq!(kept(Level::Reduced, Priority::Important), 8);
+        assert_eq!(kept(Level::Minimal, Priority::Important), 2);
+        assert_eq!(kept(Level::Emergency, Priority::Important), 0);
+        assert_eq!(kept(Level::Emergency, Priority::Critical), 8);
+    }
+}
@@ -0,0 +1,228 @@
+// Alternative priority buffer: one BinaryHeap in strict (priority, enqueue time, sequence) order
+use shared_protocol::{Priority, SensorReading};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+use super::prio_buffer::{bucket, BufferStats, DropPolicy, InsertResult};
+
+#[derive(Debug)]
+struct Entry {
+    reading: SensorReading,
+    enqueued: Instant,
+}
+
+impl Entry {
+    /// Smaller pops first: most urgent priority, then longest queued, then lowest sequence.
+    fn key(&self) -> (Priority, Instant, u64) {
+        (self.reading.priority, self.enqueued, self.reading.sequence_number)
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // BinaryHeap is a max-heap: reverse so the smallest key is on top
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key().cmp(&self.key())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    heap: BinaryHeap<Entry>,
+    depth: [usize; 3],   // per bucket (hi, im, lo), as in BufferStats
+    dropped: [u64; 3],
+}
+
+impl Inner {
+    /// Same victim as the VecDeque buffer: the oldest entry of the lowest priority bucket
+    /// present. O(n): the heap only orders its top.
+    fn evict(&mut self) -> Option<Priority> {
+        let victim = (0..3).rev().find(|&b| self.depth[b] > 0)?;
+        let mut entries = std::mem::take(&mut self.heap).into_vec();
+        let idx = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| bucket(e.reading.priority) == victim)
+            .min_by_key(|(_, e)| e.enqueued)
+            .map(|(i, _)| i)?;
+        let gone = entries.swap_remove(idx);
+        self.heap = BinaryHeap::from(entries);
+        self.depth[victim] -= 1;
+        self.dropped[victim] += 1;
+        Some(gone.reading.priority)
+    }
+
+    fn admit(&mut self, capacity: usize, policy: DropPolicy, incoming: Priority) -> InsertResult {
+        if self.heap.len() < capacity {
+            return InsertResult::Accepted;
+        }
+        let evict = match policy {
+            DropPolicy::HeadDrop => true,
+            DropPolicy::TailDrop => false,
+            DropPolicy::PriorityEvict => {
+                (0..3).rev().find(|&b| self.depth[b] > 0).is_some_and(|victim| bucket(incoming) < victim)
+            }
+        };
+        if evict {
+            return match self.evict() {
+                Some(dropped_priority) => InsertResult::Dropped { dropped_priority, dropped_count: 1 },
+                None => InsertResult::Accepted,
+            };
+        }
+        self.dropped[bucket(incoming)] += 1;
+        InsertResult::Rejected { priority: incoming }
+    }
+}
+
+/// Drop-in alternative to `BufferHandle` with one global order instead of three FIFOs:
+/// O(log n) push and pop, no aging threshold (age only breaks ties within a priority).
+#[derive(Clone, Debug)]
+pub struct HeapBufferHandle {
+    inner: Arc<Mutex<Inner>>,
+    capacity: usize,
+    policy: DropPolicy,
+}
+
+impl HeapBufferHandle {
+    pub fn new(capacity: usize) -> Self {
+        Self { inner: Arc::default(), capacity, policy: DropPolicy::default() }
+    }
+
+    /// Eviction behaviour when full (default `HeadDrop`); set right after construction.
+    pub fn with_policy(mut self, policy: DropPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub async fn len(&self) -> usize {
+        self.inner.lock().await.heap.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Push under the buffer's `DropPolicy`; a full buffer evicts as `BufferHandle::push`.
+    pub async fn push(&self, r: SensorReading) -> InsertResult {
+        let mut g = self.inner.lock().await;
+        let res = g.admit(self.capacity, self.policy, r.priority);
+        if !matches!(res, InsertResult::Rejected { .. }) {
+            g.depth[bucket(r.priority)] += 1;
+            g.heap.push(Entry { reading: r, enqueued: Instant::now() });
+        }
+        res
+    }
+
+    /// Pop up to `n` in composite-key order.
+    pub async fn pop_many(&self, n: usize) -> Vec<SensorReading> {
+        let mut g = self.inner.lock().await;
+        let mut out = Vec::with_capacity(n.min(g.heap.len()));
+        while out.len() < n {
+            let Some(e) = g.heap.pop() else { break };
+            g.depth[bucket(e.reading.priority)] -= 1;
+            out.push(e.reading);
+        }
+        out
+    }
+
+    pub async fn stats(&self) -> BufferStats {
+        let g = self.inner.lock().await;
+        BufferStats {
+            hi: g.depth[0],
+            im: g.depth[1],
+            lo: g.depth[2],
+            total_dropped_hi: g.dropped[0],
+            total_dropped_im: g.dropped[1],
+            total_dropped_lo: g.dropped[2],
+        }
+    }
+
+    /// Percent fill (0.0..=100.0)
+    pub async fn fill_pct(&self) -> f64 {
+        if self.capacity == 0 {
+            return 0.0;
+        }
+        (self.len().await as f64 / self.capacity as f64) * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::ThermalSensor;
+
+    fn reading(priority: Priority, seq: u64) -> SensorReading {
+        let mut r = ThermalSensor::new(1, "CPU").create_reading(70.0, seq);
+        r.priority = priority;
+        r
+    }
+
+    #[tokio::test]
+    async fn pops_follow_priority_then_age_then_sequence() {
+        let buf = HeapBufferHandle::new(16);
+        let pushed = [
+            (Priority::Normal, 1),
+            (Priority::Critical, 7),
+            (Priority::Important, 3),
+            (Priority::Emergency, 9),
+            (Priority::Critical, 2),
+            (Priority::Normal, 0),
+        ];
+        for (p, seq) in pushed {
+            buf.push(reading(p, seq)).await;
+            std::thread::sleep(std::time::Duration::from_millis(1)); // distinct enqueue instants
+        }
+        let got: Vec<_> = buf.pop_many(16).await.iter().map(|r| (r.priority, r.sequence_number)).collect();
+        // same-priority entries leave in enqueue order, not sequence order
+        assert_eq!(got, [
+            (Priority::Emergency, 9),
+            (Priority::Critical, 7),
+            (Priority::Critical, 2),
+            (Priority::Important, 3),
+            (Priority::Normal, 1),
+            (Priority::Normal, 0),
+        ]);
+
+        // with equal enqueue instants the sequence number decides
+        let a = Entry { reading: reading(Priority::Normal, 5), enqueued: Instant::now() };
+        let b = Entry { reading: reading(Priority::Normal, 4), enqueued: a.enqueued };
+        assert!(b > a, "lower sequence pops first");
+    }
+
+    #[tokio::test]
+    async fn full_heap_evicts_like_the_deque_buffer() {
+        let buf = HeapBufferHandle::new(3);
+        buf.push(reading(Priority::Normal, 0)).await;
+        buf.push(reading(Priority::Normal, 1)).await;
+        buf.push(reading(Priority::Critical, 2)).await;
+        let res = buf.push(reading(Priority::Important, 3)).await;
+        assert!(matches!(res, InsertResult::Dropped { dropped_priority: Priority::Normal, .. }));
+        let s = buf.stats().await;
+        assert_eq!((s.hi, s.im, s.lo, s.total_dropped_lo), (1, 1, 1, 1));
+        assert_eq!(buf.fill_pct().await, 100.0);
+        // the oldest Normal went; the newer one is still queued
+        let seqs: Vec<_> = buf.pop_many(3).await.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(seqs, [2, 3, 1]);
+
+        let tail = HeapBufferHandle::new(1).with_policy(DropPolicy::TailDrop);
+        tail.push(reading(Priority::Normal, 0)).await;
+        assert!(matches!(tail.push(reading(Priority::Critical, 1)).await, InsertResult::Rejected { .. }));
+        assert_eq!(tail.stats().await.total_dropped_hi, 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! Recent readings per sensor id, filled by the ingest task, so ground can ask for
+//! data again (`CommandType::DataRequest`) after a lost or corrupt batch.
+//! Readings are stored once behind an `Arc`; lookups hand out shared references and only
+//! the caller that needs an owned copy (to send it) pays for the clone.
+use once_cell::sync::OnceCell;
+use shared_protocol::{SensorReading, SensorType};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Readings kept per sensor unless `--history-depth` says otherwise.
+pub const DEPTH: usize = 32;
+
+pub struct History {
+    depth: usize,
+    rings: Mutex<HashMap<u32, VecDeque<Arc<SensorReading>>>>,
+}
+
+static HISTORY: OnceCell<History> = OnceCell::new();
+
+fn history() -> &'static History {
+    HISTORY.get_or_init(|| History::new(DEPTH))
+}
+
+/// Set the per-sensor depth; before the first reading is recorded (later calls are ignored).
+pub fn init(depth: usize) {
+    let _ = HISTORY.set(History::new(depth));
+}
+
+pub fn record(r: &SensorReading) {
+    history().record(r);
+}
+
+/// Up to `n` of the newest readings of `sensor_id`, oldest first.
+pub fn recent(sensor_id: u32, n: usize) -> Vec<Arc<SensorReading>> {
+    history().recent(sensor_id, n)
+}
+
+/// Newest reading of each sensor type seen so far (one per type).
+pub fn latest_per_type() -> Vec<Arc<SensorReading>> {
+    history().latest_per_type()
+}
+
+impl History {
+    pub fn new(depth: usize) -> Self {
+        Self { depth: depth.max(1), rings: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn record(&self, r: &SensorReading) {
+        let r = Arc::new(r.clone()); // outside the lock
+        let mut rings = self.rings.lock().unwrap_or_else(|e| e.into_inner());
+        let ring = rings.entry(r.sensor_id).or_insert_with(|| VecDeque::with_capacity(self.depth));
+        if ring.len() == self.depth {
+            ring.pop_front();
+        }
+        ring.push_back(r);
+    }
+
+    /// With several sensors of a type, the one sampled last wins. Ordered by type.
+    pub fn latest_per_type(&self) -> Vec<Arc<SensorReading>> {
+        let rings = self.rings.lock().unwrap_or_else(|e| e.into_inner());
+        let mut newest: HashMap<SensorType, &Arc<SensorReading>> = HashMap::new();
+        for r in rings.values().filter_map(|ring| ring.back()) {
+            let slot = newest.entry(r.sensor_type).or_insert(r);
+            if r.timestamp > slot.timestamp {
+                *slot = r;
+            }
+        }
+        [SensorType::Thermal, SensorType::Power, SensorType::Attitude, SensorType::Radiation]
+            .iter()
+            .filter_map(|t| newest.get(t).map(|r| Arc::clone(r)))
+            .collect()
+    }
+
+    pub fn recent(&self, sensor_id: u32, n: usize) -> Vec<Arc<SensorReading>> {
+        let rings = self.rings.lock().unwrap_or_else(|e| e.into_inner());
+        rings.get(&sensor_id).map_or_else(Vec::new, |ring| {
+            ring.iter().skip(ring.len().saturating_sub(n)).cloned().collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::ThermalSensor;
+
+    #[test]
+    fn ring_keeps_only_the_newest_depth_readings() {
+        let h = History::new(4);
+        let (a, b) = (ThermalSensor::new(1, "A"), ThermalSensor::new(2, "B"));
+        for seq in 0..10 {
+            h.record(&a.create_reading(40.0, seq));
+        }
+        h.record(&b.create_reading(50.0, 0));
+
+        let seqs = |v: Vec<Arc<SensorReading>>| v.iter().map(|r| r.sequence_number).collect::<Vec<_>>();
+        assert_eq!(seqs(h.recent(1, 10)), [6, 7, 8, 9]);
+        assert_eq!(seqs(h.recent(1, 2)), [8, 9]);
+        assert_eq!(seqs(h.recent(2, 10)), [0]);
+        assert!(h.recent(3, 10).is_empty());
+
+        // lookups share the stored reading instead of copying it
+        assert!(Arc::ptr_eq(&h.recent(1, 1)[0], &h.recent(1, 1)[0]));
+        assert_eq!(h.latest_per_type().len(), 1);
+    }
+}
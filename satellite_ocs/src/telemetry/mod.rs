@@ -1,5 +1,12 @@
 pub mod batcher;
+pub mod coalesce;
+pub mod csv_replay;
+pub mod degrade;
+#[allow(dead_code)] // alternative to prio_buffer; compared in benches/prio_buffer.rs
+pub mod heap_buffer;
+pub mod history;
 pub mod prio_buffer;
 
 pub use batcher::spawn_batcher;
-pub use batcher::{CHANNEL, init_priority_buffer, BUFFER, EMER_TX};
\ No newline at end of file
+pub use batcher::{init_priority_buffer, BUFFER, EMER_TX};
+pub use batcher::{backpressure_count, replay, snapshot, try_enqueue, IngressError};
\ No newline at end of file
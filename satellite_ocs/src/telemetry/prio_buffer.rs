@@ -2,8 +2,14 @@ use once_cell::sync::OnceCell;
 use shared_protocol::{Priority, SensorReading};
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// Default age after which a queued reading jumps ahead of fresher, higher-priority ones
+/// (the OCS itself takes it from `--aging-ms`).
+#[cfg(test)]
+const DEFAULT_AGE_THRESHOLD: Duration = Duration::from_millis(500);
+
 /// Result of inserting into bounded buffer
 #[derive(Debug, Clone)]
 pub enum InsertResult {
@@ -14,33 +20,282 @@ pub enum InsertResult {
         dropped_priority: Priority,
         dropped_count: usize,
     },
+    /// Buffer full and the policy kept what was queued: the incoming reading was not inserted
+    Rejected { priority: Priority },
+}
+
+/// What `push` does when the buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum DropPolicy {
+    /// Evict the oldest reading of the lowest priority present, whatever arrives.
+    #[default]
+    HeadDrop,
+    /// Keep what is queued; the incoming reading is rejected.
+    TailDrop,
+    /// Evict as `HeadDrop`, but only for an incoming reading that outranks the victim;
+    /// otherwise the incoming one is rejected.
+    PriorityEvict,
+}
+
+/// Largest share of a batch each bucket (Critical+Emergency, Important, Normal) may
+/// take, as fractions of the batch size. `1,1,1` (the default) caps nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchCaps(pub [f64; 3]);
+
+impl Default for BatchCaps {
+    fn default() -> Self {
+        Self([1.0; 3])
+    }
+}
+
+impl BatchCaps {
+    pub fn is_unbounded(&self) -> bool {
+        self.0.iter().all(|&f| f >= 1.0)
+    }
+
+    /// Slots bucket `idx` may fill in a batch of `n` (at least one when its fraction is > 0).
+    fn slots(&self, idx: usize, n: usize) -> usize {
+        // tolerance: 10 * 0.3 is 3.0000000000000004 in f64
+        (n as f64 * self.0[idx] - 1e-9).ceil().max(0.0) as usize
+    }
+}
+
+impl std::str::FromStr for BatchCaps {
+    type Err = String;
+
+    /// "critical,important,normal" fractions, e.g. "0.5,0.3,0.3".
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let [hi, im, lo] = parts.as_slice() else {
+            return Err(format!("batch caps '{s}': expected critical,important,normal"));
+        };
+        let mut caps = [0.0; 3];
+        for (cap, p) in caps.iter_mut().zip([hi, im, lo]) {
+            *cap = p.parse().map_err(|e| format!("batch caps '{s}': {e}"))?;
+            if !(0.0..=1.0).contains(cap) {
+                return Err(format!("batch caps '{s}': {cap} is not a fraction"));
+            }
+        }
+        Ok(Self(caps))
+    }
+}
+
+/// Slots reserved per bucket (Critical+Emergency, Important, Normal). With these set, a
+/// full bucket only evicts from itself, so a flood of one class can't push out another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BucketCaps(pub [usize; 3]);
+
+impl BucketCaps {
+    pub fn total(&self) -> usize {
+        self.0.iter().sum()
+    }
+}
+
+impl std::str::FromStr for BucketCaps {
+    type Err = String;
+
+    /// "critical,important,normal" slot counts, e.g. "64,64,128".
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let [hi, im, lo] = parts.as_slice() else {
+            return Err(format!("bucket caps '{s}': expected critical,important,normal"));
+        };
+        let mut caps = [0; 3];
+        for (cap, p) in caps.iter_mut().zip([hi, im, lo]) {
+            *cap = p.parse().map_err(|e| format!("bucket caps '{s}': {e}"))?;
+        }
+        Ok(Self(caps))
+    }
+}
+
+/// Snapshot of per-priority depths and cumulative evictions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    pub hi: usize,
+    pub im: usize,
+    pub lo: usize,
+    pub total_dropped_hi: u64,
+    pub total_dropped_im: u64,
+    pub total_dropped_lo: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    reading: SensorReading,
+    enqueued: Instant,
 }
 
 #[derive(Debug)]
 struct Inner {
-    capacity: usize,
-    hi: VecDeque<SensorReading>,  // Emergency + Critical
-    im: VecDeque<SensorReading>,  // Important
-    lo: VecDeque<SensorReading>,  // Normal
+    age_threshold: Option<Duration>, // None = strict priority order
+    hi: VecDeque<Entry>,  // Emergency + Critical
+    im: VecDeque<Entry>,  // Important
+    lo: VecDeque<Entry>,  // Normal
+    // readings lost since start (evicted or rejected), per bucket
+    dropped_hi: u64,
+    dropped_im: u64,
+    dropped_lo: u64,
+}
+
+/// Queue index (0=hi, 1=im, 2=lo) for a priority.
+pub(crate) fn bucket(p: Priority) -> usize {
+    match p {
+        Priority::Emergency | Priority::Critical => 0, // hi
+        Priority::Important => 1,                      // im
+        Priority::Normal => 2,                         // lo
+    }
+}
+
+impl Inner {
+    fn len(&self) -> usize {
+        self.hi.len() + self.im.len() + self.lo.len()
+    }
+
+    /// When full, evict the oldest entry of the **lowest priority present**
+    /// (Normal → Important → Critical); returns what was dropped.
+    fn make_room(&mut self, capacity: usize) -> Option<Priority> {
+        if self.len() < capacity {
+            return None;
+        }
+        if self.lo.pop_front().is_some() {
+            self.dropped_lo += 1;
+            Some(Priority::Normal)
+        } else if self.im.pop_front().is_some() {
+            self.dropped_im += 1;
+            Some(Priority::Important)
+        } else if self.hi.pop_front().is_some() {
+            // Only if completely flooded by critical/emergency traffic
+            self.dropped_hi += 1;
+            Some(Priority::Critical)
+        } else {
+            None // capacity 0: nothing to evict
+        }
+    }
+
+    fn count_drop(&mut self, idx: usize) {
+        match idx {
+            0 => self.dropped_hi += 1,
+            1 => self.dropped_im += 1,
+            _ => self.dropped_lo += 1,
+        }
+    }
+
+    /// Room for one `incoming` reading in its own bucket of `caps`. Only that bucket is
+    /// evicted from (`HeadDrop`); `TailDrop` and `PriorityEvict` reject the reading, since
+    /// nothing in its own class ranks below it.
+    fn admit_in_bucket(&mut self, caps: BucketCaps, policy: DropPolicy, incoming: Priority) -> InsertResult {
+        let idx = bucket(incoming);
+        if self.queue_mut(idx).len() < caps.0[idx] {
+            return InsertResult::Accepted;
+        }
+        if policy == DropPolicy::HeadDrop
+            && let Some(e) = self.queue_mut(idx).pop_front()
+        {
+            self.count_drop(idx);
+            return insert_result(Some(e.reading.priority));
+        }
+        self.count_drop(idx);
+        InsertResult::Rejected { priority: incoming }
+    }
+
+    /// Room for one `incoming` reading under `policy`; `Rejected` means don't insert it.
+    fn admit(&mut self, capacity: usize, caps: Option<BucketCaps>, policy: DropPolicy, incoming: Priority) -> InsertResult {
+        if let Some(caps) = caps {
+            return self.admit_in_bucket(caps, policy, incoming);
+        }
+        if self.len() < capacity {
+            return InsertResult::Accepted;
+        }
+        let evict = match policy {
+            DropPolicy::HeadDrop => true,
+            DropPolicy::TailDrop => false,
+            DropPolicy::PriorityEvict => [&self.hi, &self.im, &self.lo]
+                .iter()
+                .rposition(|q| !q.is_empty())
+                .is_some_and(|victim| bucket(incoming) < victim),
+        };
+        if evict {
+            return insert_result(self.make_room(capacity));
+        }
+        self.count_drop(bucket(incoming));
+        InsertResult::Rejected { priority: incoming }
+    }
+
+    fn queue_mut(&mut self, idx: usize) -> &mut VecDeque<Entry> {
+        match idx {
+            0 => &mut self.hi,
+            1 => &mut self.im,
+            _ => &mut self.lo,
+        }
+    }
+
+    /// Queue index (0=hi, 1=im, 2=lo) whose head is the oldest entry past the age threshold.
+    fn oldest_aged(&self, now: Instant) -> Option<usize> {
+        let max_age = self.age_threshold?;
+        [&self.hi, &self.im, &self.lo]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, q)| q.front().map(|e| (i, e.enqueued)))
+            .filter(|&(_, t)| now.duration_since(t) >= max_age)
+            .min_by_key(|&(_, t)| t)
+            .map(|(i, _)| i)
+    }
+}
+
+fn insert_result(dropped: Option<Priority>) -> InsertResult {
+    match dropped {
+        Some(dropped_priority) => InsertResult::Dropped { dropped_priority, dropped_count: 1 },
+        None => InsertResult::Accepted,
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct BufferHandle {
     inner: Arc<Mutex<Inner>>,
+    capacity: usize, // fixed at construction; kept outside the lock
+    policy: DropPolicy,
+    bucket_caps: Option<BucketCaps>, // None = one pool of `capacity` shared by all buckets
 }
 
 impl BufferHandle {
+    #[cfg(test)]
     pub fn new(capacity: usize) -> Self {
+        Self::with_aging(capacity, Some(DEFAULT_AGE_THRESHOLD))
+    }
+
+    /// Buffer whose `pop_many` serves readings older than `age_threshold` first,
+    /// regardless of priority (`None` disables aging).
+    pub fn with_aging(capacity: usize, age_threshold: Option<Duration>) -> Self {
         Self {
             inner: Arc::new(Mutex::new(Inner {
-                capacity,
+                age_threshold,
                 hi: VecDeque::new(),
                 im: VecDeque::new(),
                 lo: VecDeque::new(),
+                dropped_hi: 0,
+                dropped_im: 0,
+                dropped_lo: 0,
             })),
+            capacity,
+            policy: DropPolicy::default(),
+            bucket_caps: None,
         }
     }
 
+    /// Eviction behaviour when full (default `HeadDrop`); set right after construction.
+    pub fn with_policy(mut self, policy: DropPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Per-bucket capacities in place of the shared pool (`capacity` becomes their sum);
+    /// set right after construction.
+    pub fn with_bucket_caps(mut self, caps: BucketCaps) -> Self {
+        self.capacity = caps.total();
+        self.bucket_caps = Some(caps);
+        self
+    }
+
     /// Current fill (total items)
     pub async fn len(&self) -> usize {
         let g = self.inner.lock().await;
@@ -49,66 +304,71 @@ impl BufferHandle {
 
     pub fn capacity(&self) -> usize {
         // constant; no lock needed
-        self.inner.blocking_lock().capacity
+        self.capacity
     }
 
-    /// Push with priority-aware drop policy.
-    /// If full, evict from the **lowest priority present** (Normal → Important → Critical).
+    /// Push under the buffer's `DropPolicy`. By default (`HeadDrop`), if full, evict from
+    /// the **lowest priority present** (Normal → Important → Critical).
     pub async fn push(&self, r: SensorReading) -> InsertResult {
         let mut g = self.inner.lock().await;
-
-        let total = g.hi.len() + g.im.len() + g.lo.len();
-        let target_q = match r.priority {
-            Priority::Emergency | Priority::Critical => 0, // hi
-            Priority::Important => 1,                      // im
-            Priority::Normal => 2,                         // lo
-        };
-
-        let mut dropped: Option<Priority> = None;
-
-        if total >= g.capacity {
-            // Evict policy: drop from the lowest non-empty bucket
-            if !g.lo.is_empty() {
-                g.lo.pop_front();
-                dropped = Some(Priority::Normal);
-            } else if !g.im.is_empty() {
-                g.im.pop_front();
-                dropped = Some(Priority::Important);
-            } else if !g.hi.is_empty() {
-                // Only if completely flooded by critical/emergency traffic
-                g.hi.pop_front();
-                dropped = Some(Priority::Critical);
-            } else {
-                // Shouldn't happen; capacity says full but queues empty
-            }
-        }
-
-        match target_q {
-            0 => g.hi.push_back(r),
-            1 => g.im.push_back(r),
-            _ => g.lo.push_back(r),
+        let res = g.admit(self.capacity, self.bucket_caps, self.policy, r.priority);
+        if !matches!(res, InsertResult::Rejected { .. }) {
+            g.queue_mut(bucket(r.priority)).push_back(Entry {
+                reading: r,
+                enqueued: Instant::now(),
+            });
         }
+        res
+    }
 
-        if let Some(dp) = dropped {
-            InsertResult::Dropped {
-                dropped_priority: dp,
-                dropped_count: 1,
-            }
-        } else {
-            InsertResult::Accepted
-        }
+    /// Put unsent readings back **ahead** of everything queued in their priority, keeping
+    /// their relative order, so per-sensor sequence numbers still go out monotonic.
+    /// Same drop policy as `push`; one result per reading.
+    pub async fn push_front_batch(&self, readings: Vec<SensorReading>) -> Vec<InsertResult> {
+        let mut g = self.inner.lock().await;
+        let now = Instant::now();
+        let mut results: Vec<InsertResult> = readings
+            .into_iter()
+            .rev()
+            .map(|r| {
+                let res = g.admit(self.capacity, self.bucket_caps, self.policy, r.priority);
+                if !matches!(res, InsertResult::Rejected { .. }) {
+                    g.queue_mut(bucket(r.priority)).push_front(Entry { reading: r, enqueued: now });
+                }
+                res
+            })
+            .collect();
+        results.reverse();
+        results
     }
 
-    /// Pop up to `n` in priority order.
+    /// Pop up to `n`: aged readings first (oldest first), then in priority order.
     pub async fn pop_many(&self, n: usize) -> Vec<SensorReading> {
+        self.pop_batch_with_quota(n, 0.0).await
+    }
+
+    /// Like `pop_many`, but at least `lower_quota_pct` % of the batch goes to Important/Normal
+    /// readings when any are queued, so a Critical flood can't starve them. Reserved slots
+    /// left unused by the lower buckets go back to Critical.
+    pub async fn pop_batch_with_quota(&self, n: usize, lower_quota_pct: f64) -> Vec<SensorReading> {
         let mut g = self.inner.lock().await;
         let mut out = Vec::with_capacity(n);
         let mut need = n;
 
-        let mut take_from = |q: &mut VecDeque<SensorReading>, need: &mut usize, out: &mut Vec<_>| {
+        // Aging: anything past the threshold goes out before fresher, higher-priority data
+        let now = Instant::now();
+        while need > 0 {
+            let Some(idx) = g.oldest_aged(now) else { break };
+            if let Some(e) = g.queue_mut(idx).pop_front() {
+                out.push(e.reading);
+                need -= 1;
+            }
+        }
+
+        let take_from = |q: &mut VecDeque<Entry>, need: &mut usize, out: &mut Vec<_>| {
             while *need > 0 {
                 if let Some(x) = q.pop_front() {
-                    out.push(x);
+                    out.push(x.reading);
                     *need -= 1;
                 } else {
                     break;
@@ -116,7 +376,18 @@ impl BufferHandle {
             }
         };
 
-        take_from(&mut g.hi, &mut need, &mut out);
+        // aged Important/Normal readings already count toward the quota
+        let lower_sent = out.iter().filter(|r| r.priority >= Priority::Important).count();
+        let quota = (n as f64 * lower_quota_pct.clamp(0.0, 100.0) / 100.0).ceil() as usize;
+        let reserved = quota
+            .saturating_sub(lower_sent)
+            .min(g.im.len() + g.lo.len())
+            .min(need);
+
+        let mut hi_need = need - reserved;
+        let before = out.len();
+        take_from(&mut g.hi, &mut hi_need, &mut out);
+        need -= out.len() - before;
         if need > 0 {
             take_from(&mut g.im, &mut need, &mut out);
         }
@@ -127,14 +398,248 @@ impl BufferHandle {
         out
     }
 
+    /// Pop up to `n` with each bucket held to its share of `caps` (aged readings first,
+    /// counted against their bucket). Slots a capped bucket can't use go to the others,
+    /// in priority order, so the batch is only short when the buffer is.
+    pub async fn pop_many_balanced(&self, n: usize, caps: BatchCaps) -> Vec<SensorReading> {
+        let mut g = self.inner.lock().await;
+        let mut out = Vec::with_capacity(n);
+        let mut taken = [0usize; 3];
+
+        let now = Instant::now();
+        while out.len() < n {
+            let Some(idx) = g.oldest_aged(now) else { break };
+            if let Some(e) = g.queue_mut(idx).pop_front() {
+                out.push(e.reading);
+                taken[idx] += 1;
+            }
+        }
+
+        // capped pass, then uncapped fill
+        for capped in [true, false] {
+            for (idx, count) in taken.iter_mut().enumerate() {
+                let limit = if capped { caps.slots(idx, n) } else { n };
+                while out.len() < n && *count < limit {
+                    let Some(e) = g.queue_mut(idx).pop_front() else { break };
+                    out.push(e.reading);
+                    *count += 1;
+                }
+            }
+        }
+        out
+    }
+
+    /// Sample time of the oldest buffered reading.
+    pub async fn oldest_timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let g = self.inner.lock().await;
+        g.hi.iter().chain(&g.im).chain(&g.lo).map(|e| e.reading.timestamp).min()
+    }
+
+    /// Per-priority depths and drop totals
+    pub async fn stats(&self) -> BufferStats {
+        let g = self.inner.lock().await;
+        BufferStats {
+            hi: g.hi.len(),
+            im: g.im.len(),
+            lo: g.lo.len(),
+            total_dropped_hi: g.dropped_hi,
+            total_dropped_im: g.dropped_im,
+            total_dropped_lo: g.dropped_lo,
+        }
+    }
+
     /// Percent fill (0.0..=100.0)
     pub async fn fill_pct(&self) -> f64 {
         let g = self.inner.lock().await;
         let total = g.hi.len() + g.im.len() + g.lo.len();
-        if g.capacity == 0 {
+        if self.capacity == 0 {
             0.0
         } else {
-            (total as f64 / g.capacity as f64) * 100.0
+            (total as f64 / self.capacity as f64) * 100.0
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_protocol::{PowerSensor, ThermalSensor};
+
+    fn reading(priority: Priority) -> SensorReading {
+        let mut r = ThermalSensor::new(1, "CPU").create_reading(70.0, 0);
+        r.priority = priority;
+        r
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn capacity_is_readable_from_a_runtime_task() {
+        let buf = BufferHandle::new(42);
+        let cap = tokio::spawn(async move { buf.capacity() }).await.unwrap();
+        assert_eq!(cap, 42);
+    }
+
+    #[tokio::test]
+    async fn evictions_advance_per_priority_counters() {
+        let buf = BufferHandle::with_aging(2, None);
+        buf.push(reading(Priority::Normal)).await;
+        buf.push(reading(Priority::Important)).await;
+
+        // full: each push evicts the lowest bucket present
+        buf.push(reading(Priority::Critical)).await; // drops normal
+        buf.push(reading(Priority::Critical)).await; // drops important
+        buf.push(reading(Priority::Critical)).await; // drops critical
+
+        let s = buf.stats().await;
+        assert_eq!((s.hi, s.im, s.lo), (2, 0, 0));
+        assert_eq!((s.total_dropped_hi, s.total_dropped_im, s.total_dropped_lo), (1, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn each_drop_policy_at_capacity() {
+        let full = |policy| async move {
+            let buf = BufferHandle::with_aging(2, None).with_policy(policy);
+            buf.push(reading(Priority::Normal)).await;
+            buf.push(reading(Priority::Important)).await;
+            buf
+        };
+        let depths = |s: BufferStats| (s.hi, s.im, s.lo);
+
+        // head-drop: the oldest lowest-priority reading makes room, whatever arrives
+        let buf = full(DropPolicy::HeadDrop).await;
+        let res = buf.push(reading(Priority::Normal)).await;
+        assert!(matches!(res, InsertResult::Dropped { dropped_priority: Priority::Normal, .. }));
+        assert_eq!(depths(buf.stats().await), (0, 1, 1));
+
+        // tail-drop: queued data is kept, even against a Critical reading
+        let buf = full(DropPolicy::TailDrop).await;
+        let res = buf.push(reading(Priority::Critical)).await;
+        assert!(matches!(res, InsertResult::Rejected { priority: Priority::Critical }));
+        let s = buf.stats().await;
+        assert_eq!(depths(s), (0, 1, 1));
+        assert_eq!(s.total_dropped_hi, 1);
+
+        // priority-evict: only an incoming reading that outranks the victim gets in
+        let buf = full(DropPolicy::PriorityEvict).await;
+        let res = buf.push(reading(Priority::Normal)).await;
+        assert!(matches!(res, InsertResult::Rejected { priority: Priority::Normal }));
+        let res = buf.push(reading(Priority::Important)).await;
+        assert!(matches!(res, InsertResult::Dropped { dropped_priority: Priority::Normal, .. }));
+        let res = buf.push(reading(Priority::Important)).await;
+        assert!(matches!(res, InsertResult::Rejected { priority: Priority::Important }));
+        assert_eq!(depths(buf.stats().await), (0, 2, 0));
+    }
+
+    #[tokio::test]
+    async fn aged_normal_reading_beats_fresh_critical() {
+        let buf = BufferHandle::with_aging(16, Some(Duration::from_millis(20)));
+
+        let mut normal = PowerSensor::new(1, "BUS").create_reading(80.0, 28.0, 1.0, 28.0, 0);
+        normal.priority = Priority::Normal;
+        let mut critical = ThermalSensor::new(1, "CPU").create_reading(90.0, 0);
+        critical.priority = Priority::Critical;
+
+        buf.push(normal).await;
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        buf.push(critical).await;
+
+        let out = buf.pop_many(2).await;
+        assert_eq!(out[0].priority, Priority::Normal);
+        assert_eq!(out[1].priority, Priority::Critical);
+
+        // without aging the strict order still holds
+        let strict = BufferHandle::with_aging(16, None);
+        strict.push(out[0].clone()).await;
+        strict.push(out[1].clone()).await;
+        assert_eq!(strict.pop_many(1).await[0].priority, Priority::Critical);
+    }
+
+    #[tokio::test]
+    async fn front_batch_goes_ahead_in_original_order() {
+        let buf = BufferHandle::with_aging(16, None);
+        let seq = |n: u64| {
+            let mut r = reading(Priority::Normal);
+            r.sequence_number = n;
+            r
+        };
+        buf.push(seq(1)).await; // A
+        buf.push(seq(2)).await; // B
+        buf.push_front_batch(vec![seq(10), seq(11)]).await; // X, Y (unsent earlier)
+
+        let order: Vec<u64> = buf.pop_many(4).await.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(order, [10, 11, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn quota_guarantees_important_slots_under_critical_flood() {
+        let buf = BufferHandle::with_aging(64, None);
+        for _ in 0..40 {
+            buf.push(reading(Priority::Critical)).await;
+        }
+        for _ in 0..3 {
+            buf.push(reading(Priority::Important)).await;
+        }
+
+        // 20% of 10 → 2 slots reserved for the lower buckets
+        let out = buf.pop_batch_with_quota(10, 20.0).await;
+        let important = out.iter().filter(|r| r.priority == Priority::Important).count();
+        assert_eq!(out.len(), 10);
+        assert_eq!(important, 2);
+
+        // one Important left: the second reserved slot falls back to Critical
+        let out = buf.pop_batch_with_quota(10, 20.0).await;
+        assert_eq!(out.len(), 10);
+        assert_eq!(out.iter().filter(|r| r.priority == Priority::Important).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn balanced_batch_honors_per_priority_caps() {
+        let buf = BufferHandle::with_aging(64, None);
+        for p in [(Priority::Critical, 30), (Priority::Important, 10), (Priority::Normal, 2)] {
+            for _ in 0..p.1 {
+                buf.push(reading(p.0)).await;
+            }
+        }
+        let count = |out: &[SensorReading], p| out.iter().filter(|r| r.priority == p).count();
+        let caps: BatchCaps = "0.5,0.3,0.3".parse().unwrap();
+
+        // 5 critical, 3 important, 2 normal: all within their caps
+        let out = buf.pop_many_balanced(10, caps).await;
+        let mix = [Priority::Critical, Priority::Important, Priority::Normal].map(|p| count(&out, p));
+        assert_eq!(mix, [5, 3, 2]);
+
+        // no Normal left: its slots fall back to Critical first
+        let out = buf.pop_many_balanced(10, caps).await;
+        let mix = [Priority::Critical, Priority::Important, Priority::Normal].map(|p| count(&out, p));
+        assert_eq!(mix, [7, 3, 0]);
+
+        assert!("0.5,0.3".parse::<BatchCaps>().is_err());
+        assert!("0.5,1.5,0.3".parse::<BatchCaps>().is_err());
+        assert!(BatchCaps::default().is_unbounded());
+    }
+
+    #[tokio::test]
+    async fn normal_flood_does_not_evict_critical_with_bucket_caps() {
+        let caps: BucketCaps = "2,1,3".parse().unwrap();
+        let buf = BufferHandle::with_aging(100, None).with_bucket_caps(caps);
+        assert_eq!(buf.capacity(), 6);
+        buf.push(reading(Priority::Critical)).await;
+        buf.push(reading(Priority::Emergency)).await;
+        for _ in 0..20 {
+            buf.push(reading(Priority::Normal)).await;
+        }
+        let s = buf.stats().await;
+        assert_eq!((s.hi, s.im, s.lo), (2, 0, 3));
+        assert_eq!((s.total_dropped_hi, s.total_dropped_lo), (0, 17));
+
+        // a full bucket only evicts from itself, even with room elsewhere
+        let res = buf.push(reading(Priority::Critical)).await;
+        assert!(matches!(res, InsertResult::Dropped { dropped_priority: Priority::Critical, .. }));
+        assert_eq!(buf.stats().await.lo, 3);
+        assert!(matches!(buf.push(reading(Priority::Important)).await, InsertResult::Accepted));
+
+        let tail = BufferHandle::new(100).with_bucket_caps(caps).with_policy(DropPolicy::TailDrop);
+        tail.push(reading(Priority::Important)).await;
+        assert!(matches!(tail.push(reading(Priority::Important)).await, InsertResult::Rejected { .. }));
+        assert!("2,1".parse::<BucketCaps>().is_err());
+    }
+}
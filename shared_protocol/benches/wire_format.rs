@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use shared_protocol::{
+    CommunicationPacket, CryptoContext, SerializationFormat, Source, ThermalSensor,
+};
+
+// A full telemetry batch (64 readings), as sent by the OCS batcher
+fn telemetry_batch() -> CommunicationPacket {
+    let thermal = ThermalSensor::new(1, "CPU");
+    let readings = (0..64)
+        .map(|i| thermal.create_reading(60.0 + i as f64 * 0.1, i))
+        .collect();
+    CommunicationPacket::new_telemetry(readings, Source::Satellite)
+}
+
+// Benchmark seal + open of a 64-reading packet with each codec
+fn bench_wire_formats(c: &mut Criterion) {
+    let pkt = telemetry_batch();
+
+    for (name, format) in [
+        ("json", SerializationFormat::Json),
+        ("bincode", SerializationFormat::Bincode),
+    ] {
+        // replay window disabled: the same frame is opened on every iteration
+        let crypto = CryptoContext::new(1, [7u8; 32], 0).with_format(format);
+        let sealed = crypto.seal_to_bytes(&pkt).expect("seal");
+        println!("{name}: {} bytes on wire for 64 readings", sealed.len());
+
+        c.bench_function(&format!("seal_64_readings_{name}"), |b| {
+            b.iter(|| std::hint::black_box(crypto.seal_to_bytes(&pkt).unwrap()));
+        });
+
+        c.bench_function(&format!("open_64_readings_{name}"), |b| {
+            b.iter(|| std::hint::black_box(crypto.open_from_bytes(&sealed).unwrap()));
+        });
+    }
+}
+
+criterion_group!(benches, bench_wire_formats);
+criterion_main!(benches);
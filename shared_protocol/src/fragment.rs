@@ -0,0 +1,228 @@
+//! UDP fragmentation for sealed frames larger than one datagram.
+//!
+//! Fragment datagram: [FRAGMENT_MAGIC][frame_id u32 BE][index u16 BE][count u16 BE][chunk]
+//!
+//! Frames that fit in one datagram go out untouched. They start with a u32 BE
+//! length prefix whose top byte is 0 (frames are ≤ MAX_PACKET_SIZE), so they can
+//! never be mistaken for a fragment.
+
+use crate::MAX_PACKET_SIZE;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+pub const FRAGMENT_MAGIC: u8 = 0xFA;
+pub const FRAGMENT_HEADER_LEN: usize = 9;
+/// Safe payload size for a typical Ethernet path (IP/UDP headers excluded).
+pub const DEFAULT_MTU: usize = 1400;
+/// Incomplete fragment sets older than this are discarded.
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Splits outgoing frames into MTU-sized datagrams.
+#[derive(Debug)]
+pub struct Fragmenter {
+    mtu: usize,
+    next_id: AtomicU32,
+}
+
+impl Fragmenter {
+    pub fn new(mtu: usize) -> Self {
+        Self {
+            mtu: mtu.max(FRAGMENT_HEADER_LEN + 1),
+            next_id: AtomicU32::new(1),
+        }
+    }
+
+    pub fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    /// Datagrams to send for `frame` (just the frame itself when it fits).
+    pub fn fragment(&self, frame: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        if frame.len() <= self.mtu {
+            return Ok(vec![frame.to_vec()]);
+        }
+        let chunk_len = self.mtu - FRAGMENT_HEADER_LEN;
+        let count = frame.len().div_ceil(chunk_len);
+        let count = u16::try_from(count)
+            .map_err(|_| format!("frame needs {count} fragments (mtu {})", self.mtu))?;
+        let frame_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        Ok(frame
+            .chunks(chunk_len)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut d = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+                d.push(FRAGMENT_MAGIC);
+                d.extend_from_slice(&frame_id.to_be_bytes());
+                d.extend_from_slice(&(index as u16).to_be_bytes());
+                d.extend_from_slice(&count.to_be_bytes());
+                d.extend_from_slice(chunk);
+                d
+            })
+            .collect())
+    }
+}
+
+impl Default for Fragmenter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MTU)
+    }
+}
+
+#[derive(Debug)]
+struct Partial {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+    started: Instant,
+}
+
+/// Rebuilds frames from fragments arriving in any order.
+#[derive(Debug)]
+pub struct Reassembler {
+    timeout: Duration,
+    partial: HashMap<u32, Partial>,
+    discarded: u64,
+}
+
+impl Reassembler {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            partial: HashMap::new(),
+            discarded: 0,
+        }
+    }
+
+    /// Feed one datagram; returns a complete frame once all its fragments are in.
+    /// Unfragmented datagrams are returned as-is.
+    pub fn push(&mut self, datagram: &[u8]) -> Option<Vec<u8>> {
+        self.push_at(datagram, Instant::now())
+    }
+
+    /// Fragment sets (complete or not) dropped as stale or malformed.
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Incomplete frames currently buffered.
+    pub fn pending(&self) -> usize {
+        self.partial.len()
+    }
+
+    fn push_at(&mut self, datagram: &[u8], now: Instant) -> Option<Vec<u8>> {
+        self.expire(now);
+
+        if datagram.first() != Some(&FRAGMENT_MAGIC) {
+            return Some(datagram.to_vec());
+        }
+        if datagram.len() <= FRAGMENT_HEADER_LEN {
+            self.discarded += 1;
+            return None;
+        }
+        let frame_id = u32::from_be_bytes([datagram[1], datagram[2], datagram[3], datagram[4]]);
+        let index = u16::from_be_bytes([datagram[5], datagram[6]]) as usize;
+        let count = u16::from_be_bytes([datagram[7], datagram[8]]) as usize;
+        let chunk = &datagram[FRAGMENT_HEADER_LEN..];
+
+        if count == 0 || index >= count {
+            self.discarded += 1;
+            return None;
+        }
+
+        let set = self.partial.entry(frame_id).or_insert_with(|| Partial {
+            chunks: vec![None; count],
+            received: 0,
+            bytes: 0,
+            started: now,
+        });
+        if set.chunks.len() != count || set.bytes + chunk.len() > MAX_PACKET_SIZE + 4 {
+            // inconsistent header or oversized frame: give up on the whole set
+            self.partial.remove(&frame_id);
+            self.discarded += 1;
+            return None;
+        }
+        if set.chunks[index].is_none() {
+            set.bytes += chunk.len();
+            set.received += 1;
+            set.chunks[index] = Some(chunk.to_vec());
+        }
+        if set.received < count {
+            return None;
+        }
+
+        let set = self.partial.remove(&frame_id)?;
+        let mut frame = Vec::with_capacity(set.bytes);
+        for c in set.chunks.into_iter().flatten() {
+            frame.extend_from_slice(&c);
+        }
+        Some(frame)
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let timeout = self.timeout;
+        let before = self.partial.len();
+        self.partial
+            .retain(|_, p| now.saturating_duration_since(p.started) < timeout);
+        self.discarded += (before - self.partial.len()) as u64;
+    }
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(DEFAULT_REASSEMBLY_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_10k() -> Vec<u8> {
+        let body: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut f = (body.len() as u32).to_be_bytes().to_vec();
+        f.extend(body);
+        f
+    }
+
+    #[test]
+    fn reassembles_10k_frame_out_of_order() {
+        let frame = frame_10k();
+        let frags = Fragmenter::new(1400).fragment(&frame).unwrap();
+        assert_eq!(frags.len(), 8);
+        assert!(frags.iter().all(|d| d.len() <= 1400));
+
+        let mut r = Reassembler::default();
+        let mut out = None;
+        for d in frags.iter().rev() {
+            assert!(out.is_none());
+            out = r.push(d);
+        }
+        assert_eq!(out.unwrap(), frame);
+        assert_eq!(r.pending(), 0);
+
+        // small frames pass straight through
+        let small = Fragmenter::default().fragment(&frame[..100]).unwrap();
+        assert_eq!(small, vec![frame[..100].to_vec()]);
+        assert_eq!(r.push(&small[0]).unwrap(), frame[..100].to_vec());
+    }
+
+    #[test]
+    fn incomplete_set_is_discarded_after_timeout() {
+        let frags = Fragmenter::new(1400).fragment(&frame_10k()).unwrap();
+        let mut r = Reassembler::new(Duration::from_millis(100));
+        let t0 = Instant::now();
+
+        // one fragment never arrives
+        for d in &frags[1..] {
+            assert!(r.push_at(d, t0).is_none());
+        }
+        assert_eq!(r.pending(), 1);
+
+        // the late fragment shows up after the timeout: the set is gone
+        assert!(r.push_at(&frags[0], t0 + Duration::from_millis(150)).is_none());
+        assert_eq!(r.discarded(), 1);
+        assert_eq!(r.pending(), 1); // only the late fragment's new set
+    }
+}
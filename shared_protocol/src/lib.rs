@@ -2,23 +2,51 @@
 
 use chrono::{DateTime, Utc};
 use crc32fast::Hasher; // retained for compatibility; not used on-wire once AEAD is on
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
 use uuid::Uuid;
 
+pub mod fragment;
+pub mod loss;
+pub use fragment::{Fragmenter, Reassembler};
+pub use loss::{LossTracker, SeqGap};
+
 // =============================== Common =====================================
 
 pub type Timestamp = DateTime<Utc>;
 
 pub const PROTOCOL_VERSION: u16 = 1;
 pub const MAX_PACKET_SIZE: usize = 1024 * 1024; // 1MB
+/// Largest JSON-encoded reading list put in one telemetry packet: a JSON frame spells
+/// each ciphertext byte as up to 4 characters, so this keeps the sealed frame under
+/// `MAX_PACKET_SIZE` with room for the headers.
+pub const SAFE_TELEMETRY_SIZE: usize = MAX_PACKET_SIZE / 5;
 pub const DEFAULT_SATELLITE_PORT: u16 = 7890;
 pub const DEFAULT_GROUND_CONTROL_PORT: u16 = 7891;
+/// Width of the per-source anti-replay window (sequence numbers).
+pub const DEFAULT_REPLAY_WINDOW: u32 = 64;
+/// Relative W vs V*A disagreement above which a power reading is only `Fair`
+pub const POWER_MISMATCH_TOLERANCE: f64 = 0.05;
+/// `text_param` of a DataRequest asking for the newest reading of every sensor type at once.
+pub const SNAPSHOT_REQUEST: &str = "SNAPSHOT";
+/// Metadata key marking an attitude reading's value layout; "quaternion" when present,
+/// roll/pitch/yaw otherwise.
+pub const ATTITUDE_REPR_KEY: &str = "attitude_repr";
+pub const ATTITUDE_REPR_QUATERNION: &str = "quaternion";
+/// Command metadata: re-sends allowed after the first attempt.
+pub const MAX_RETRIES_KEY: &str = "max_retries";
+/// Command metadata: backoff (ms) before the first retry, doubling with each one.
+pub const BACKOFF_BASE_MS_KEY: &str = "backoff_base_ms";
+pub const DEFAULT_MAX_RETRIES: u8 = 3;
+pub const DEFAULT_BACKOFF_BASE_MS: u64 = 100;
+const ZSTD_LEVEL: i32 = 3; // fast; telemetry JSON compresses well even at low levels
 
 // =============================== Enums ======================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Source {
     Satellite,
@@ -33,14 +61,17 @@ pub enum PacketType {
     Ack,
     Emergency,
     Heartbeat,
+    Config,
+    Handshake,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SensorType {
     Thermal,
     Power,
     Attitude,
+    Radiation,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
@@ -92,6 +123,28 @@ pub enum CommandType {
     Diagnostic,
     Maintenance,
     DataRequest,
+    SetThreshold,
+    ClockCorrection,
+}
+
+impl CommandType {
+    /// Priorities a command of this type may carry (enforced by `Command::validate`).
+    pub fn allowed_priorities(self) -> &'static [Priority] {
+        match self {
+            CommandType::Emergency => &[Priority::Emergency],
+            CommandType::Recovery => &[Priority::Emergency, Priority::Critical],
+            CommandType::ThermalControl
+            | CommandType::PowerControl
+            | CommandType::AttitudeControl => {
+                &[Priority::Critical, Priority::Important, Priority::Normal]
+            }
+            CommandType::Diagnostic
+            | CommandType::Maintenance
+            | CommandType::DataRequest
+            | CommandType::SetThreshold
+            | CommandType::ClockCorrection => &[Priority::Important, Priority::Normal],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -136,6 +189,147 @@ pub struct SensorReading {
     pub metadata: HashMap<String, String>,
 }
 
+/// `deg` folded into (-180, 180], so 359° reads as -1° rather than a near-full turn.
+fn wrap_deg(deg: f64) -> f64 {
+    let w = deg.rem_euclid(360.0);
+    if w > 180.0 { w - 360.0 } else { w }
+}
+
+/// √(roll² + pitch² + yaw²) over the wrapped angles.
+fn euler_error_deg(roll: f64, pitch: f64, yaw: f64) -> f64 {
+    (wrap_deg(roll).powi(2) + wrap_deg(pitch).powi(2) + wrap_deg(yaw).powi(2)).sqrt()
+}
+
+/// Rotation angle (degrees, 0..=180) of the quaternion (w, x, y, z) away from identity;
+/// q and -q are the same rotation. A zero quaternion reads as 0.
+fn quat_angle_deg(w: f64, x: f64, y: f64, z: f64) -> f64 {
+    let norm = (w * w + x * x + y * y + z * z).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    (2.0 * (w.abs() / norm).min(1.0).acos()).to_degrees()
+}
+
+/// The description every `create_reading` gives a sensor of this type at `location`.
+fn describe(sensor_type: SensorType, location: &str) -> String {
+    match sensor_type {
+        SensorType::Thermal => format!("Temperature sensor at {location}"),
+        SensorType::Power => format!("Power management sensor at {location}"),
+        SensorType::Attitude => format!("Attitude control sensor at {location}"),
+        SensorType::Radiation => format!("Radiation dosimeter at {location}"),
+    }
+}
+
+impl SensorReading {
+    /// Strip what the receiver can rebuild before the reading goes on the wire: the
+    /// stock description, value4 where it is unused (thermal) or derived (power W = V·A,
+    /// attitude error = √(r²+p²+y²)), and metadata entries with empty values.
+    /// Measured values, thresholds and timing are kept; `expand` undoes it.
+    pub fn compact(&mut self) {
+        if self.description == describe(self.sensor_type, &self.location) {
+            self.description.clear();
+        }
+        match self.sensor_type {
+            SensorType::Attitude if self.is_quaternion() => {} // value4 is qz
+            SensorType::Thermal | SensorType::Power | SensorType::Attitude => self.value4 = 0.0,
+            SensorType::Radiation => {} // value3/value4 carry the (commandable) dose thresholds
+        }
+        self.metadata.retain(|_, v| !v.is_empty());
+    }
+
+    /// Rebuild the fields `compact` cleared; a no-op on a reading that was never compacted.
+    pub fn expand(&mut self) {
+        if self.description.is_empty() {
+            self.description = describe(self.sensor_type, &self.location);
+        }
+        if self.value4 == 0.0 {
+            match self.sensor_type {
+                SensorType::Power => self.value4 = self.value2 * self.value3,
+                SensorType::Attitude if self.is_quaternion() => {}
+                SensorType::Attitude => self.value4 = self.attitude_error(),
+                SensorType::Thermal | SensorType::Radiation => {}
+            }
+        }
+    }
+
+    /// Whether an attitude reading carries a quaternion (see `create_reading_quat`).
+    pub fn is_quaternion(&self) -> bool {
+        self.metadata.get(ATTITUDE_REPR_KEY).is_some_and(|v| v == ATTITUDE_REPR_QUATERNION)
+    }
+
+    /// Attitude error in degrees: the rotation angle away from identity for a quaternion
+    /// reading, √(roll² + pitch² + yaw²) for an Euler one (value1..value3, each wrapped
+    /// into (-180, 180] first).
+    pub fn attitude_error(&self) -> f64 {
+        if self.is_quaternion() {
+            return quat_angle_deg(self.value1, self.value2, self.value3, self.value4);
+        }
+        euler_error_deg(self.value1, self.value2, self.value3)
+    }
+
+    /// (roll, pitch, yaw) in degrees whichever representation the reading carries;
+    /// a quaternion is converted in ZYX order (pitch saturates at ±90°).
+    pub fn euler_degrees(&self) -> (f64, f64, f64) {
+        if !self.is_quaternion() {
+            return (self.value1, self.value2, self.value3);
+        }
+        let (w, x, y, z) = (self.value1, self.value2, self.value3, self.value4);
+        let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+        let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees())
+    }
+
+    /// Reject physically impossible readings before they are queued: non-finite values
+    /// and out-of-range fields for the sensor type (value layout as in `create_reading`).
+    pub fn validate_ranges(&self) -> Result<(), String> {
+        let fields = [
+            ("value1", self.value1),
+            ("value2", self.value2),
+            ("value3", self.value3),
+            ("value4", self.value4),
+            ("processing_latency_ms", self.processing_latency_ms),
+            ("jitter_ms", self.jitter_ms),
+            ("drift_ms", self.drift_ms),
+        ];
+        if let Some((name, v)) = fields.iter().find(|(_, v)| !v.is_finite()) {
+            return Err(format!("{name} is not finite ({v})"));
+        }
+        let in_range = |name: &str, v: f64, lo: f64, hi: f64| {
+            if (lo..=hi).contains(&v) {
+                Ok(())
+            } else {
+                Err(format!("{name} {v} outside {lo}..={hi}"))
+            }
+        };
+        match self.sensor_type {
+            SensorType::Thermal => in_range("temperature °C", self.value1, -273.15, 1000.0),
+            SensorType::Power => {
+                in_range("battery %", self.value1, 0.0, 100.0)?;
+                in_range("voltage V", self.value2, 0.0, 1000.0)
+            }
+            SensorType::Attitude if self.is_quaternion() => {
+                for (name, v) in [("qw", self.value1), ("qx", self.value2), ("qy", self.value3), ("qz", self.value4)] {
+                    in_range(name, v, -1.0, 1.0)?;
+                }
+                if self.value1 == 0.0 && self.value2 == 0.0 && self.value3 == 0.0 && self.value4 == 0.0 {
+                    return Err("zero quaternion".into());
+                }
+                Ok(())
+            }
+            SensorType::Attitude => {
+                in_range("roll °", self.value1, -180.0, 180.0)?;
+                in_range("pitch °", self.value2, -180.0, 180.0)?;
+                in_range("yaw °", self.value3, -180.0, 180.0)
+            }
+            SensorType::Radiation => {
+                in_range("dose rate mGy/h", self.value1, 0.0, f64::MAX)?;
+                in_range("total dose mGy", self.value2, 0.0, f64::MAX)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThermalSensor {
     pub sensor_id: u32,
@@ -179,7 +373,7 @@ impl ThermalSensor {
         SensorReading {
             sensor_id: self.sensor_id,
             sensor_type: SensorType::Thermal,
-            description: format!("Temperature sensor at {}", self.location),
+            description: describe(SensorType::Thermal, &self.location),
             location: self.location.clone(),
             timestamp: Utc::now(),
             sequence_number,
@@ -222,6 +416,25 @@ impl PowerSensor {
         }
     }
 
+    /// Battery range first, then cross-field checks: no current without a positive
+    /// voltage, and W must match V*A within `POWER_MISMATCH_TOLERANCE`.
+    fn quality(battery_percentage: f64, voltage: f64, current: f64, power_watts: f64) -> Quality {
+        if !(0.0..=100.0).contains(&battery_percentage) {
+            return Quality::Invalid;
+        }
+        if voltage <= 0.0 && current != 0.0 {
+            return Quality::Invalid;
+        }
+        let expected = voltage * current;
+        // small absolute floor so near-zero loads don't trip on rounding
+        let allowed = (expected.abs() * POWER_MISMATCH_TOLERANCE).max(0.01);
+        if (power_watts - expected).abs() > allowed {
+            Quality::Fair
+        } else {
+            Quality::Good
+        }
+    }
+
     /// value1: battery %, value2: V, value3: A, value4: W
     pub fn create_reading(
         &self,
@@ -250,7 +463,7 @@ impl PowerSensor {
         SensorReading {
             sensor_id: self.sensor_id,
             sensor_type: SensorType::Power,
-            description: format!("Power management sensor at {}", self.location),
+            description: describe(SensorType::Power, &self.location),
             location: self.location.clone(),
             timestamp: Utc::now(),
             sequence_number,
@@ -259,11 +472,7 @@ impl PowerSensor {
             value3: current,
             value4: power_watts,
             priority,
-            quality: if (0.0..=100.0).contains(&battery_percentage) {
-                Quality::Good
-            } else {
-                Quality::Invalid
-            },
+            quality: Self::quality(battery_percentage, voltage, current, power_watts),
             status,
             processing_latency_ms: 0.0,
             jitter_ms: 0.0,
@@ -293,7 +502,8 @@ impl AttitudeSensor {
         }
     }
 
-    /// value1: roll°, value2: pitch°, value3: yaw°, value4: √(r²+p²+y²)
+    /// value1: roll°, value2: pitch°, value3: yaw° (as given), value4: √(r²+p²+y²) with
+    /// each angle wrapped into (-180, 180], so 359° counts as 1° off, not 359°.
     pub fn create_reading(
         &self,
         roll_degrees: f64,
@@ -301,9 +511,20 @@ impl AttitudeSensor {
         yaw_degrees: f64,
         sequence_number: u64,
     ) -> SensorReading {
-        let attitude_error =
-            (roll_degrees.powi(2) + pitch_degrees.powi(2) + yaw_degrees.powi(2)).sqrt();
+        let attitude_error = euler_error_deg(roll_degrees, pitch_degrees, yaw_degrees);
+        self.reading([roll_degrees, pitch_degrees, yaw_degrees, attitude_error], attitude_error, sequence_number)
+    }
+
+    /// value1..value4: qw, qx, qy, qz (unit quaternion, body relative to target), flagged
+    /// with `ATTITUDE_REPR_KEY`; the error is the rotation angle from identity, free of the
+    /// Euler ambiguity near ±90° pitch.
+    pub fn create_reading_quat(&self, qw: f64, qx: f64, qy: f64, qz: f64, sequence_number: u64) -> SensorReading {
+        let mut r = self.reading([qw, qx, qy, qz], quat_angle_deg(qw, qx, qy, qz), sequence_number);
+        r.metadata.insert(ATTITUDE_REPR_KEY.into(), ATTITUDE_REPR_QUATERNION.into());
+        r
+    }
 
+    fn reading(&self, values: [f64; 4], attitude_error: f64, sequence_number: u64) -> SensorReading {
         let status = if attitude_error >= self.critical_error_threshold {
             Status::Critical
         } else if attitude_error >= self.max_acceptable_error {
@@ -323,14 +544,14 @@ impl AttitudeSensor {
         SensorReading {
             sensor_id: self.sensor_id,
             sensor_type: SensorType::Attitude,
-            description: format!("Attitude control sensor at {}", self.location),
+            description: describe(SensorType::Attitude, &self.location),
             location: self.location.clone(),
             timestamp: Utc::now(),
             sequence_number,
-            value1: roll_degrees,
-            value2: pitch_degrees,
-            value3: yaw_degrees,
-            value4: attitude_error,
+            value1: values[0],
+            value2: values[1],
+            value3: values[2],
+            value4: values[3],
             priority,
             quality: Quality::Good,
             status,
@@ -342,6 +563,75 @@ impl AttitudeSensor {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RadiationSensor {
+    pub sensor_id: u32,
+    pub location: String,
+    pub warning_dose: f64,  // accumulated mGy
+    pub critical_dose: f64, // accumulated mGy
+    pub sampling_interval_ms: u64,
+}
+
+impl RadiationSensor {
+    pub fn new(sensor_id: u32, location: &str) -> Self {
+        Self {
+            sensor_id,
+            location: location.to_string(),
+            warning_dose: 50.0,
+            critical_dose: 100.0,
+            sampling_interval_ms: 1000, // 1Hz
+        }
+    }
+
+    /// value1: dose rate mGy/h, value2: total dose mGy, value3: warn mGy, value4: crit mGy
+    pub fn create_reading(
+        &self,
+        dose_rate: f64,
+        total_dose: f64,
+        sequence_number: u64,
+    ) -> SensorReading {
+        let status = if total_dose >= self.critical_dose {
+            Status::Critical
+        } else if total_dose >= self.warning_dose {
+            Status::Warning
+        } else {
+            Status::Normal
+        };
+
+        let priority = if total_dose >= self.critical_dose {
+            Priority::Critical
+        } else if total_dose >= self.warning_dose {
+            Priority::Important
+        } else {
+            Priority::Normal
+        };
+
+        SensorReading {
+            sensor_id: self.sensor_id,
+            sensor_type: SensorType::Radiation,
+            description: describe(SensorType::Radiation, &self.location),
+            location: self.location.clone(),
+            timestamp: Utc::now(),
+            sequence_number,
+            value1: dose_rate,
+            value2: total_dose,
+            value3: self.warning_dose,
+            value4: self.critical_dose,
+            priority,
+            quality: if dose_rate >= 0.0 && total_dose >= 0.0 {
+                Quality::Good
+            } else {
+                Quality::Invalid
+            },
+            status,
+            processing_latency_ms: 0.0,
+            jitter_ms: 0.0,
+            drift_ms: 0.0,
+            metadata: HashMap::new(),
+        }
+    }
+}
+
 // ================================ Commands ==================================
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -372,7 +662,71 @@ pub struct Command {
     pub metadata: HashMap<String, String>,
 }
 
+/// Every constructor's retry policy, unless the caller already set one.
+fn retry_defaults(mut meta: HashMap<String, String>) -> HashMap<String, String> {
+    meta.entry(MAX_RETRIES_KEY.into()).or_insert_with(|| DEFAULT_MAX_RETRIES.to_string());
+    meta.entry(BACKOFF_BASE_MS_KEY.into()).or_insert_with(|| DEFAULT_BACKOFF_BASE_MS.to_string());
+    meta
+}
+
 impl Command {
+    /// Override the constructor's retry policy.
+    pub fn with_retry_policy(mut self, max_retries: u8, backoff_base_ms: u64) -> Self {
+        self.metadata.insert(MAX_RETRIES_KEY.into(), max_retries.to_string());
+        self.metadata.insert(BACKOFF_BASE_MS_KEY.into(), backoff_base_ms.to_string());
+        self
+    }
+
+    /// Re-sends allowed after the first attempt (metadata, else `DEFAULT_MAX_RETRIES`).
+    pub fn max_retries(&self) -> u8 {
+        self.metadata.get(MAX_RETRIES_KEY).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_MAX_RETRIES)
+    }
+
+    /// Backoff before retry number `retry` (1-based): base · 2^(retry-1), saturating.
+    pub fn backoff(&self, retry: u8) -> chrono::Duration {
+        let base: u64 = self
+            .metadata
+            .get(BACKOFF_BASE_MS_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_BACKOFF_BASE_MS);
+        let ms = base.saturating_mul(1u64 << retry.saturating_sub(1).min(32));
+        chrono::Duration::milliseconds(ms.min(i64::MAX as u64) as i64)
+    }
+
+    /// The next attempt: `retry_count` + 1 and the deadline pushed back by that retry's
+    /// backoff; `None` once `max_retries` re-sends have been made.
+    pub fn next_retry(mut self) -> Option<Command> {
+        if self.retry_count >= self.max_retries() {
+            return None;
+        }
+        self.retry_count += 1;
+        let backoff = self.backoff(self.retry_count);
+        self.deadline = self.deadline.map(|d| d + backoff);
+        Some(self)
+    }
+
+    /// Checked by the sender before sealing and by the satellite before executing:
+    /// distinct source and destination, a deadline still ahead, and a priority the
+    /// command type allows (`CommandType::allowed_priorities`).
+    pub fn validate(&self) -> Result<(), String> {
+        if self.source == self.destination {
+            return Err(format!("source and destination are both {:?}", self.source));
+        }
+        if let Some(deadline) = self.deadline
+            && deadline <= Utc::now()
+        {
+            return Err(format!("deadline {} already passed", deadline.to_rfc3339()));
+        }
+        let allowed = self.command_type.allowed_priorities();
+        if !allowed.contains(&self.priority) {
+            return Err(format!(
+                "{:?} command cannot have {:?} priority (allowed: {:?})",
+                self.command_type, self.priority, allowed
+            ));
+        }
+        Ok(())
+    }
+
     // -------------------------- THERMAL ------------------------------------
     pub fn thermal_normal_operation(sensor_id: u32) -> Self {
         Self {
@@ -391,7 +745,7 @@ impl Command {
             priority: Priority::Normal,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: HashMap::new(),
+            metadata: retry_defaults(HashMap::new()),
         }
     }
 
@@ -418,7 +772,7 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -446,7 +800,7 @@ impl Command {
             priority: Priority::Critical,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -474,7 +828,7 @@ impl Command {
             priority: Priority::Emergency,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -496,7 +850,7 @@ impl Command {
             priority: Priority::Normal,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: HashMap::new(),
+            metadata: retry_defaults(HashMap::new()),
         }
     }
 
@@ -524,7 +878,7 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -552,7 +906,7 @@ impl Command {
             priority: Priority::Critical,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -574,7 +928,7 @@ impl Command {
             priority: Priority::Normal,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: HashMap::new(),
+            metadata: retry_defaults(HashMap::new()),
         }
     }
 
@@ -601,7 +955,7 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -629,7 +983,7 @@ impl Command {
             priority: Priority::Critical,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -649,6 +1003,7 @@ impl Command {
                 SensorType::Thermal => TargetSystem::ThermalManagement,
                 SensorType::Power => TargetSystem::PowerManagement,
                 SensorType::Attitude => TargetSystem::AttitudeControl,
+                SensorType::Radiation => TargetSystem::AllSystems, // no dedicated subsystem
             },
             timestamp: Utc::now(),
             deadline: Some(Utc::now() + chrono::Duration::seconds(5)),
@@ -661,7 +1016,88 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
+        }
+    }
+
+    /// "Everything right now": the satellite answers with one telemetry packet holding
+    /// the newest reading of each sensor type, outside the batch cycle.
+    pub fn snapshot_request() -> Self {
+        Self {
+            command_id: Uuid::new_v4().to_string(),
+            command_type: CommandType::DataRequest,
+            description: "Snapshot of all current sensor values".to_string(),
+            target_system: TargetSystem::AllSystems,
+            timestamp: Utc::now(),
+            deadline: Some(Utc::now() + chrono::Duration::seconds(5)),
+            retry_count: 0,
+            param1: 0.0, // no single sensor
+            param2: 0.0,
+            param3: 0.0,
+            param4: Priority::Important as u8 as f64,
+            text_param: SNAPSHOT_REQUEST.to_string(),
+            priority: Priority::Important,
+            source: Source::GroundControl,
+            destination: Source::Satellite,
+            metadata: retry_defaults(HashMap::new()),
+        }
+    }
+
+    /// NTP-style step: the satellite adds `correction_ms` (param1) to its clock. Ground
+    /// sends the negated offset it measured (satellite time − ground time).
+    pub fn clock_correction(correction_ms: f64) -> Self {
+        Self {
+            command_id: Uuid::new_v4().to_string(),
+            command_type: CommandType::ClockCorrection,
+            description: format!("Step satellite clock by {correction_ms:+.3} ms"),
+            target_system: TargetSystem::AllSystems,
+            timestamp: Utc::now(),
+            deadline: Some(Utc::now() + chrono::Duration::seconds(5)),
+            retry_count: 0,
+            param1: correction_ms,
+            param2: 0.0,
+            param3: 0.0,
+            param4: Priority::Important as u8 as f64,
+            text_param: "CLOCK_CORRECTION".to_string(),
+            priority: Priority::Important,
+            source: Source::GroundControl,
+            destination: Source::Satellite,
+            metadata: retry_defaults(HashMap::new()),
+        }
+    }
+
+    /// Change one classification threshold in flight. `which` names the field on the
+    /// sensor (thermal: critical/emergency, power: low/critical, attitude: max_error/
+    /// critical_error, radiation: warning/critical); param1 = sensor id, param2 = value.
+    pub fn set_threshold(sensor_id: u32, sensor_type: SensorType, which: &str, value: f64) -> Self {
+        let mut meta = HashMap::new();
+        meta.insert("sensor_type".into(), format!("{sensor_type:?}").to_lowercase());
+        meta.insert("threshold".into(), which.to_string());
+        Self {
+            command_id: Uuid::new_v4().to_string(),
+            command_type: CommandType::SetThreshold,
+            description: format!(
+                "Set {which} threshold of {:?} sensor {} to {}",
+                sensor_type, sensor_id, value
+            ),
+            target_system: match sensor_type {
+                SensorType::Thermal => TargetSystem::ThermalManagement,
+                SensorType::Power => TargetSystem::PowerManagement,
+                SensorType::Attitude => TargetSystem::AttitudeControl,
+                SensorType::Radiation => TargetSystem::AllSystems, // no dedicated subsystem
+            },
+            timestamp: Utc::now(),
+            deadline: Some(Utc::now() + chrono::Duration::seconds(5)),
+            retry_count: 0,
+            param1: sensor_id as f64,
+            param2: value,
+            param3: 0.0,
+            param4: Priority::Important as u8 as f64,
+            text_param: "SET_THRESHOLD".to_string(),
+            priority: Priority::Important,
+            source: Source::GroundControl,
+            destination: Source::Satellite,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -693,7 +1129,7 @@ impl Command {
             priority: Priority::Emergency,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -717,7 +1153,7 @@ impl Command {
             priority: Priority::Critical,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -733,6 +1169,7 @@ impl Command {
                 SensorType::Thermal => TargetSystem::ThermalManagement,
                 SensorType::Power => TargetSystem::PowerManagement,
                 SensorType::Attitude => TargetSystem::AttitudeControl,
+                SensorType::Radiation => TargetSystem::AllSystems, // no dedicated subsystem
             },
             timestamp: Utc::now(),
             deadline: Some(Utc::now() + chrono::Duration::seconds(30)),
@@ -745,7 +1182,7 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 
@@ -761,6 +1198,7 @@ impl Command {
                 SensorType::Thermal => TargetSystem::ThermalManagement,
                 SensorType::Power => TargetSystem::PowerManagement,
                 SensorType::Attitude => TargetSystem::AttitudeControl,
+                SensorType::Radiation => TargetSystem::AllSystems, // no dedicated subsystem
             },
             timestamp: Utc::now(),
             deadline: Some(Utc::now() + chrono::Duration::minutes(5)),
@@ -773,7 +1211,7 @@ impl Command {
             priority: Priority::Important,
             source: Source::GroundControl,
             destination: Source::Satellite,
-            metadata: meta,
+            metadata: retry_defaults(meta),
         }
     }
 }
@@ -799,14 +1237,91 @@ pub struct PacketHeader {
     pub protocol_version: u16,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+/// On the wire as `{"kind": .., "data": ..}` in JSON; see `payload_wire` for bincode.
+#[derive(Debug, Clone, PartialEq)]
 pub enum PacketPayload {
     TelemetryData(Vec<SensorReading>),
     CommandData(Command),
     AcknowledgmentData(CommandAcknowledgment),
     EmergencyAlert(EmergencyData),
     HeartbeatData(SystemHealth),
+    ConfigUpdate(ConfigUpdate),
+    /// Capability advertisement, exchanged once before telemetry flows.
+    Handshake(Capabilities),
+}
+
+/// Serde for `PacketPayload`. Human-readable formats (JSON) keep the adjacently tagged
+/// shape; bincode can't decode that (it needs `deserialize_identifier`), so compact
+/// formats get the plain externally tagged enum. Borrowing mirrors avoid a clone on encode.
+macro_rules! payload_wire {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        mod payload_wire {
+            use super::*;
+
+            #[derive(Serialize)]
+            #[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+            enum TaggedRef<'a> { $($variant(&'a $ty)),* }
+
+            #[derive(Deserialize)]
+            #[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+            enum Tagged { $($variant($ty)),* }
+
+            #[derive(Serialize)]
+            enum PlainRef<'a> { $($variant(&'a $ty)),* }
+
+            #[derive(Deserialize)]
+            enum Plain { $($variant($ty)),* }
+
+            impl Serialize for PacketPayload {
+                fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                    if s.is_human_readable() {
+                        match self { $(Self::$variant(v) => TaggedRef::$variant(v).serialize(s)),* }
+                    } else {
+                        match self { $(Self::$variant(v) => PlainRef::$variant(v).serialize(s)),* }
+                    }
+                }
+            }
+
+            impl<'de> Deserialize<'de> for PacketPayload {
+                fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                    Ok(if d.is_human_readable() {
+                        match Tagged::deserialize(d)? { $(Tagged::$variant(v) => Self::$variant(v)),* }
+                    } else {
+                        match Plain::deserialize(d)? { $(Plain::$variant(v) => Self::$variant(v)),* }
+                    })
+                }
+            }
+        }
+    };
+}
+
+payload_wire! {
+    TelemetryData(Vec<SensorReading>),
+    CommandData(Command),
+    AcknowledgmentData(CommandAcknowledgment),
+    EmergencyAlert(EmergencyData),
+    HeartbeatData(SystemHealth),
+    ConfigUpdate(ConfigUpdate),
+    Handshake(Capabilities),
+}
+
+/// Runtime parameter overrides (key → value text), applied all-or-nothing by the
+/// satellite and acknowledged like a command, under `update_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigUpdate {
+    pub update_id: String,
+    pub overrides: BTreeMap<String, String>,
+    pub timestamp: Timestamp,
+}
+
+impl ConfigUpdate {
+    pub fn new<K: Into<String>, V: Into<String>>(overrides: impl IntoIterator<Item = (K, V)>) -> Self {
+        Self {
+            update_id: Uuid::new_v4().to_string(),
+            overrides: overrides.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+            timestamp: Utc::now(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -829,6 +1344,8 @@ pub struct EmergencyData {
     pub recommended_actions: Vec<String>,
     pub auto_recovery_attempted: bool,
     pub timestamp: Timestamp,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>, // e.g. "coalesced_count" from the OCS
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -841,6 +1358,10 @@ pub struct SystemHealth {
     pub active_tasks: u32,
     pub failed_tasks: u32,
     pub timestamp: Timestamp,
+    #[serde(default)]
+    pub deadline_misses: HashMap<String, u64>, // per-task deadline misses since boot
+    #[serde(default)]
+    pub ingress_backpressure: u64, // readings refused by a full sensor ingress since boot
 }
 
 // ---------- convenience creators (same as before) ----------
@@ -853,6 +1374,25 @@ impl CommunicationPacket {
         Self::create_packet(payload, source, PacketType::Telemetry)
     }
 
+    /// Telemetry packets for `readings`, halving the batch until each part encodes
+    /// under `SAFE_TELEMETRY_SIZE`, so a large batch seals instead of failing with
+    /// `TooLarge`. Readings keep their order; a small batch gives one packet.
+    pub fn new_telemetry_split(readings: Vec<SensorReading>, source: Source) -> Vec<Self> {
+        let mut out = Vec::new();
+        let mut pending = vec![readings];
+        while let Some(mut chunk) = pending.pop() {
+            let len = serde_json::to_vec(&chunk).map_or(usize::MAX, |b| b.len());
+            if len > SAFE_TELEMETRY_SIZE && chunk.len() > 1 {
+                let back = chunk.split_off(chunk.len() / 2);
+                pending.push(back);
+                pending.push(chunk);
+            } else {
+                out.push(Self::new_telemetry(chunk, source));
+            }
+        }
+        out
+    }
+
     pub fn new_command(command: Command, source: Source) -> Self {
         let payload = PacketPayload::CommandData(command);
         Self::create_packet(payload, source, PacketType::Command)
@@ -868,6 +1408,16 @@ impl CommunicationPacket {
         Self::create_packet(payload, source, PacketType::Ack)
     }
 
+    pub fn new_config(update: ConfigUpdate, source: Source) -> Self {
+        let payload = PacketPayload::ConfigUpdate(update);
+        Self::create_packet(payload, source, PacketType::Config)
+    }
+
+    pub fn new_handshake(offer: Capabilities, source: Source) -> Self {
+        let payload = PacketPayload::Handshake(offer);
+        Self::create_packet(payload, source, PacketType::Handshake)
+    }
+
     pub fn new_heartbeat(health: SystemHealth, source: Source) -> Self {
         let payload = PacketPayload::HeartbeatData(health);
         Self::create_packet(payload, source, PacketType::Heartbeat)
@@ -926,94 +1476,482 @@ impl CommunicationPacket {
 
 // ============================ AEAD Crypto Envelope ===========================
 
+use aes_gcm::Aes256Gcm;
 use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
 use chacha20poly1305::aead::{Aead, KeyInit, Payload};
 use chacha20poly1305::aead::rand_core::{OsRng, RngCore};
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore as _, SeedableRng};
+
+/// Codec used for the plaintext packet and the encrypted frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializationFormat {
+    #[default]
+    Json,
+    Bincode,
+}
+
+impl SerializationFormat {
+    /// Tag byte in front of a bincode frame body (JSON bodies always start with `{`).
+    pub const BINCODE_TAG: u8 = 0xB1;
+
+    fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Self::Bincode => bincode::serde::encode_to_vec(value, bincode::config::standard())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Self::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            Self::Bincode => bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+                .map(|(value, _)| value)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Why sealing or opening a frame failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CryptoError {
+    #[error("{what} too large: {len} bytes")]
+    TooLarge { what: &'static str, len: usize },
+    #[error("insufficient data: expected {expected} bytes, got {got}")]
+    Truncated { expected: usize, got: usize },
+    #[error("unknown key id {0}")]
+    KeyIdMismatch(u8),
+    #[error("nonce counter exhausted for key {0}; rotate keys")]
+    NonceExhausted(u8),
+    #[error("encryption failed")]
+    EncryptFailed,
+    #[error("authentication/decryption failed")]
+    DecryptFailed,
+    #[error("serialize {what}: {reason}")]
+    Serialize { what: &'static str, reason: String },
+    #[error("deserialize {what}: {reason}")]
+    Deserialize { what: &'static str, reason: String },
+    #[error("compression: {0}")]
+    Compression(String),
+    #[error("header mismatch between clear header and decrypted packet")]
+    HeaderMismatch,
+    #[error("payload size mismatch")]
+    PayloadSizeMismatch,
+    #[error("replay detected")]
+    Replay,
+    #[error("zero-length frame")]
+    EmptyFrame,
+    #[error("unsupported clear header version {0}")]
+    UnsupportedHeaderVersion(u8),
+    #[error("nonce length {got} does not match the cipher ({expected} bytes)")]
+    BadNonceLength { expected: usize, got: usize },
+    #[error("protocol version {got} differs from the negotiated {negotiated}")]
+    ProtocolVersion { got: u16, negotiated: u16 },
+}
+
+/// AEAD cipher of a frame. Both take a 32-byte key and a 12-byte nonce and append a
+/// 16-byte tag, so the envelope layout is the same for either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AeadAlgo {
+    #[default]
+    ChaCha20Poly1305,
+    /// For hosts with AES hardware acceleration
+    Aes256Gcm,
+}
+
+impl std::str::FromStr for AeadAlgo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "chacha20poly1305" | "chacha" => Ok(Self::ChaCha20Poly1305),
+            "aes256gcm" | "aes" => Ok(Self::Aes256Gcm),
+            _ => Err(format!("unknown AEAD '{s}' (expected chacha20-poly1305 or aes-256-gcm)")),
+        }
+    }
+}
+
+impl AeadAlgo {
+    /// Nonce size the cipher takes; a header nonce of any other length is refused.
+    pub fn nonce_len(self) -> usize {
+        match self {
+            Self::ChaCha20Poly1305 | Self::Aes256Gcm => 12,
+        }
+    }
+}
+
+/// A keyed instance of either AEAD.
+enum Cipher {
+    ChaCha(ChaCha20Poly1305),
+    Aes(Box<Aes256Gcm>), // expanded key schedule is ~1 KB
+}
+
+impl Cipher {
+    fn new(algo: AeadAlgo, key: &Key) -> Self {
+        match algo {
+            AeadAlgo::ChaCha20Poly1305 => Self::ChaCha(ChaCha20Poly1305::new(key)),
+            AeadAlgo::Aes256Gcm => Self::Aes(Box::new(Aes256Gcm::new(key))),
+        }
+    }
+
+    fn encrypt(&self, nonce: &Nonce, payload: Payload<'_, '_>) -> aead::Result<Vec<u8>> {
+        match self {
+            Self::ChaCha(c) => c.encrypt(nonce, payload),
+            Self::Aes(c) => c.encrypt(nonce, payload),
+        }
+    }
+
+    fn decrypt(&self, nonce: &Nonce, payload: Payload<'_, '_>) -> aead::Result<Vec<u8>> {
+        match self {
+            Self::ChaCha(c) => c.decrypt(nonce, payload),
+            Self::Aes(c) => c.decrypt(nonce, payload),
+        }
+    }
+}
+
+/// How `seal_to_bytes` picks AEAD nonces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonceStrategy {
+    /// 12 random bytes per frame.
+    #[default]
+    Random,
+    /// Random 4-byte prefix + big-endian 64-bit counter, per key: cannot repeat
+    /// until the counter runs out, at which point sealing fails until the key is rotated.
+    Counter,
+    /// ChaCha20 stream seeded with the given value, so a run can be replayed exactly.
+    /// Test mode only: the same seed and key repeat nonces across runs.
+    Seeded(u64),
+}
+
+/// Per-key state for `NonceStrategy::Counter`.
+#[derive(Debug)]
+struct NonceCounter {
+    prefix: [u8; 4],
+    next: u64,
+}
+
+/// `ClearHeader` layout written by `seal_to_bytes`: carries its version (so it is in the
+/// AAD) and a nonce sized by the cipher rather than fixed at 12 bytes.
+pub const HEADER_VERSION: u8 = 2;
+/// Headers from before versioning: no version field (and none in the AAD), 12-byte nonce.
+/// Still opened, as JSON frames; a bincode body needs the current layout.
+pub const LEGACY_HEADER_VERSION: u8 = 1;
+
+fn legacy_header_version() -> u8 {
+    LEGACY_HEADER_VERSION
+}
+
+fn is_legacy_header(v: &u8) -> bool {
+    *v == LEGACY_HEADER_VERSION
+}
 
 /// Clear header that stays outside encryption (needed for routing).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClearHeader {
+    // omitted for legacy headers so their AAD serializes exactly as it was sealed
+    #[serde(default = "legacy_header_version", skip_serializing_if = "is_legacy_header")]
+    pub header_version: u8,
     pub protocol_version: u16,
     pub packet_type: PacketType,
     pub sequence_number: u32,
     pub source: Source,
     pub destination: Source,
     pub key_id: u8,         // support key rotation
-    pub nonce: [u8; 12],    // AEAD nonce (unique per key)
-    // Optional: flags for compression, etc.
+    pub nonce: Vec<u8>,     // AEAD nonce (unique per key), `algo.nonce_len()` bytes
+    #[serde(default)]
+    pub format: SerializationFormat, // codec of the plaintext packet
+    #[serde(default)]
+    pub compressed: bool,   // plaintext is zstd-compressed (covered by AAD)
+    #[serde(default)]
+    pub algo: AeadAlgo,     // cipher used; part of the AAD, so it can't be downgraded
 }
 
 /// On-wire encrypted frame: [length (u32 BE)] [json(EncryptedFrame)]
+/// or [length (u32 BE)] [BINCODE_TAG] [bincode(EncryptedFrame)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EncryptedFrame {
     pub header: ClearHeader, // used as AAD
-    pub ciphertext: Vec<u8>, // includes Poly1305 tag appended
+    pub ciphertext: Vec<u8>, // includes the AEAD tag appended
+}
+
+impl EncryptedFrame {
+    /// Encode the frame body (without length prefix) using the header's format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        match self.header.format {
+            SerializationFormat::Json => SerializationFormat::Json.encode(self),
+            SerializationFormat::Bincode => {
+                let mut out = vec![SerializationFormat::BINCODE_TAG];
+                out.extend(SerializationFormat::Bincode.encode(self)?);
+                Ok(out)
+            }
+        }
+    }
+
+    /// Decode a frame body (without length prefix), detecting the format from its tag.
+    pub fn from_bytes(body: &[u8]) -> Result<Self, String> {
+        match body.first() {
+            Some(&SerializationFormat::BINCODE_TAG) => {
+                SerializationFormat::Bincode.decode(&body[1..])
+            }
+            _ => SerializationFormat::Json.decode(body),
+        }
+    }
+}
+
+/// Sliding window of recently accepted sequence numbers for one source.
+#[derive(Debug, Default)]
+struct ReplayWindow {
+    highest: Option<u32>,
+    seen: HashSet<u32>,
+}
+
+impl ReplayWindow {
+    /// `true` if `seq` is newer than the window floor and hasn't been seen yet.
+    fn is_fresh(&self, seq: u32, width: u32) -> bool {
+        match self.highest {
+            None => true,
+            Some(hi) if seq > hi => true,
+            Some(hi) if hi - seq >= width => false, // older than the window
+            Some(_) => !self.seen.contains(&seq),
+        }
+    }
+
+    /// Mark `seq` as seen, advancing the window if it is the newest so far.
+    fn record(&mut self, seq: u32, width: u32) {
+        let hi = match self.highest {
+            Some(hi) if hi >= seq => hi,
+            _ => {
+                self.highest = Some(seq);
+                seq
+            }
+        };
+        self.seen.insert(seq);
+        let floor = hi.saturating_sub(width - 1);
+        self.seen.retain(|&s| s >= floor);
+    }
 }
 
 pub struct CryptoContext {
-    key_id: u8,
-    key: Key, // type alias, no generics
+    active_key_id: u8,               // key used for sealing
+    keys: HashMap<u8, Key>,          // every key accepted when opening (rotation)
+    replay_window: u32,              // 0 disables replay protection
+    replay: Mutex<HashMap<Source, ReplayWindow>>,
+    format: SerializationFormat,     // codec used when sealing
+    compress: bool,                  // zstd the plaintext when it helps
+    algo: AeadAlgo,                  // cipher used when sealing
+    nonce_strategy: NonceStrategy,
+    nonce_counters: Mutex<HashMap<u8, NonceCounter>>, // per key id (Counter strategy)
+    seeded_rng: Mutex<Option<ChaCha20Rng>>, // Seeded strategy; created on first seal
+    offer: Capabilities,                    // advertised in a handshake
+    negotiated: Mutex<Option<Negotiated>>,  // set by `negotiate`; overrides format/algo/compress
 }
 
 impl CryptoContext {
-    pub fn new(key_id: u8, key_bytes_32: [u8; 32]) -> Self {
+    /// `replay_window` is the number of sequence numbers tracked per source
+    /// (`DEFAULT_REPLAY_WINDOW` is a good default; 0 disables the check).
+    pub fn new(key_id: u8, key_bytes_32: [u8; 32], replay_window: u32) -> Self {
+        Self::with_keys(HashMap::from([(key_id, key_bytes_32)]), key_id, replay_window)
+    }
+
+    /// Receiver holding several keys (e.g. old + new during rotation).
+    /// `active_key_id` selects the key used by `seal_to_bytes`.
+    pub fn with_keys(keys: HashMap<u8, [u8; 32]>, active_key_id: u8, replay_window: u32) -> Self {
         Self {
-            key_id,
-            key: Key::from_slice(&key_bytes_32).to_owned(),
+            active_key_id,
+            keys: keys
+                .into_iter()
+                .map(|(id, bytes)| (id, Key::from_slice(&bytes).to_owned()))
+                .collect(),
+            replay_window,
+            replay: Mutex::new(HashMap::new()),
+            format: SerializationFormat::default(),
+            compress: false,
+            algo: AeadAlgo::default(),
+            nonce_strategy: NonceStrategy::default(),
+            nonce_counters: Mutex::new(HashMap::new()),
+            seeded_rng: Mutex::new(None),
+            offer: Capabilities::all(),
+            negotiated: Mutex::new(None),
+        }
+    }
+
+    /// Advertise only these capabilities in a handshake (default: everything this build speaks).
+    pub fn with_capabilities(mut self, offer: Capabilities) -> Self {
+        self.offer = offer;
+        self
+    }
+
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.offer
+    }
+
+    /// Settle on a common set with the peer's advertisement; on success sealing switches
+    /// to it and frames of any other protocol version are refused. A failed negotiation
+    /// leaves the context as it was.
+    pub fn negotiate(&self, peer: &Capabilities) -> Result<Negotiated, NegotiationError> {
+        let n = self.offer.negotiate(peer)?;
+        *self.negotiated.lock().unwrap_or_else(|e| e.into_inner()) = Some(n);
+        Ok(n)
+    }
+
+    pub fn negotiated(&self) -> Option<Negotiated> {
+        *self.negotiated.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Seal with the given codec; opening always follows the frame's own tag.
+    pub fn with_format(mut self, format: SerializationFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// zstd-compress packets before encryption (kept only if smaller).
+    pub fn with_compression(mut self, on: bool) -> Self {
+        self.compress = on;
+        self
+    }
+
+    /// Seal with the given AEAD; opening always follows the frame's own tag.
+    pub fn with_algo(mut self, algo: AeadAlgo) -> Self {
+        self.algo = algo;
+        self
+    }
+
+    /// Choose how sealing nonces are generated.
+    pub fn with_nonce_strategy(mut self, strategy: NonceStrategy) -> Self {
+        self.nonce_strategy = strategy;
+        self
+    }
+
+    /// Add (or replace) a key that frames may be opened with.
+    pub fn add_key(&mut self, key_id: u8, key_bytes_32: [u8; 32]) {
+        self.keys.insert(key_id, Key::from_slice(&key_bytes_32).to_owned());
+        // a replaced key starts a fresh nonce sequence
+        self.nonce_counters
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&key_id);
+    }
+
+    /// Switch the sealing key; the key must already be known.
+    pub fn set_active_key(&mut self, key_id: u8) -> Result<(), CryptoError> {
+        if !self.keys.contains_key(&key_id) {
+            return Err(CryptoError::KeyIdMismatch(key_id));
         }
+        self.active_key_id = key_id;
+        Ok(())
     }
 
-    fn cipher(&self) -> ChaCha20Poly1305 {
-        ChaCha20Poly1305::new(&self.key)
+    pub fn active_key_id(&self) -> u8 {
+        self.active_key_id
     }
 
-    fn gen_nonce() -> [u8; 12] {
+    fn cipher(&self, key_id: u8, algo: AeadAlgo) -> Option<Cipher> {
+        self.keys.get(&key_id).map(|key| Cipher::new(algo, key))
+    }
+
+    fn gen_nonce(&self, key_id: u8) -> Result<[u8; 12], CryptoError> {
         let mut nonce = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce);
-        nonce
+        match self.nonce_strategy {
+            NonceStrategy::Random => OsRng.fill_bytes(&mut nonce),
+            NonceStrategy::Seeded(seed) => self
+                .seeded_rng
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get_or_insert_with(|| ChaCha20Rng::seed_from_u64(seed))
+                .fill_bytes(&mut nonce),
+            NonceStrategy::Counter => {
+                let mut counters = self.nonce_counters.lock().unwrap_or_else(|e| e.into_inner());
+                let c = counters.entry(key_id).or_insert_with(|| {
+                    let mut prefix = [0u8; 4];
+                    OsRng.fill_bytes(&mut prefix);
+                    NonceCounter { prefix, next: 0 }
+                });
+                if c.next == u64::MAX {
+                    return Err(CryptoError::NonceExhausted(key_id));
+                }
+                nonce[..4].copy_from_slice(&c.prefix);
+                nonce[4..].copy_from_slice(&c.next.to_be_bytes());
+                c.next += 1;
+            }
+        }
+        Ok(nonce)
     }
 
     /// Seal a logical packet to **length-prefixed encrypted bytes** ready to send.
-    pub fn seal_to_bytes(&self, packet: &CommunicationPacket) -> Result<Vec<u8>, String> {
+    pub fn seal_to_bytes(&self, packet: &CommunicationPacket) -> Result<Vec<u8>, CryptoError> {
+        // Negotiated settings, once a handshake has completed, override the builders'
+        let (format, algo, compress) = match self.negotiated() {
+            Some(n) => (n.format, n.algo, n.compress),
+            None => (self.format, self.algo, self.compress),
+        };
+
         // Serialize the logical packet (payload+header)
-        let serialized = serde_json::to_vec(packet)
-            .map_err(|e| format!("serialize packet: {e}"))?;
+        let serialized = format
+            .encode(packet)
+            .map_err(|reason| CryptoError::Serialize { what: "packet", reason })?;
 
         if serialized.len() > MAX_PACKET_SIZE {
-            return Err(format!("Packet too large before encryption: {}", serialized.len()));
+            return Err(CryptoError::TooLarge { what: "packet", len: serialized.len() });
         }
 
-        let nonce_arr = Self::gen_nonce();
+        // Optional compression; only worth it when the result is smaller
+        let mut compressed = false;
+        let serialized = if compress {
+            let packed = zstd::bulk::compress(&serialized, ZSTD_LEVEL)
+                .map_err(|e| CryptoError::Compression(e.to_string()))?;
+            if packed.len() < serialized.len() {
+                compressed = true;
+                packed
+            } else {
+                serialized
+            }
+        } else {
+            serialized
+        };
+
+        let cipher = self
+            .cipher(self.active_key_id, algo)
+            .ok_or(CryptoError::KeyIdMismatch(self.active_key_id))?;
+
+        let nonce_arr = self.gen_nonce(self.active_key_id)?;
         let nonce = Nonce::from_slice(&nonce_arr);
 
         let clear = ClearHeader {
+            header_version: HEADER_VERSION,
             protocol_version: PROTOCOL_VERSION,
             packet_type: packet.header.packet_type,
             sequence_number: packet.header.sequence_number,
             source: packet.header.source,
             destination: packet.header.destination,
-            key_id: self.key_id,
-            nonce: nonce_arr,
+            key_id: self.active_key_id,
+            nonce: nonce_arr.to_vec(),
+            format,
+            compressed,
+            algo,
         };
 
-        let aad = serde_json::to_vec(&clear).map_err(|e| format!("serialize AAD: {e}"))?;
+        let aad = serde_json::to_vec(&clear)
+            .map_err(|e| CryptoError::Serialize { what: "AAD", reason: e.to_string() })?;
 
-        let cipher = self.cipher();
         let ciphertext = cipher
             .encrypt(nonce, Payload { msg: &serialized, aad: &aad })
-            .map_err(|_| "encryption failed".to_string())?;
+            .map_err(|_| CryptoError::EncryptFailed)?;
 
         let frame = EncryptedFrame {
             header: clear,
             ciphertext,
         };
 
-        // Length-prefixed JSON framing for the encrypted frame
-        let frame_bytes =
-            serde_json::to_vec(&frame).map_err(|e| format!("serialize frame: {e}"))?;
+        // Length-prefixed framing for the encrypted frame
+        let frame_bytes = frame
+            .to_bytes()
+            .map_err(|reason| CryptoError::Serialize { what: "frame", reason })?;
 
         if frame_bytes.len() > MAX_PACKET_SIZE {
-            return Err(format!("Encrypted frame too large: {}", frame_bytes.len()));
+            return Err(CryptoError::TooLarge { what: "encrypted frame", len: frame_bytes.len() });
         }
 
         let mut out = Vec::with_capacity(frame_bytes.len() + 4);
@@ -1024,40 +1962,59 @@ impl CryptoContext {
 
     /// Open **one complete frame** from a contiguous buffer (length-prefixed),
     /// returning the logical `CommunicationPacket`.
-    pub fn open_from_bytes(&self, buf: &[u8]) -> Result<CommunicationPacket, String> {
-        if buf.len() < 4 {
-            return Err("insufficient data: need 4-byte length prefix".into());
-        }
-        let len = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
-        if buf.len() < 4 + len {
-            return Err(format!(
-                "insufficient data: expected {} bytes, got {}",
-                4 + len,
-                buf.len()
-            ));
+    pub fn open_from_bytes(&self, buf: &[u8]) -> Result<CommunicationPacket, CryptoError> {
+        let total = framed_len(buf)?.ok_or(CryptoError::Truncated { expected: 4, got: buf.len() })?;
+        if buf.len() < total {
+            return Err(CryptoError::Truncated { expected: total, got: buf.len() });
         }
-        let json = &buf[4..4 + len];
-        let frame: EncryptedFrame =
-            serde_json::from_slice(json).map_err(|e| format!("frame deserialization: {e}"))?;
+        let frame = EncryptedFrame::from_bytes(&buf[4..total])
+            .map_err(|reason| CryptoError::Deserialize { what: "frame", reason })?;
+        self.open_frame(&frame)
+    }
 
-        if frame.header.key_id != self.key_id {
-            return Err(format!(
-                "key id mismatch: frame={}, ctx={}",
-                frame.header.key_id, self.key_id
-            ));
+    /// Authenticate + decrypt an already-deframed `EncryptedFrame`
+    /// (e.g. one produced by `FrameReader`).
+    pub fn open_frame(&self, frame: &EncryptedFrame) -> Result<CommunicationPacket, CryptoError> {
+        // Refuse layouts this build doesn't know before trusting any other field
+        let version = frame.header.header_version;
+        if version != HEADER_VERSION && version != LEGACY_HEADER_VERSION {
+            return Err(CryptoError::UnsupportedHeaderVersion(version));
         }
+        let expected = frame.header.algo.nonce_len();
+        if frame.header.nonce.len() != expected {
+            return Err(CryptoError::BadNonceLength { expected, got: frame.header.nonce.len() });
+        }
+        if let Some(n) = self.negotiated()
+            && frame.header.protocol_version != n.protocol_version
+        {
+            return Err(CryptoError::ProtocolVersion { got: frame.header.protocol_version, negotiated: n.protocol_version });
+        }
+
+        let cipher = self
+            .cipher(frame.header.key_id, frame.header.algo)
+            .ok_or(CryptoError::KeyIdMismatch(frame.header.key_id))?;
 
         let aad = serde_json::to_vec(&frame.header)
-            .map_err(|e| format!("AAD serialization: {e}"))?;
+            .map_err(|e| CryptoError::Serialize { what: "AAD", reason: e.to_string() })?;
         let nonce = Nonce::from_slice(&frame.header.nonce);
 
-        let cipher = self.cipher();
         let plaintext = cipher
             .decrypt(nonce, Payload { msg: &frame.ciphertext, aad: &aad })
-            .map_err(|_| "authentication/decryption failed".to_string())?;
+            .map_err(|_| CryptoError::DecryptFailed)?;
+
+        // Bounded decompression so a hostile frame can't balloon in memory
+        let plaintext = if frame.header.compressed {
+            zstd::bulk::decompress(&plaintext, MAX_PACKET_SIZE)
+                .map_err(|e| CryptoError::Compression(e.to_string()))?
+        } else {
+            plaintext
+        };
 
-        let packet: CommunicationPacket = serde_json::from_slice(&plaintext)
-            .map_err(|e| format!("packet deserialization: {e}"))?;
+        let packet: CommunicationPacket = frame
+            .header
+            .format
+            .decode(&plaintext)
+            .map_err(|reason| CryptoError::Deserialize { what: "packet", reason })?;
 
         // Optional: sanity checks (version, type, seq) vs clear header
         if packet.header.protocol_version != frame.header.protocol_version
@@ -1066,13 +2023,185 @@ impl CryptoContext {
             || packet.header.source != frame.header.source
             || packet.header.destination != frame.header.destination
         {
-            return Err("header mismatch between clear header and decrypted packet".into());
+            return Err(CryptoError::HeaderMismatch);
+        }
+
+        // Declared size is the JSON payload length (see `create_packet`), whatever the codec
+        let payload_len = serde_json::to_vec(&packet.payload)
+            .map_err(|e| CryptoError::Serialize { what: "payload", reason: e.to_string() })?
+            .len();
+        if payload_len != packet.header.payload_size_bytes as usize {
+            return Err(CryptoError::PayloadSizeMismatch);
+        }
+
+        // Anti-replay: only authenticated frames may advance the window
+        if self.replay_window > 0 {
+            let mut windows = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+            let window = windows.entry(frame.header.source).or_default();
+            if !window.is_fresh(frame.header.sequence_number, self.replay_window) {
+                return Err(CryptoError::Replay);
+            }
+            window.record(frame.header.sequence_number, self.replay_window);
         }
 
         Ok(packet)
     }
 }
 
+// ============================ Capability Handshake ===========================
+
+/// What one side can speak, advertised in `PacketPayload::Handshake` before telemetry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub protocol_versions: Vec<u16>,
+    pub formats: Vec<SerializationFormat>,
+    pub algos: Vec<AeadAlgo>,
+    pub compression: bool,
+}
+
+/// The common set both sides settled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Negotiated {
+    pub protocol_version: u16,
+    pub format: SerializationFormat,
+    pub algo: AeadAlgo,
+    pub compress: bool,
+}
+
+/// Why two advertisements have nothing in common.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NegotiationError {
+    #[error("incompatible protocol versions: ours {ours:?}, peer {peer:?}")]
+    NoCommonVersion { ours: Vec<u16>, peer: Vec<u16> },
+    #[error("no common serialization format: ours {ours:?}, peer {peer:?}")]
+    NoCommonFormat { ours: Vec<SerializationFormat>, peer: Vec<SerializationFormat> },
+    #[error("no common AEAD: ours {ours:?}, peer {peer:?}")]
+    NoCommonAlgo { ours: Vec<AeadAlgo>, peer: Vec<AeadAlgo> },
+}
+
+// Fixed preference orders, so both ends pick the same option from each other's lists
+const FORMAT_PREFERENCE: [SerializationFormat; 2] = [SerializationFormat::Bincode, SerializationFormat::Json];
+const ALGO_PREFERENCE: [AeadAlgo; 2] = [AeadAlgo::ChaCha20Poly1305, AeadAlgo::Aes256Gcm];
+
+impl Capabilities {
+    /// Everything this build speaks.
+    pub fn all() -> Self {
+        Self {
+            protocol_versions: vec![PROTOCOL_VERSION],
+            formats: FORMAT_PREFERENCE.to_vec(),
+            algos: ALGO_PREFERENCE.to_vec(),
+            compression: true,
+        }
+    }
+
+    /// Highest common protocol version, first shared format and AEAD in the fixed
+    /// preference order, compression only if both offer it. Symmetric: either side gets
+    /// the same result.
+    pub fn negotiate(&self, peer: &Capabilities) -> Result<Negotiated, NegotiationError> {
+        let protocol_version = self
+            .protocol_versions
+            .iter()
+            .filter(|v| peer.protocol_versions.contains(v))
+            .max()
+            .copied()
+            .ok_or_else(|| NegotiationError::NoCommonVersion {
+                ours: self.protocol_versions.clone(),
+                peer: peer.protocol_versions.clone(),
+            })?;
+        let format = FORMAT_PREFERENCE
+            .into_iter()
+            .find(|f| self.formats.contains(f) && peer.formats.contains(f))
+            .ok_or_else(|| NegotiationError::NoCommonFormat { ours: self.formats.clone(), peer: peer.formats.clone() })?;
+        let algo = ALGO_PREFERENCE
+            .into_iter()
+            .find(|a| self.algos.contains(a) && peer.algos.contains(a))
+            .ok_or_else(|| NegotiationError::NoCommonAlgo { ours: self.algos.clone(), peer: peer.algos.clone() })?;
+        Ok(Negotiated { protocol_version, format, algo, compress: self.compression && peer.compression })
+    }
+}
+
+// ============================== Stream Framing ==============================
+
+/// Total size (4-byte big-endian prefix included) of the frame announced at the head of
+/// `buf`, or `None` until the prefix is complete. Rejects a zero length and one past
+/// `MAX_PACKET_SIZE`, and never overflows computing `4 + len`.
+pub fn framed_len(buf: &[u8]) -> Result<Option<usize>, CryptoError> {
+    let Some(prefix) = buf.first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(*prefix) as usize;
+    if len == 0 {
+        return Err(CryptoError::EmptyFrame);
+    }
+    let total = len
+        .checked_add(4)
+        .ok_or(CryptoError::TooLarge { what: "frame", len })?;
+    if len > MAX_PACKET_SIZE {
+        return Err(CryptoError::TooLarge { what: "frame", len });
+    }
+    Ok(Some(total))
+}
+
+/// Incremental reader for length-prefixed frames arriving over a byte stream
+/// (e.g. TCP), where one frame may be split across several reads.
+#[derive(Debug, Default)]
+pub struct FrameReader {
+    buf: Vec<u8>,
+    rejected: u64,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the next chunk read from the stream.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Next complete frame, or `None` until more bytes arrive.
+    /// Oversized or undecodable frames are discarded and counted in `rejected()`.
+    pub fn next_frame(&mut self) -> Option<EncryptedFrame> {
+        loop {
+            let total = match framed_len(&self.buf) {
+                Ok(Some(total)) => total,
+                Ok(None) => return None,
+                Err(CryptoError::EmptyFrame) => {
+                    // a bare prefix: skip it and carry on with the next frame
+                    self.rejected += 1;
+                    self.buf.drain(..4);
+                    continue;
+                }
+                Err(_) => {
+                    // a bogus length leaves no way to resync the stream: drop what we hold
+                    self.rejected += 1;
+                    self.buf.clear();
+                    return None;
+                }
+            };
+            if self.buf.len() < total {
+                return None;
+            }
+            let body: Vec<u8> = self.buf.drain(..total).skip(4).collect();
+            match EncryptedFrame::from_bytes(&body) {
+                Ok(frame) => return Some(frame),
+                Err(_) => self.rejected += 1, // skip it, try the next frame
+            }
+        }
+    }
+
+    /// Bytes held back waiting for the rest of a frame.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Frames dropped as oversized or malformed.
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+}
+
 // ================================ Tests =====================================
 
 #[cfg(test)]
@@ -1090,7 +2219,7 @@ mod tests {
 
         // Crypto
         let key = [7u8; 32];
-        let crypto = CryptoContext::new(1, key);
+        let crypto = CryptoContext::new(1, key, DEFAULT_REPLAY_WINDOW);
 
         // Seal → bytes → open
         let bytes = crypto.seal_to_bytes(&pkt).expect("seal");
@@ -1110,7 +2239,7 @@ mod tests {
         let cmd = Command::thermal_normal_operation(1);
         let pkt = CommunicationPacket::new_command(cmd, Source::GroundControl);
 
-        let crypto = CryptoContext::new(42, [9u8; 32]);
+        let crypto = CryptoContext::new(42, [9u8; 32], DEFAULT_REPLAY_WINDOW);
         let bytes = crypto.seal_to_bytes(&pkt).unwrap();
         let back = crypto.open_from_bytes(&bytes).unwrap();
 
@@ -1118,4 +2247,593 @@ mod tests {
         assert_eq!(back.header.source, Source::GroundControl);
         assert_eq!(back.header.destination, Source::Satellite);
     }
+
+    fn heartbeat_with_seq(seq: u32) -> CommunicationPacket {
+        let health = SystemHealth {
+            overall_status: "nominal".into(),
+            cpu_usage_percent: 0.0,
+            memory_usage_percent: 0.0,
+            disk_usage_percent: 0.0,
+            uptime_seconds: 0,
+            active_tasks: 0,
+            failed_tasks: 0,
+            timestamp: Utc::now(),
+            deadline_misses: HashMap::new(),
+            ingress_backpressure: 0,
+        };
+        let mut pkt = CommunicationPacket::new_heartbeat(health, Source::Satellite);
+        // pin the sequence number; GLOBAL_SEQ is shared with concurrently running tests
+        pkt.header.sequence_number = seq;
+        pkt
+    }
+
+    #[test]
+    fn replayed_frame_is_rejected() {
+        let crypto = CryptoContext::new(1, [3u8; 32], DEFAULT_REPLAY_WINDOW);
+
+        let first = crypto.seal_to_bytes(&heartbeat_with_seq(10)).unwrap();
+        assert!(crypto.open_from_bytes(&first).is_ok());
+        assert_eq!(crypto.open_from_bytes(&first).unwrap_err(), CryptoError::Replay);
+
+        let fresh = crypto.seal_to_bytes(&heartbeat_with_seq(11)).unwrap();
+        assert!(crypto.open_from_bytes(&fresh).is_ok());
+    }
+
+    #[test]
+    fn replay_window_tolerates_reordering_but_not_stale_frames() {
+        let crypto = CryptoContext::new(1, [3u8; 32], 4);
+        let frames: Vec<Vec<u8>> = (100..106)
+            .map(|seq| crypto.seal_to_bytes(&heartbeat_with_seq(seq)).unwrap())
+            .collect();
+
+        // mild reordering inside the window is fine
+        assert!(crypto.open_from_bytes(&frames[1]).is_ok());
+        assert!(crypto.open_from_bytes(&frames[0]).is_ok());
+
+        // jump ahead: window now covers 102..=105
+        assert!(crypto.open_from_bytes(&frames[5]).is_ok());
+        assert!(crypto.open_from_bytes(&frames[2]).is_ok());
+        assert!(crypto.open_from_bytes(&frames[1]).is_err()); // fell out of the window
+        assert!(crypto.open_from_bytes(&frames[2]).is_err()); // seen already
+        assert!(crypto.open_from_bytes(&frames[4]).is_ok());
+    }
+
+    #[test]
+    fn key_rotation_accepts_old_and_new_frames() {
+        let mut crypto = CryptoContext::new(1, [1u8; 32], DEFAULT_REPLAY_WINDOW);
+        let old = crypto.seal_to_bytes(&heartbeat_with_seq(200)).unwrap();
+
+        crypto.add_key(2, [2u8; 32]);
+        crypto.set_active_key(2).unwrap();
+        let new = crypto.seal_to_bytes(&heartbeat_with_seq(201)).unwrap();
+
+        assert_eq!(crypto.open_from_bytes(&old).unwrap().header.sequence_number, 200);
+        assert_eq!(crypto.open_from_bytes(&new).unwrap().header.sequence_number, 201);
+
+        // a receiver that only knows the new key rejects the old frame
+        let rx = CryptoContext::with_keys(HashMap::from([(2, [2u8; 32])]), 2, DEFAULT_REPLAY_WINDOW);
+        assert_eq!(rx.open_from_bytes(&old).unwrap_err(), CryptoError::KeyIdMismatch(1));
+        assert!(rx.open_from_bytes(&new).is_ok());
+        assert!(crypto.set_active_key(9).is_err());
+    }
+
+    #[test]
+    fn counter_nonces_are_unique_and_increment() {
+        let crypto = CryptoContext::new(1, [3u8; 32], 0).with_nonce_strategy(NonceStrategy::Counter);
+        let pkt = heartbeat_with_seq(1);
+
+        let nonces: Vec<Vec<u8>> = (0..500)
+            .map(|_| {
+                let bytes = crypto.seal_to_bytes(&pkt).unwrap();
+                EncryptedFrame::from_bytes(&bytes[4..]).unwrap().header.nonce
+            })
+            .collect();
+        assert_eq!(nonces.iter().collect::<HashSet<_>>().len(), nonces.len());
+        for (i, n) in nonces.iter().enumerate() {
+            assert_eq!(n[..4], nonces[0][..4]); // fixed per-key prefix
+            assert_eq!(u64::from_be_bytes(n[4..].try_into().unwrap()), i as u64);
+        }
+
+        // exhaustion forces a key rotation
+        crypto.nonce_counters.lock().unwrap().get_mut(&1).unwrap().next = u64::MAX;
+        assert_eq!(crypto.seal_to_bytes(&pkt).unwrap_err(), CryptoError::NonceExhausted(1));
+    }
+
+    #[test]
+    fn seeded_nonces_repeat_per_seed() {
+        let seq = |seed| {
+            let crypto = CryptoContext::new(1, [5u8; 32], 0).with_nonce_strategy(NonceStrategy::Seeded(seed));
+            (0..10).map(|_| crypto.gen_nonce(1).unwrap()).collect::<Vec<_>>()
+        };
+        let a = seq(42);
+        assert_eq!(a, seq(42));
+        assert_ne!(a, seq(43));
+        assert_eq!(a.iter().collect::<HashSet<_>>().len(), a.len());
+    }
+
+    #[test]
+    fn oversized_batch_is_split_into_sealable_packets() {
+        let thermal = ThermalSensor::new(1, "CPU");
+        let readings: Vec<_> = (0..4000).map(|i| thermal.create_reading(60.0, i)).collect();
+        let crypto = CryptoContext::new(1, [5u8; 32], 0);
+        let whole = CommunicationPacket::new_telemetry(readings.clone(), Source::Satellite);
+        assert!(matches!(crypto.seal_to_bytes(&whole), Err(CryptoError::TooLarge { .. })));
+
+        let parts = CommunicationPacket::new_telemetry_split(readings.clone(), Source::Satellite);
+        assert!(parts.len() > 1);
+        let mut back = Vec::new();
+        for pkt in &parts {
+            let frame = crypto.seal_to_bytes(pkt).unwrap();
+            let PacketPayload::TelemetryData(r) = crypto.open_from_bytes(&frame).unwrap().payload else {
+                panic!("expected telemetry");
+            };
+            back.extend(r);
+        }
+        assert_eq!(back, readings);
+        assert_eq!(CommunicationPacket::new_telemetry_split(readings[..3].to_vec(), Source::Satellite).len(), 1);
+    }
+
+    #[test]
+    fn each_crypto_failure_has_its_own_variant() {
+        let crypto = CryptoContext::new(1, [6u8; 32], 0);
+        assert_eq!(crypto.open_from_bytes(&[0, 0]).unwrap_err(), CryptoError::Truncated { expected: 4, got: 2 });
+        assert_eq!(
+            crypto.open_from_bytes(&[0, 0, 0, 100, 1]).unwrap_err(),
+            CryptoError::Truncated { expected: 104, got: 5 }
+        );
+        assert!(matches!(
+            crypto.open_from_bytes(&[0, 0, 0, 3, b'x', b'y', b'z']).unwrap_err(),
+            CryptoError::Deserialize { what: "frame", .. }
+        ));
+
+        let thermal = ThermalSensor::new(1, "CPU");
+        let huge = CommunicationPacket::new_telemetry(
+            (0..4000).map(|i| thermal.create_reading(50.0, i)).collect(),
+            Source::Satellite,
+        );
+        assert!(matches!(crypto.seal_to_bytes(&huge).unwrap_err(), CryptoError::TooLarge { what: "packet", .. }));
+
+        // validly sealed, but the clear header disagrees with the packet inside
+        let pkt = heartbeat_with_seq(300);
+        let mut clear = ClearHeader {
+            header_version: HEADER_VERSION,
+            protocol_version: PROTOCOL_VERSION,
+            packet_type: pkt.header.packet_type,
+            sequence_number: 301,
+            source: pkt.header.source,
+            destination: pkt.header.destination,
+            key_id: 1,
+            nonce: vec![0u8; 12],
+            format: SerializationFormat::Json,
+            compressed: false,
+            algo: AeadAlgo::ChaCha20Poly1305,
+        };
+        let aad = serde_json::to_vec(&clear).unwrap();
+        let msg = serde_json::to_vec(&pkt).unwrap();
+        let ciphertext = crypto
+            .cipher(1, clear.algo)
+            .unwrap()
+            .encrypt(Nonce::from_slice(&clear.nonce), Payload { msg: &msg, aad: &aad })
+            .unwrap();
+        let frame = EncryptedFrame { header: clear.clone(), ciphertext };
+        assert_eq!(crypto.open_frame(&frame).unwrap_err(), CryptoError::HeaderMismatch);
+
+        clear.key_id = 4;
+        assert_eq!(
+            crypto.open_frame(&EncryptedFrame { header: clear, ..frame }).unwrap_err(),
+            CryptoError::KeyIdMismatch(4)
+        );
+    }
+
+    #[test]
+    fn header_versions_are_checked_before_decryption() {
+        let crypto = CryptoContext::new(1, [5u8; 32], 0);
+        let pkt = heartbeat_with_seq(40);
+        let sealed = EncryptedFrame::from_bytes(&crypto.seal_to_bytes(&pkt).unwrap()[4..]).unwrap();
+        assert_eq!(sealed.header.header_version, HEADER_VERSION);
+        assert!(serde_json::to_string(&sealed.header).unwrap().contains("\"header_version\":2"));
+
+        let mut future = sealed.clone();
+        future.header.header_version = 9;
+        let body = future.to_bytes().unwrap();
+        let bytes = [&(body.len() as u32).to_be_bytes()[..], &body].concat();
+        assert_eq!(crypto.open_from_bytes(&bytes).unwrap_err(), CryptoError::UnsupportedHeaderVersion(9));
+        assert_eq!(
+            CryptoError::UnsupportedHeaderVersion(9).to_string(),
+            "unsupported clear header version 9"
+        );
+
+        let mut short = sealed;
+        short.header.nonce.truncate(8);
+        assert_eq!(crypto.open_frame(&short).unwrap_err(), CryptoError::BadNonceLength { expected: 12, got: 8 });
+
+        // a pre-versioning JSON frame (no header_version, none in its AAD) still opens
+        let mut clear = short.header;
+        clear.header_version = LEGACY_HEADER_VERSION;
+        clear.nonce = vec![1u8; 12];
+        let aad = serde_json::to_vec(&clear).unwrap();
+        assert!(!String::from_utf8_lossy(&aad).contains("header_version"));
+        let ciphertext = crypto
+            .cipher(1, clear.algo)
+            .unwrap()
+            .encrypt(Nonce::from_slice(&clear.nonce), Payload { msg: &serde_json::to_vec(&pkt).unwrap(), aad: &aad })
+            .unwrap();
+        let legacy = EncryptedFrame::from_bytes(&serde_json::to_vec(&EncryptedFrame { header: clear, ciphertext }).unwrap()).unwrap();
+        assert_eq!(legacy.header.header_version, LEGACY_HEADER_VERSION);
+        assert_eq!(crypto.open_frame(&legacy).unwrap().header.sequence_number, 40);
+    }
+
+    #[test]
+    fn both_aead_algorithms_roundtrip() {
+        for algo in [AeadAlgo::ChaCha20Poly1305, AeadAlgo::Aes256Gcm] {
+            let pkt = CommunicationPacket::new_command(Command::thermal_normal_operation(1), Source::GroundControl);
+            let sealer = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW).with_algo(algo);
+            // the receiver's own setting doesn't matter: the header tag picks the cipher
+            let opener = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW);
+            let bytes = sealer.seal_to_bytes(&pkt).unwrap();
+            let frame = EncryptedFrame::from_bytes(&bytes[4..]).unwrap();
+            assert_eq!(frame.header.algo, algo);
+            assert_eq!(opener.open_from_bytes(&bytes).unwrap().header.sequence_number, pkt.header.sequence_number);
+        }
+        assert_eq!("aes-256-gcm".parse(), Ok(AeadAlgo::Aes256Gcm));
+        assert!("rot13".parse::<AeadAlgo>().is_err());
+    }
+
+    #[test]
+    fn handshake_settles_on_common_capabilities_or_fails_cleanly() {
+        let sat = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW).with_algo(AeadAlgo::Aes256Gcm);
+        let ground = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW).with_capabilities(Capabilities {
+            protocol_versions: vec![PROTOCOL_VERSION, PROTOCOL_VERSION + 1],
+            formats: vec![SerializationFormat::Json],
+            algos: vec![AeadAlgo::Aes256Gcm, AeadAlgo::ChaCha20Poly1305],
+            compression: false,
+        });
+
+        // each side negotiates from the other's advertisement, carried in a handshake packet
+        let hello = CommunicationPacket::new_handshake(sat.capabilities().clone(), Source::Satellite);
+        let PacketPayload::Handshake(offer) = ground.open_from_bytes(&sat.seal_to_bytes(&hello).unwrap()).unwrap().payload else {
+            panic!("expected a handshake");
+        };
+        let agreed = ground.negotiate(&offer).unwrap();
+        assert_eq!(sat.negotiate(ground.capabilities()).unwrap(), agreed);
+        assert_eq!(agreed, Negotiated {
+            protocol_version: PROTOCOL_VERSION,
+            format: SerializationFormat::Json,
+            algo: AeadAlgo::ChaCha20Poly1305,
+            compress: false,
+        });
+
+        // sealing follows the agreement, not the builder's AES setting
+        let bytes = sat.seal_to_bytes(&telemetry_batch(4)).unwrap();
+        let frame = EncryptedFrame::from_bytes(&bytes[4..]).unwrap();
+        assert_eq!((frame.header.algo, frame.header.format), (AeadAlgo::ChaCha20Poly1305, SerializationFormat::Json));
+        assert!(ground.open_from_bytes(&bytes).is_ok());
+        // and a frame claiming another protocol version is refused before decryption
+        let mut other = frame.clone();
+        other.header.protocol_version += 1;
+        let mut framed = other.to_bytes().unwrap();
+        framed.splice(0..0, (framed.len() as u32).to_be_bytes());
+        assert_eq!(
+            ground.open_from_bytes(&framed).unwrap_err(),
+            CryptoError::ProtocolVersion { got: PROTOCOL_VERSION + 1, negotiated: PROTOCOL_VERSION }
+        );
+
+        // disjoint versions: a clear error, and the context keeps its old settings
+        let future = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW)
+            .with_capabilities(Capabilities { protocol_versions: vec![PROTOCOL_VERSION + 7], ..Capabilities::all() });
+        let err = future.negotiate(&Capabilities::all()).unwrap_err();
+        assert!(matches!(err, NegotiationError::NoCommonVersion { .. }));
+        assert!(err.to_string().contains("incompatible protocol versions"));
+        assert_eq!(future.negotiated(), None);
+    }
+
+    #[test]
+    fn relabelled_aead_tag_fails_to_open() {
+        let pkt = CommunicationPacket::new_command(Command::thermal_normal_operation(1), Source::GroundControl);
+        let crypto = CryptoContext::new(1, [4u8; 32], 0).with_algo(AeadAlgo::Aes256Gcm);
+        let mut frame = EncryptedFrame::from_bytes(&crypto.seal_to_bytes(&pkt).unwrap()[4..]).unwrap();
+
+        // AES-GCM ciphertext presented as ChaCha20-Poly1305 (a downgrade attempt)
+        frame.header.algo = AeadAlgo::ChaCha20Poly1305;
+        assert_eq!(crypto.open_frame(&frame).unwrap_err(), CryptoError::DecryptFailed);
+        frame.header.algo = AeadAlgo::Aes256Gcm;
+        assert!(crypto.open_frame(&frame).is_ok());
+    }
+
+    #[test]
+    fn validate_ranges_rejects_non_finite_and_impossible_values() {
+        let thermal = ThermalSensor::new(1, "CPU");
+        let power = PowerSensor::new(2, "Main Bus");
+        let attitude = AttitudeSensor::new(3, "IMU");
+        let radiation = RadiationSensor::new(7, "Payload Bay");
+        assert!(thermal.create_reading(45.0, 0).validate_ranges().is_ok());
+        assert!(power.create_reading(90.0, 12.0, -2.0, 24.0, 0).validate_ranges().is_ok());
+        assert!(attitude.create_reading(-3.0, 2.0, 179.0, 0).validate_ranges().is_ok());
+        assert!(radiation.create_reading(0.1, 5.0, 0).validate_ranges().is_ok());
+
+        // NaN / infinity anywhere
+        assert!(thermal.create_reading(f64::NAN, 0).validate_ranges().unwrap_err().contains("value1"));
+        assert!(power.create_reading(50.0, f64::INFINITY, 1.0, 1.0, 0).validate_ranges().is_err());
+        let mut r = thermal.create_reading(45.0, 0);
+        r.jitter_ms = f64::NEG_INFINITY;
+        assert!(r.validate_ranges().unwrap_err().contains("jitter_ms"));
+
+        // out of range per type
+        assert!(thermal.create_reading(-300.0, 0).validate_ranges().is_err());
+        assert!(power.create_reading(101.0, 12.0, 1.0, 12.0, 0).validate_ranges().unwrap_err().contains("battery"));
+        assert!(power.create_reading(-1.0, 12.0, 1.0, 12.0, 0).validate_ranges().is_err());
+        assert!(attitude.create_reading(0.0, 270.0, 0.0, 0).validate_ranges().unwrap_err().contains("pitch"));
+        assert!(radiation.create_reading(-0.5, 5.0, 0).validate_ranges().is_err());
+    }
+
+    #[test]
+    fn compacted_attitude_keeps_angles_and_expands_back() {
+        let original = AttitudeSensor::new(3, "IMU").create_reading(3.0, -4.0, 12.0, 9);
+        let mut r = original.clone();
+        r.metadata.insert("note".into(), String::new());
+        r.compact();
+        assert_eq!((r.value1, r.value2, r.value3, r.value4), (3.0, -4.0, 12.0, 0.0));
+        assert!(r.description.is_empty() && r.metadata.is_empty());
+
+        // smaller on the wire in both body formats
+        for fmt in [SerializationFormat::Json, SerializationFormat::Bincode] {
+            assert!(fmt.encode(&r).unwrap().len() < fmt.encode(&original).unwrap().len(), "{fmt:?}");
+        }
+
+        let mut received: SensorReading = SerializationFormat::Bincode.decode(&SerializationFormat::Bincode.encode(&r).unwrap()).unwrap();
+        received.expand();
+        assert_eq!(received.attitude_error(), 13.0);
+        assert_eq!(received, original);
+
+        // power watts are rebuilt from V·A; radiation thresholds are never stripped
+        let mut p = PowerSensor::new(2, "Main Bus").create_reading(90.0, 12.0, 2.0, 24.0, 0);
+        p.compact();
+        p.expand();
+        assert_eq!(p.value4, 24.0);
+        let mut rad = RadiationSensor::new(7, "Payload Bay").create_reading(0.4, 5.0, 0);
+        let (warn, crit) = (rad.value3, rad.value4);
+        rad.compact();
+        assert_eq!((rad.value3, rad.value4), (warn, crit));
+    }
+
+    #[test]
+    fn attitude_error_wraps_angles_near_the_full_turn() {
+        let sensor = AttitudeSensor::new(3, "IMU");
+        let r = sensor.create_reading(359.0, -352.0, 0.5, 0);
+        assert_eq!((r.value1, r.value2, r.value3), (359.0, -352.0, 0.5), "raw angles kept");
+        let expected = (1.0f64 + 64.0 + 0.25).sqrt(); // -1°, 8°, 0.5°
+        assert!((r.value4 - expected).abs() < 1e-9, "{}", r.value4);
+        assert!((r.attitude_error() - expected).abs() < 1e-9);
+        assert_ne!(r.priority, Priority::Critical);
+        assert!(sensor.create_reading(360.0, -720.0, 0.0, 1).value4.abs() < 1e-9);
+        // ±180° is as far off as it gets either way
+        assert!((sensor.create_reading(-180.0, 0.0, 0.0, 2).value4 - 180.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quaternion_reading_gives_rotation_angle_and_roundtrips() {
+        let sensor = AttitudeSensor::new(3, "IMU");
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        // 90° about z: error 90° (Critical), yaw 90°
+        let r = sensor.create_reading_quat(h, 0.0, 0.0, h, 4);
+        assert!(r.is_quaternion());
+        assert!((r.attitude_error() - 90.0).abs() < 1e-9);
+        assert_eq!(r.priority, Priority::Critical);
+        let (roll, pitch, yaw) = r.euler_degrees();
+        assert!(roll.abs() < 1e-9 && pitch.abs() < 1e-9 && (yaw - 90.0).abs() < 1e-9);
+        assert!(r.validate_ranges().is_ok());
+        // q and -q are the same small rotation; identity is no error
+        assert!((sensor.create_reading_quat(-0.9998477, 0.0174524, 0.0, 0.0, 5).attitude_error() - 2.0).abs() < 1e-3);
+        assert_eq!(sensor.create_reading_quat(1.0, 0.0, 0.0, 0.0, 6).priority, Priority::Normal);
+        // straight up: the Euler form would be ambiguous, the quaternion is not
+        let up = sensor.create_reading_quat(h, 0.0, h, 0.0, 7);
+        assert!((up.euler_degrees().1 - 90.0).abs() < 1e-6);
+
+        // compact keeps all four components and the flag; both body formats roundtrip
+        let mut wire = r.clone();
+        wire.compact();
+        for fmt in [SerializationFormat::Json, SerializationFormat::Bincode] {
+            let mut got: SensorReading = fmt.decode(&fmt.encode(&wire).unwrap()).unwrap();
+            got.expand();
+            assert!(got.is_quaternion() && (got.attitude_error() - 90.0).abs() < 1e-9, "{fmt:?}");
+            if fmt == SerializationFormat::Bincode {
+                assert_eq!(got, r);
+            }
+        }
+        assert!(sensor.create_reading_quat(0.0, 0.0, 0.0, 0.0, 8).validate_ranges().is_err());
+    }
+
+    #[test]
+    fn next_retry_counts_up_with_doubling_backoff_then_stops() {
+        let cmd = Command::thermal_normal_operation(1).with_retry_policy(2, 100);
+        let deadline = cmd.deadline.unwrap();
+        assert_eq!(Command::power_normal_operation(1).max_retries(), DEFAULT_MAX_RETRIES);
+
+        let first = cmd.next_retry().unwrap();
+        assert_eq!(first.retry_count, 1);
+        assert_eq!(first.deadline.unwrap() - deadline, chrono::Duration::milliseconds(100));
+        let second = first.clone().next_retry().unwrap();
+        assert_eq!(second.retry_count, 2);
+        assert_eq!(second.deadline.unwrap() - deadline, chrono::Duration::milliseconds(300));
+        assert_eq!(second.command_id, first.command_id);
+        assert!(second.next_retry().is_none());
+
+        // no deadline stays none; a missing policy falls back to the defaults
+        let mut bare = Command::thermal_normal_operation(1);
+        bare.deadline = None;
+        bare.metadata.clear();
+        let again = bare.next_retry().unwrap();
+        assert_eq!((again.retry_count, again.deadline), (1, None));
+        assert_eq!(again.backoff(3), chrono::Duration::milliseconds(400));
+    }
+
+    #[test]
+    fn command_validate_checks_each_invariant() {
+        let stock = [
+            Command::thermal_normal_operation(1),
+            Command::thermal_emergency_response(1, 90.0),
+            Command::power_critical_response(2, 10.0),
+            Command::enter_safe_mode(vec![1]),
+            Command::initiate_recovery_mode(),
+            Command::set_threshold(1, SensorType::Thermal, "critical", 70.0),
+        ];
+        for cmd in &stock {
+            assert_eq!(cmd.validate(), Ok(()), "{:?}", cmd.command_type);
+        }
+
+        let mut late = Command::thermal_normal_operation(1);
+        late.deadline = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert!(late.validate().unwrap_err().contains("deadline"));
+
+        let mut wrong_prio = Command::thermal_emergency_response(1, 90.0);
+        wrong_prio.priority = Priority::Normal;
+        assert!(wrong_prio.validate().unwrap_err().contains("priority"));
+
+        let mut loopback = Command::thermal_normal_operation(1);
+        loopback.destination = Source::GroundControl;
+        assert!(loopback.validate().unwrap_err().contains("source and destination"));
+    }
+
+    #[test]
+    fn tampered_payload_size_is_rejected() {
+        let crypto = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW);
+        let mut pkt = heartbeat_with_seq(300);
+        pkt.header.payload_size_bytes += 1;
+        let sealed = crypto.seal_to_bytes(&pkt).unwrap();
+        assert_eq!(crypto.open_from_bytes(&sealed).unwrap_err(), CryptoError::PayloadSizeMismatch);
+    }
+
+    fn telemetry_batch(n: u64) -> CommunicationPacket {
+        let thermal = ThermalSensor::new(1, "CPU");
+        let readings = (0..n).map(|i| thermal.create_reading(60.0 + i as f64 * 0.1, i)).collect();
+        CommunicationPacket::new_telemetry(readings, Source::Satellite)
+    }
+
+    #[test]
+    fn bincode_roundtrip_interoperates_with_json_receiver() {
+        let tx = CryptoContext::new(1, [5u8; 32], DEFAULT_REPLAY_WINDOW)
+            .with_format(SerializationFormat::Bincode);
+        let rx = CryptoContext::new(1, [5u8; 32], DEFAULT_REPLAY_WINDOW);
+
+        let pkt = telemetry_batch(64);
+        let bin = tx.seal_to_bytes(&pkt).unwrap();
+        let json = rx.seal_to_bytes(&pkt).unwrap();
+        assert_eq!(bin[4], SerializationFormat::BINCODE_TAG);
+        assert!(bin.len() < json.len(), "bincode {} vs json {}", bin.len(), json.len());
+
+        let back = rx.open_from_bytes(&bin).unwrap();
+        assert_eq!(back, pkt);
+    }
+
+    #[test]
+    fn compressed_batch_is_smaller_and_roundtrips() {
+        let plain = CryptoContext::new(1, [6u8; 32], DEFAULT_REPLAY_WINDOW);
+        let packed = CryptoContext::new(1, [6u8; 32], DEFAULT_REPLAY_WINDOW).with_compression(true);
+
+        let pkt = telemetry_batch(256);
+        let small = packed.seal_to_bytes(&pkt).unwrap();
+        let big = plain.seal_to_bytes(&pkt).unwrap();
+        assert!(small.len() < big.len(), "compressed {} vs plain {}", small.len(), big.len());
+
+        let frame = EncryptedFrame::from_bytes(&small[4..]).unwrap();
+        assert!(frame.header.compressed);
+
+        // flipping the flag must break authentication (it is part of the AAD)
+        let mut flipped = frame.clone();
+        flipped.header.compressed = false;
+        assert!(plain.open_frame(&flipped).is_err());
+
+        assert_eq!(plain.open_frame(&frame).unwrap(), pkt);
+    }
+
+    #[test]
+    fn frame_reader_reassembles_byte_at_a_time() {
+        let crypto = CryptoContext::new(1, [4u8; 32], DEFAULT_REPLAY_WINDOW);
+        let mut stream = crypto.seal_to_bytes(&heartbeat_with_seq(300)).unwrap();
+        stream.extend(crypto.seal_to_bytes(&heartbeat_with_seq(301)).unwrap());
+
+        let mut reader = FrameReader::new();
+        let mut frames = Vec::new();
+        for b in &stream {
+            reader.push_bytes(std::slice::from_ref(b));
+            while let Some(frame) = reader.next_frame() {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(reader.buffered(), 0);
+        let seqs: Vec<u32> = frames
+            .iter()
+            .map(|f| crypto.open_frame(f).unwrap().header.sequence_number)
+            .collect();
+        assert_eq!(seqs, vec![300, 301]);
+    }
+
+    #[test]
+    fn frame_reader_rejects_oversized_length() {
+        let mut reader = FrameReader::new();
+        reader.push_bytes(&((MAX_PACKET_SIZE + 1) as u32).to_be_bytes()[..2]);
+        assert!(reader.next_frame().is_none());
+        reader.push_bytes(&((MAX_PACKET_SIZE + 1) as u32).to_be_bytes()[2..]);
+        reader.push_bytes(&[0u8; 16]);
+
+        assert!(reader.next_frame().is_none());
+        assert_eq!(reader.rejected(), 1);
+        assert_eq!(reader.buffered(), 0);
+    }
+
+    #[test]
+    fn zero_and_maximal_length_prefixes_are_rejected() {
+        let crypto = CryptoContext::new(1, [6u8; 32], 0);
+        assert_eq!(framed_len(&[0, 0]), Ok(None));
+        assert_eq!(framed_len(&[0, 0, 0, 7]), Ok(Some(11)));
+
+        assert_eq!(framed_len(&[0, 0, 0, 0]), Err(CryptoError::EmptyFrame));
+        assert_eq!(crypto.open_from_bytes(&[0, 0, 0, 0, 9, 9]).unwrap_err(), CryptoError::EmptyFrame);
+
+        let max = u32::MAX as usize;
+        assert_eq!(framed_len(&[0xff; 4]), Err(CryptoError::TooLarge { what: "frame", len: max }));
+        assert_eq!(crypto.open_from_bytes(&[0xff; 8]).unwrap_err(), CryptoError::TooLarge { what: "frame", len: max });
+
+        // a stream reader skips the empty frame and still yields the next one
+        let pkt = heartbeat_with_seq(400);
+        let mut reader = FrameReader::new();
+        reader.push_bytes(&[0, 0, 0, 0]);
+        reader.push_bytes(&crypto.seal_to_bytes(&pkt).unwrap());
+        let frame = reader.next_frame().expect("frame after the empty one");
+        assert_eq!(crypto.open_frame(&frame).unwrap().header.sequence_number, 400);
+        assert_eq!(reader.rejected(), 1);
+    }
+
+    #[test]
+    fn power_quality_checks_cross_field_consistency() {
+        let s = PowerSensor::new(2, "Main Bus");
+        assert_eq!(s.create_reading(90.0, 12.0, 2.0, 24.0, 0).quality, Quality::Good);
+        assert_eq!(s.create_reading(90.0, 12.0, 2.0, 30.0, 1).quality, Quality::Fair);
+        assert_eq!(s.create_reading(90.0, -12.0, 2.0, -24.0, 2).quality, Quality::Invalid);
+    }
+
+    #[test]
+    fn radiation_status_follows_dose_thresholds_and_roundtrips() {
+        let s = RadiationSensor::new(7, "Payload Bay");
+        let cases = [
+            (10.0, Status::Normal, Priority::Normal),
+            (50.0, Status::Warning, Priority::Important),
+            (120.0, Status::Critical, Priority::Critical),
+        ];
+        for (seq, (total, status, priority)) in cases.into_iter().enumerate() {
+            let r = s.create_reading(0.4, total, seq as u64);
+            assert_eq!((r.status, r.priority), (status, priority), "total={total}");
+        }
+
+        let crypto = CryptoContext::new(1, [9u8; 32], DEFAULT_REPLAY_WINDOW);
+        let pkt = CommunicationPacket::new_telemetry(vec![s.create_reading(0.4, 12.5, 3)], Source::Satellite);
+        let back = crypto.open_from_bytes(&crypto.seal_to_bytes(&pkt).unwrap()).unwrap();
+        assert_eq!(back, pkt);
+        match back.payload {
+            PacketPayload::TelemetryData(r) => assert_eq!(r[0].sensor_type, SensorType::Radiation),
+            other => panic!("unexpected payload {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,96 @@
+//! Packet-loss accounting from `ClearHeader.sequence_number` gaps.
+//!
+//! The sender numbers every packet from one global u32 counter, so a jump of more
+//! than one between consecutive packets from a source means packets were lost.
+//! Differences are taken with wrapping arithmetic so the u32 rollover is not a gap;
+//! a number at or behind the last one seen (reordered or duplicated) is not counted.
+
+use crate::Source;
+use std::collections::HashMap;
+
+/// One detected gap: `missing` packets between `after` and the packet just received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeqGap {
+    pub source: Source,
+    pub after: u32,
+    pub missing: u32,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SourceLoss {
+    last: Option<u32>,
+    received: u64,
+    lost: u64,
+    gaps: u64,
+}
+
+/// Running per-source loss counters.
+#[derive(Debug, Default)]
+pub struct LossTracker {
+    sources: HashMap<Source, SourceLoss>,
+}
+
+impl LossTracker {
+    /// Record one received packet; returns the gap in front of it, if any.
+    pub fn observe(&mut self, source: Source, seq: u32) -> Option<SeqGap> {
+        let s = self.sources.entry(source).or_default();
+        s.received += 1;
+        let Some(last) = s.last else {
+            s.last = Some(seq);
+            return None;
+        };
+        let step = seq.wrapping_sub(last);
+        if step == 0 || step > u32::MAX / 2 {
+            return None; // duplicate or late arrival: already accounted for
+        }
+        s.last = Some(seq);
+        if step == 1 {
+            return None;
+        }
+        s.lost += u64::from(step - 1);
+        s.gaps += 1;
+        Some(SeqGap { source, after: last, missing: step - 1 })
+    }
+
+    /// Packets inferred lost from `source`.
+    pub fn lost(&self, source: Source) -> u64 {
+        self.sources.get(&source).map_or(0, |s| s.lost)
+    }
+
+    /// Number of gaps seen from `source`.
+    pub fn gaps(&self, source: Source) -> u64 {
+        self.sources.get(&source).map_or(0, |s| s.gaps)
+    }
+
+    /// lost / (received + lost), in percent; 0 before anything arrived.
+    pub fn loss_pct(&self, source: Source) -> f64 {
+        match self.sources.get(&source) {
+            Some(s) if s.received + s.lost > 0 => s.lost as f64 * 100.0 / (s.received + s.lost) as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_missing_sequence_is_one_gap_of_one() {
+        let mut t = LossTracker::default();
+        let gaps: Vec<_> = [1, 2, 4].into_iter().filter_map(|s| t.observe(Source::Satellite, s)).collect();
+        assert_eq!(gaps, [SeqGap { source: Source::Satellite, after: 2, missing: 1 }]);
+        assert_eq!((t.gaps(Source::Satellite), t.lost(Source::Satellite)), (1, 1));
+        assert_eq!(t.loss_pct(Source::Satellite), 25.0);
+    }
+
+    #[test]
+    fn wraparound_and_reordering_are_not_loss() {
+        let mut t = LossTracker::default();
+        for seq in [u32::MAX - 1, u32::MAX, 0, 1, 0, 2] {
+            assert_eq!(t.observe(Source::Satellite, seq), None);
+        }
+        assert_eq!(t.lost(Source::Satellite), 0);
+        assert_eq!(t.observe(Source::Satellite, 5).map(|g| g.missing), Some(2));
+    }
+}